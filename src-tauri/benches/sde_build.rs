@@ -0,0 +1,241 @@
+//! Times the SDE index build, and loading the cache file it writes
+//! (`cargo bench --bench sde_build`).
+//!
+//! Runs against a generated archive shaped like the real SDE, or against
+//! the archive at `TELESCOPE_SDE_ARCHIVE` when set. `TELESCOPE_BENCH_RUNS`
+//! overrides the number of timed runs.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use zip::write::SimpleFileOptions;
+
+const DEFAULT_RUNS: usize = 5;
+const LOCALES: [&str; 8] = ["en", "de", "es", "fr", "ja", "ko", "ru", "zh"];
+const CATEGORIES: i64 = 50;
+const GROUPS: i64 = 1_500;
+const MARKET_GROUPS: i64 = 2_000;
+const TYPES: i64 = 50_000;
+const REGIONS: i64 = 100;
+const CONSTELLATIONS: i64 = 1_200;
+const SYSTEMS: i64 = 8_400;
+const STARGATES: i64 = 13_800;
+
+fn main() {
+    let (archive, generated) = match std::env::var_os("TELESCOPE_SDE_ARCHIVE") {
+        Some(path) => (PathBuf::from(path), false),
+        None => (write_fixture(), true),
+    };
+    let runs = std::env::var("TELESCOPE_BENCH_RUNS")
+        .ok()
+        .and_then(|runs| runs.parse().ok())
+        .unwrap_or(DEFAULT_RUNS);
+
+    // Warm the page cache so the first run isn't an outlier.
+    let rows = telescope_lib::build_index_for_bench(&archive).expect("index build failed");
+    let times = time_runs(runs, || {
+        telescope_lib::build_index_for_bench(&archive).expect("index build failed");
+    });
+    println!(
+        "sde_build: {} rows from {:?}, {} runs: {}",
+        rows,
+        archive.file_name().unwrap_or_default(),
+        runs,
+        summary(&times),
+    );
+
+    let cache = telescope_lib::encode_index_for_bench(&archive).expect("index build failed");
+    let times = time_runs(runs, || {
+        telescope_lib::decode_index_for_bench(&cache).expect("cache load failed");
+    });
+    println!(
+        "sde_load: {} byte cache, {} runs: {}",
+        cache.len(),
+        runs,
+        summary(&times),
+    );
+
+    if generated {
+        let _ = std::fs::remove_file(&archive);
+    }
+}
+
+/// Sorted run times.
+fn time_runs(runs: usize, mut run: impl FnMut()) -> Vec<Duration> {
+    let mut times: Vec<Duration> = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .collect();
+    times.sort_unstable();
+    times
+}
+
+fn summary(times: &[Duration]) -> String {
+    format!(
+        "min {:.1?}, median {:.1?}, max {:.1?}",
+        times[0],
+        times[times.len() / 2],
+        times[times.len() - 1],
+    )
+}
+
+/// A `name`/`description`-style object with every locale.
+fn localized(text: &str) -> String {
+    let fields: Vec<String> = LOCALES
+        .iter()
+        .map(|locale| format!("\"{}\": \"{} ({})\"", locale, text, locale))
+        .collect();
+    format!("{{{}}}", fields.join(", "))
+}
+
+fn write_fixture() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("telescope-sde-bench-{}.zip", std::process::id()));
+    let mut writer = zip::ZipWriter::new(File::create(&path).expect("create fixture"));
+    let mut member = |name: &str, lines: &mut dyn Iterator<Item = String>| {
+        writer
+            .start_file(name, SimpleFileOptions::default())
+            .expect("start member");
+        for line in lines {
+            writeln!(writer, "{}", line).expect("write member");
+        }
+    };
+
+    member(
+        "_sde.jsonl",
+        &mut std::iter::once(r#"{"_key": "sde", "buildNumber": 3012345}"#.to_string()),
+    );
+    member(
+        "categories.jsonl",
+        &mut (1..=CATEGORIES).map(|id| {
+            format!(
+                r#"{{"_key": {}, "name": {}, "published": true}}"#,
+                id,
+                localized(&format!("Category {}", id))
+            )
+        }),
+    );
+    member(
+        "groups.jsonl",
+        &mut (1..=GROUPS).map(|id| {
+            format!(
+                r#"{{"_key": {}, "categoryID": {}, "name": {}, "anchorable": false, "published": true}}"#,
+                id,
+                id % CATEGORIES + 1,
+                localized(&format!("Group {}", id))
+            )
+        }),
+    );
+    member(
+        "marketGroups.jsonl",
+        &mut (1..=MARKET_GROUPS).map(|id| {
+            // A tree: each group's parent has half its ID.
+            let parent = match id / 2 {
+                0 => "null".to_string(),
+                parent => parent.to_string(),
+            };
+            format!(
+                r#"{{"_key": {}, "name": {}, "parentGroupID": {}, "hasTypes": true}}"#,
+                id,
+                localized(&format!("Market Group {}", id)),
+                parent
+            )
+        }),
+    );
+    let description = "A ship hull of the kind the index never reads. ".repeat(6);
+    member(
+        "types.jsonl",
+        &mut (1..=TYPES).map(|id| {
+            format!(
+                r#"{{"_key": {}, "groupID": {}, "name": {}, "description": {}, "mass": 1067000.0, "volume": 27289.0, "portionSize": 1, "published": true, "metaGroupID": {}, "marketGroupID": {}}}"#,
+                id,
+                id % GROUPS + 1,
+                localized(&format!("Type {}", id)),
+                localized(&description),
+                id % 4 + 1,
+                id % MARKET_GROUPS + 1
+            )
+        }),
+    );
+    // Real types carry a few dozen attributes; only ships are kept.
+    let attributes: Vec<String> = [14, 13, 12, 1137, 102, 101, 552, 37, 1281, 600]
+        .iter()
+        .chain(&[3, 4, 9, 38, 48, 70, 109, 110, 111, 113, 161, 162, 263, 265])
+        .map(|id| format!(r#"{{"attributeID": {}, "value": 2.0}}"#, id))
+        .collect();
+    let attributes = attributes.join(", ");
+    member(
+        "typeDogma.jsonl",
+        &mut (1..=TYPES).map(|id| {
+            format!(
+                r#"{{"_key": {}, "dogmaAttributes": [{}], "dogmaEffects": [{{"effectID": 11, "isDefault": false}}]}}"#,
+                id, attributes
+            )
+        }),
+    );
+    member(
+        "typeBonus.jsonl",
+        &mut (1..=TYPES).step_by(10).map(|id| {
+            format!(
+                r#"{{"_key": {}, "roleBonuses": [{{"bonus": 80.0, "bonusText": {}, "importance": 1, "unitID": 105}}], "types": [{{"_key": 3329, "_value": [{{"bonus": 10.0, "bonusText": {}, "importance": 1, "unitID": 105}}]}}]}}"#,
+                id,
+                localized("reduction in <a href=showinfo:447>Warp Scrambler</a> capacitor need"),
+                localized("bonus to <a href=showinfo:526>Stasis Webifier</a> range")
+            )
+        }),
+    );
+    member(
+        "mapRegions.jsonl",
+        &mut (1..=REGIONS).map(|id| {
+            format!(
+                r#"{{"_key": {}, "name": {}}}"#,
+                10_000_000 + id,
+                localized(&format!("Region {}", id))
+            )
+        }),
+    );
+    member(
+        "mapConstellations.jsonl",
+        &mut (1..=CONSTELLATIONS).map(|id| {
+            format!(
+                r#"{{"_key": {}, "name": {}, "regionID": {}}}"#,
+                20_000_000 + id,
+                localized(&format!("Constellation {}", id)),
+                10_000_000 + id % REGIONS + 1
+            )
+        }),
+    );
+    member(
+        "mapSolarSystems.jsonl",
+        &mut (1..=SYSTEMS).map(|id| {
+            format!(
+                r#"{{"_key": {}, "name": {}, "constellationID": {}, "securityStatus": 0.5, "position": {{"x": {}.0, "y": 0.0, "z": -1.0e17}}, "planetIDs": [40000001, 40000002]}}"#,
+                30_000_000 + id,
+                localized(&format!("System {}", id)),
+                20_000_000 + id % CONSTELLATIONS + 1,
+                id
+            )
+        }),
+    );
+    member(
+        "mapStargates.jsonl",
+        &mut (1..=STARGATES).map(|id| {
+            let from = 30_000_000 + id % SYSTEMS + 1;
+            let to = 30_000_000 + (id * 7) % SYSTEMS + 1;
+            format!(
+                r#"{{"_key": {}, "solarSystemID": {}, "destination": {{"solarSystemID": {}, "stargateID": {}}}}}"#,
+                50_000_000 + id,
+                from,
+                to,
+                50_000_000 + id + 1
+            )
+        }),
+    );
+
+    writer.finish().expect("finish fixture");
+    path
+}
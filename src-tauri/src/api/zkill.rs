@@ -238,6 +238,7 @@ fn parse_top_systems(json: &serde_json::Value) -> Vec<SystemStats> {
                                 system_id,
                                 system_name,
                                 kills,
+                                jumps: None,
                             });
                         }
                     }
@@ -5,16 +5,20 @@
 use futures::StreamExt;
 use log::{debug, error, info, warn};
 use serde::Serialize;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 
 use crate::api::{create_client, esi, zkill};
+use crate::domain::dscan::SdeIndex;
 use crate::domain::lookup::{
     LookupEvent, LookupProgress, LookupTracker, BATCH_INTERVAL_MS, MAX_BATCH_SIZE,
 };
+use crate::domain::map::annotate_jumps;
 use crate::domain::threat::{calculate_threat_level, detect_pilot_flags};
-use crate::models::{CharacterInfo, PilotFlags, PilotIntel};
+use crate::models::{CharacterInfo, FleetLookupResult, PilotFlags, PilotIntel};
+use crate::sde;
 
 /// Cap on simultaneous per-pilot lookups so large locals don't burst
 /// hundreds of concurrent ESI/zKill requests into rate limits.
@@ -46,8 +50,15 @@ fn emit_batch(app: &AppHandle, pilots: Vec<PilotResult>, progress: LookupProgres
     let _ = app.emit("pilot-batch", PilotBatch { pilots, progress });
 }
 
+/// Look up every pilot in `names_text` (one per line). With an
+/// `origin_system_id`, each pilot's top systems carry their gate distance
+/// from that system.
 #[tauri::command]
-pub async fn lookup_pilots(app: AppHandle, names_text: String) -> Result<Vec<PilotIntel>, String> {
+pub async fn lookup_pilots(
+    app: AppHandle,
+    names_text: String,
+    origin_system_id: Option<i64>,
+) -> Result<Vec<PilotIntel>, String> {
     let client = create_client()?;
 
     let names: Vec<String> = names_text
@@ -68,6 +79,17 @@ pub async fn lookup_pilots(app: AppHandle, names_text: String) -> Result<Vec<Pil
     let id_map = esi::resolve_character_ids(&client, &names).await?;
     info!("Resolved {} character IDs", id_map.len());
 
+    let index = sde_index(&app).await;
+    let distances = match origin_system_id {
+        Some(origin) => jump_distances_from(&app, origin).await?,
+        None => None,
+    };
+    let annotate = |pilot: &mut PilotIntel| {
+        if let (Some(distances), Some(zkill)) = (&distances, pilot.zkill.as_mut()) {
+            annotate_jumps(&mut zkill.top_systems, distances);
+        }
+    };
+
     let mut results: Vec<PilotIntel> = Vec::with_capacity(total);
     let mut uncached: Vec<(usize, String, Option<i64>)> = Vec::new();
     let mut tracker = LookupTracker::new(total);
@@ -82,7 +104,8 @@ pub async fn lookup_pilots(app: AppHandle, names_text: String) -> Result<Vec<Pil
     for (i, name) in names.into_iter().enumerate() {
         let character_id = id_map.get(&name.to_lowercase()).copied();
 
-        if let Some(pilot) = try_from_cache(&app, character_id) {
+        if let Some(mut pilot) = try_from_cache(&app, &index, character_id) {
+            annotate(&mut pilot);
             tracker.apply(LookupEvent::CacheHit);
             queue.push_back(PilotResult {
                 pilot: pilot.clone(),
@@ -105,6 +128,7 @@ pub async fn lookup_pilots(app: AppHandle, names_text: String) -> Result<Vec<Pil
         |(launch_index, (i, name, character_id))| {
             let app = app.clone();
             let client = client.clone();
+            let index = Arc::clone(&index);
             async move {
                 // Pace launches: the n-th lookup may not start before
                 // n * DISPATCH_INTERVAL_MS after the stream began.
@@ -115,7 +139,7 @@ pub async fn lookup_pilots(app: AppHandle, names_text: String) -> Result<Vec<Pil
                         ),
                 )
                 .await;
-                let (pilot, _) = fetch_pilot_intel(&app, &client, name, character_id).await;
+                let (pilot, _) = fetch_pilot_intel(&app, &client, &index, name, character_id).await;
                 (i, pilot)
             }
         },
@@ -137,7 +161,8 @@ pub async fn lookup_pilots(app: AppHandle, names_text: String) -> Result<Vec<Pil
             }
             next = lookups.next(), if !stream_done => {
                 match next {
-                    Some((index, pilot)) => {
+                    Some((index, mut pilot)) => {
+                        annotate(&mut pilot);
                         tracker.apply(LookupEvent::Fetched);
                         queue.push_back(PilotResult {
                             pilot: pilot.clone(),
@@ -161,7 +186,85 @@ pub async fn lookup_pilots(app: AppHandle, names_text: String) -> Result<Vec<Pil
     Ok(results)
 }
 
-fn try_from_cache(app: &AppHandle, character_id: Option<i64>) -> Option<PilotIntel> {
+/// Parse a fleet-composition paste and run its pilots through
+/// [`lookup_pilots`]. Ships stay unclassified while the SDE index is not
+/// ready; the composition is still returned.
+#[tauri::command]
+pub async fn lookup_fleet(
+    app: AppHandle,
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    text: String,
+    expected_system: Option<String>,
+) -> Result<FleetLookupResult, String> {
+    let index = sde_service
+        .index(app_dir.inner().as_path())
+        .await?
+        .unwrap_or_default();
+
+    let composition = tokio::task::spawn_blocking(move || {
+        crate::domain::fleet::parse_fleet_text(&index, &text, expected_system.as_deref())
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+
+    info!(
+        "Fleet paste: {} members, {} out of position",
+        composition.members.len(),
+        composition.out_of_position.len()
+    );
+
+    let pilots = lookup_pilots(app, composition.pilot_names.join("\n"), None).await?;
+    Ok(FleetLookupResult {
+        composition,
+        pilots,
+    })
+}
+
+/// Gate distances from `origin` to every reachable system, or `None` while
+/// the SDE map isn't built yet.
+async fn jump_distances_from(
+    app: &AppHandle,
+    origin: i64,
+) -> Result<Option<HashMap<i64, usize>>, String> {
+    let (Some(app_dir), Some(sde_service)) = (
+        app.try_state::<std::path::PathBuf>(),
+        app.try_state::<sde::SdeService>(),
+    ) else {
+        return Ok(None);
+    };
+    let Some(map) = sde_service.map(app_dir.inner().as_path()).await? else {
+        return Ok(None);
+    };
+    Ok(Some(map.jump_distances(origin)))
+}
+
+/// The SDE index hull roles are read from; empty (pilots flagged by group
+/// ID alone) while it isn't built yet or fails to load.
+async fn sde_index(app: &AppHandle) -> Arc<SdeIndex> {
+    let (Some(app_dir), Some(sde_service)) = (
+        app.try_state::<std::path::PathBuf>(),
+        app.try_state::<sde::SdeService>(),
+    ) else {
+        return Arc::default();
+    };
+    match sde_service.index(app_dir.inner().as_path()).await {
+        Ok(index) => index.unwrap_or_default(),
+        Err(err) => {
+            warn!(
+                "SDE index unavailable, flagging pilots by group ID: {}",
+                err
+            );
+            Arc::default()
+        }
+    }
+}
+
+fn try_from_cache(
+    app: &AppHandle,
+    index: &SdeIndex,
+    character_id: Option<i64>,
+) -> Option<PilotIntel> {
     let id = character_id?;
 
     let character = esi::try_get_cached_character(app, id)?;
@@ -172,7 +275,7 @@ fn try_from_cache(app: &AppHandle, character_id: Option<i64>) -> Option<PilotInt
     // warm-cache path.
     let zkill_opt = Some(zkill_result);
     let threat_level = calculate_threat_level(&zkill_opt);
-    let flags = detect_pilot_flags(&zkill_opt);
+    let flags = detect_pilot_flags(&zkill_opt, index);
 
     Some(PilotIntel {
         character,
@@ -186,6 +289,7 @@ fn try_from_cache(app: &AppHandle, character_id: Option<i64>) -> Option<PilotInt
 async fn fetch_pilot_intel(
     app: &AppHandle,
     client: &reqwest::Client,
+    index: &SdeIndex,
     name: String,
     character_id: Option<i64>,
 ) -> (PilotIntel, bool) {
@@ -215,7 +319,7 @@ async fn fetch_pilot_intel(
                 };
 
                 let threat_level = calculate_threat_level(&zkill);
-                let flags = detect_pilot_flags(&zkill);
+                let flags = detect_pilot_flags(&zkill, index);
                 (
                     PilotIntel {
                         character,
@@ -0,0 +1,93 @@
+//! Solar-system commands: thin wrappers over the `domain::map` index kept
+//! by the `crate::sde` service.
+
+use crate::domain::jump_range;
+use crate::models::{
+    CapitalThreat, JumpRangeSystem, JumpShipClass, PilotIntel, Route, RoutePreference,
+    SolarSystemEntry,
+};
+use crate::sde;
+
+/// Results returned when the caller doesn't pass a limit.
+const DEFAULT_RESOLVE_LIMIT: usize = 10;
+
+/// Prefix/fuzzy search over solar-system names, best match first.
+#[tauri::command]
+pub async fn resolve_system(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SolarSystemEntry>, String> {
+    let map = sde_service
+        .map(app_dir.inner().as_path())
+        .await?
+        .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+
+    Ok(map.resolve(&query, limit.unwrap_or(DEFAULT_RESOLVE_LIMIT)))
+}
+
+/// Gate route between two systems. Errors when either system is unknown
+/// or every route is blocked by `avoid`.
+#[tauri::command]
+pub async fn plan_route(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    origin_id: i64,
+    destination_id: i64,
+    preference: Option<RoutePreference>,
+    avoid: Option<Vec<i64>>,
+) -> Result<Route, String> {
+    let map = sde_service
+        .map(app_dir.inner().as_path())
+        .await?
+        .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+
+    map.route(
+        origin_id,
+        destination_id,
+        preference.unwrap_or_default(),
+        avoid.as_deref().unwrap_or_default(),
+    )
+    .ok_or_else(|| "No route between these systems".to_string())
+}
+
+/// Systems a jump-drive hull can reach from `origin_id`. The calibration
+/// level defaults to V.
+#[tauri::command]
+pub async fn jump_range(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    origin_id: i64,
+    ship_class: JumpShipClass,
+    calibration_level: Option<u8>,
+) -> Result<Vec<JumpRangeSystem>, String> {
+    let map = sde_service
+        .map(app_dir.inner().as_path())
+        .await?
+        .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+
+    Ok(jump_range::reachable_systems(
+        &map,
+        origin_id,
+        ship_class,
+        calibration_level.unwrap_or(jump_range::MAX_SKILL_LEVEL),
+    ))
+}
+
+/// Capital and super pilots among `pilots` whose top systems are within
+/// jump range of the staging system.
+#[tauri::command]
+pub async fn capital_threats(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    staging_id: i64,
+    pilots: Vec<PilotIntel>,
+) -> Result<Vec<CapitalThreat>, String> {
+    let map = sde_service
+        .map(app_dir.inner().as_path())
+        .await?
+        .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+
+    Ok(jump_range::capital_threats(&map, staging_id, &pilots))
+}
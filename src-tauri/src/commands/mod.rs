@@ -2,11 +2,17 @@
 //! so `lib.rs`'s `generate_handler![commands::...]` entries keep resolving.
 
 pub mod lookup;
+pub mod map;
 pub mod overlay;
+pub mod paste;
+pub mod probe;
 pub mod sde;
 pub mod system;
 
 pub use lookup::*;
+pub use map::*;
 pub use overlay::*;
+pub use paste::*;
+pub use probe::*;
 pub use sde::*;
 pub use system::*;
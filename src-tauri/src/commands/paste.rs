@@ -0,0 +1,54 @@
+//! Unified paste ingestion: detect what a paste is with the pure
+//! `crate::domain::paste` classifier and dispatch it to the matching
+//! pipeline.
+
+use log::info;
+use tauri::AppHandle;
+
+use crate::commands::{lookup_fleet, lookup_pilots};
+use crate::domain::{dscan, overview, paste, probe};
+use crate::models::{IngestedPaste, PasteKind};
+use crate::sde;
+
+#[tauri::command]
+pub async fn ingest_paste(
+    app: AppHandle,
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    text: String,
+) -> Result<IngestedPaste, String> {
+    let text = paste::normalize_paste(&text);
+    let kind = paste::detect_paste_kind(&text);
+    info!("Ingesting paste detected as {:?}", kind);
+
+    match kind {
+        PasteKind::Local => {
+            let names = paste::local_pilot_names(&text).join("\n");
+            Ok(IngestedPaste::Local(lookup_pilots(app, names, None).await?))
+        }
+        PasteKind::Killmail => {
+            let names = paste::killmail_pilot_names(&text).join("\n");
+            Ok(IngestedPaste::Killmail(
+                lookup_pilots(app, names, None).await?,
+            ))
+        }
+        PasteKind::Fleet => Ok(IngestedPaste::Fleet(
+            lookup_fleet(app, app_dir, sde_service, text, None).await?,
+        )),
+        PasteKind::Probe => Ok(IngestedPaste::Probe(probe::parse_probe_text(&text))),
+        PasteKind::Dscan | PasteKind::Overview => {
+            let index = sde_service
+                .index(app_dir.inner().as_path())
+                .await?
+                .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+
+            tokio::task::spawn_blocking(move || match kind {
+                PasteKind::Dscan => IngestedPaste::Dscan(dscan::parse_dscan_text(&index, &text)),
+                _ => IngestedPaste::Overview(overview::parse_overview_text(&index, &text)),
+            })
+            .await
+            .map_err(|err| err.to_string())
+        }
+        PasteKind::Unknown => Ok(IngestedPaste::Unknown),
+    }
+}
@@ -0,0 +1,19 @@
+//! Probe scanner commands: thin wrappers over the pure
+//! `crate::domain::probe` parser.
+
+use crate::domain::probe;
+use crate::models::{ProbeDiff, ProbeParseResult};
+
+#[tauri::command]
+pub fn parse_probe_scan(text: String) -> ProbeParseResult {
+    probe::parse_probe_text(&text)
+}
+
+/// Parse two probe-scanner pastes and report new, gone and resolved
+/// signatures.
+#[tauri::command]
+pub fn diff_probe_scans(previous_text: String, current_text: String) -> ProbeDiff {
+    let previous = probe::parse_probe_text(&previous_text);
+    let current = probe::parse_probe_text(&current_text);
+    probe::diff_probe_scans(&previous, &current)
+}
@@ -1,22 +1,141 @@
 //! SDE index commands: thin wrappers over the `crate::sde` service (I/O)
 //! and `crate::domain::dscan` (pure parsing).
 
-use crate::models::{DscanParseResult, SdeStatus};
-use crate::sde;
+use std::sync::Arc;
 
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::models::{
+    DscanDiff, DscanParseResult, FleetAnalysis, OverviewParseResult, SdeCoverage, SdeProgress,
+    SdeSchedule, SdeStatus, TypeDetails, TypeSearchHit,
+};
+use crate::{doctrines, sde};
+
+/// Forward SDE update progress to the frontend as "sde-progress" events.
+fn progress_listener(app: AppHandle) -> sde::ProgressListener {
+    Arc::new(move |progress: &SdeProgress| {
+        let _ = app.emit("sde-progress", progress.clone());
+    })
+}
+
+/// Run the background SDE checks for the app's lifetime, emitting
+/// "sde-updated" with the new status whenever one installs a build.
+pub fn start_sde_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let app_dir = app.state::<std::path::PathBuf>();
+        let sde_service = app.state::<sde::SdeService>();
+        sde::run_sde_scheduler(
+            app_dir.inner().as_path(),
+            sde_service.inner(),
+            progress_listener(app.clone()),
+            |status| {
+                let _ = app.emit("sde-updated", status.clone());
+            },
+        )
+        .await;
+    });
+}
+
+/// Check for a new SDE build; `force` checks even when the last check is
+/// more recent than the check interval.
 #[tauri::command]
 pub async fn ensure_sde_index(
+    app: AppHandle,
     app_dir: tauri::State<'_, std::path::PathBuf>,
     sde_service: tauri::State<'_, sde::SdeService>,
+    force: Option<bool>,
 ) -> Result<SdeStatus, String> {
-    sde::ensure_sde_index(app_dir.inner().as_path(), sde_service.inner()).await
+    sde::ensure_sde_index(
+        app_dir.inner().as_path(),
+        sde_service.inner(),
+        progress_listener(app),
+        force.unwrap_or(false),
+    )
+    .await
 }
 
 #[tauri::command]
 pub async fn get_sde_status(
     app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+) -> Result<SdeStatus, String> {
+    sde::get_sde_status(app_dir.inner().as_path(), sde_service.inner()).await
+}
+
+#[tauri::command]
+pub async fn get_sde_coverage(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+) -> Result<SdeCoverage, String> {
+    sde::load_coverage(app_dir.inner().as_path())
+}
+
+/// Save a new category/group coverage and rebuild the index with it, from
+/// the members kept from the cached build when possible.
+#[tauri::command]
+pub async fn set_sde_coverage(
+    app: AppHandle,
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    coverage: SdeCoverage,
+) -> Result<SdeStatus, String> {
+    sde::save_coverage(app_dir.inner().as_path(), coverage)?;
+    sde::ensure_sde_index(
+        app_dir.inner().as_path(),
+        sde_service.inner(),
+        progress_listener(app),
+        false,
+    )
+    .await
+}
+
+/// Build the index from a local SDE archive instead of downloading one.
+#[tauri::command]
+pub async fn import_sde_archive(
+    app: AppHandle,
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    path: String,
 ) -> Result<SdeStatus, String> {
-    sde::get_sde_status(app_dir.inner().as_path()).await
+    sde::import_sde_archive(
+        app_dir.inner().as_path(),
+        sde_service.inner(),
+        std::path::Path::new(&path),
+        progress_listener(app),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn get_sde_sources(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+) -> Result<Vec<String>, String> {
+    sde::load_sde_sources(app_dir.inner().as_path())
+}
+
+/// Save the SDE archive URLs to try, in fallback order.
+#[tauri::command]
+pub async fn set_sde_sources(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sources: Vec<String>,
+) -> Result<Vec<String>, String> {
+    sde::save_sde_sources(app_dir.inner().as_path(), sources)
+}
+
+#[tauri::command]
+pub async fn get_sde_schedule(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+) -> Result<SdeSchedule, String> {
+    Ok(sde::load_sde_schedule_or_default(app_dir.inner().as_path()))
+}
+
+/// Set how often the background scheduler checks for a new SDE build.
+#[tauri::command]
+pub async fn set_sde_check_interval(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    hours: u32,
+) -> Result<SdeSchedule, String> {
+    sde::save_sde_check_interval(app_dir.inner().as_path(), sde_service.inner(), hours)
 }
 
 #[tauri::command]
@@ -24,14 +143,135 @@ pub async fn parse_dscan(
     app_dir: tauri::State<'_, std::path::PathBuf>,
     sde_service: tauri::State<'_, sde::SdeService>,
     text: String,
+    language: Option<String>,
 ) -> Result<DscanParseResult, String> {
     let index = sde_service
         .index(app_dir.inner().as_path())
         .await?
         .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+    let map = sde_service.map(app_dir.inner().as_path()).await?;
 
     // Large pastes are pure CPU work; keep them off the async runtime.
-    tokio::task::spawn_blocking(move || crate::domain::dscan::parse_dscan_text(&index, &text))
+    tokio::task::spawn_blocking(move || {
+        use crate::domain::dscan::{parse_dscan_text_in, DEFAULT_LANGUAGE};
+        let mut result = parse_dscan_text_in(
+            &index,
+            &text,
+            language.as_deref().unwrap_or(DEFAULT_LANGUAGE),
+        );
+        result.detected_system =
+            map.and_then(|map| crate::domain::locate::detect_system(&map, &result.entries));
+        result
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Parse two d-scan pastes and report what changed between them.
+#[tauri::command]
+pub async fn diff_dscan(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    previous_text: String,
+    current_text: String,
+) -> Result<DscanDiff, String> {
+    let index = sde_service
+        .index(app_dir.inner().as_path())
+        .await?
+        .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+
+    tokio::task::spawn_blocking(move || {
+        use crate::domain::dscan::{diff_dscan, parse_dscan_text};
+        let previous = parse_dscan_text(&index, &previous_text);
+        let current = parse_dscan_text(&index, &current_text);
+        diff_dscan(&previous, &current)
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Parse a d-scan paste and match its ships against the user's doctrine
+/// library (re-read on every call so file edits apply immediately).
+#[tauri::command]
+pub async fn analyze_dscan_fleet(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    text: String,
+) -> Result<FleetAnalysis, String> {
+    let index = sde_service
+        .index(app_dir.inner().as_path())
+        .await?
+        .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+    let dir = app_dir.inner().clone();
+
+    tokio::task::spawn_blocking(move || {
+        let library = doctrines::load_doctrines(&dir)?;
+        let result = crate::domain::dscan::parse_dscan_text(&index, &text);
+        Ok(crate::domain::doctrine::analyze_fleet(
+            &library,
+            &result.entries,
+        ))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Parse an overview paste, whatever columns the player's overview shows.
+#[tauri::command]
+pub async fn parse_overview(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    text: String,
+) -> Result<OverviewParseResult, String> {
+    let index = sde_service
+        .index(app_dir.inner().as_path())
+        .await?
+        .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+
+    tokio::task::spawn_blocking(move || crate::domain::overview::parse_overview_text(&index, &text))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// An indexed type with its hull attributes (slots, speed, signature,
+/// tackle/e-war bonuses) when it's a ship; `None` for unknown types.
+#[tauri::command]
+pub async fn get_type_details(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    type_id: i64,
+) -> Result<Option<TypeDetails>, String> {
+    let index = sde_service
+        .index(app_dir.inner().as_path())
+        .await?
+        .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+    Ok(index.type_details(type_id))
+}
+
+/// Ranked type name autocomplete, optionally limited to some categories
+/// and groups; empty until the SDE index is built.
+#[tauri::command]
+pub async fn search_types(
+    app_dir: tauri::State<'_, std::path::PathBuf>,
+    sde_service: tauri::State<'_, sde::SdeService>,
+    query: String,
+    category_ids: Option<Vec<i64>>,
+    group_ids: Option<Vec<i64>>,
+    limit: Option<usize>,
+) -> Result<Vec<TypeSearchHit>, String> {
+    use crate::domain::type_search::{TypeSearchFilter, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT};
+
+    let Some(index) = sde_service.index(app_dir.inner().as_path()).await? else {
+        return Ok(Vec::new());
+    };
+    let filter = TypeSearchFilter {
+        category_ids: category_ids.unwrap_or_default(),
+        group_ids: group_ids.unwrap_or_default(),
+    };
+    let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT);
+
+    // The first search after a load builds the search structures.
+    tokio::task::spawn_blocking(move || index.search_types(&query, &filter, limit))
         .await
         .map_err(|err| err.to_string())
 }
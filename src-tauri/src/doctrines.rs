@@ -0,0 +1,24 @@
+//! Doctrine library persistence: a user-editable `doctrines.json` in the
+//! app data dir. Matching lives in `crate::domain::doctrine`.
+
+use std::fs;
+use std::path::Path;
+
+use crate::domain::doctrine::{default_doctrines, Doctrine};
+
+const DOCTRINES_FILE: &str = "doctrines.json";
+
+/// Read the doctrine library, writing the default library out first if
+/// the file doesn't exist yet so there is something to edit.
+pub fn load_doctrines(app_dir: &Path) -> Result<Vec<Doctrine>, String> {
+    let path = app_dir.join(DOCTRINES_FILE);
+    if !path.exists() {
+        let doctrines = default_doctrines();
+        let json = serde_json::to_string_pretty(&doctrines).map_err(|err| err.to_string())?;
+        fs::write(&path, json).map_err(|err| err.to_string())?;
+        return Ok(doctrines);
+    }
+
+    let json = fs::read(&path).map_err(|err| err.to_string())?;
+    serde_json::from_slice(&json).map_err(|err| format!("Invalid {}: {}", DOCTRINES_FILE, err))
+}
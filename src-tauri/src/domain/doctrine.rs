@@ -0,0 +1,299 @@
+//! Doctrine / fleet-composition recognition over classified d-scan rows.
+//! A doctrine is a named set of components (hull types or groups) that
+//! should each make up at least a minimum share of the ships on scan.
+//! The library itself is loaded from a user-editable file by
+//! `crate::doctrines`; matching here is pure.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::dscan::{ship_class, ShipClass};
+use crate::models::{DoctrineMatch, DscanEntry, FleetAnalysis, HullRole};
+
+/// Below this confidence a doctrine is listed but not reported as likely.
+pub const MIN_LIKELY_CONFIDENCE: f64 = 0.4;
+
+/// Logistics Cruiser, Logistics Frigate, Force Auxiliary.
+const LOGISTICS_GROUPS: &[i64] = &[832, 1527, 1538];
+/// Interceptor, Interdictor, Heavy Interdiction Cruiser.
+const TACKLE_GROUPS: &[i64] = &[831, 541, 894];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Doctrine {
+    pub name: String,
+    pub components: Vec<DoctrineComponent>,
+}
+
+/// One slice of a doctrine. A ship matches when its type ID or group ID is
+/// listed; `label` is only there to keep the file readable.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DoctrineComponent {
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub type_ids: Vec<i64>,
+    #[serde(default)]
+    pub group_ids: Vec<i64>,
+    /// Minimum share of all ships on scan, 0.0..=1.0.
+    pub min_ratio: f64,
+}
+
+impl DoctrineComponent {
+    fn matches(&self, entry: &DscanEntry) -> bool {
+        entry
+            .type_id
+            .is_some_and(|type_id| self.type_ids.contains(&type_id))
+            || entry
+                .group_id
+                .is_some_and(|group_id| self.group_ids.contains(&group_id))
+    }
+}
+
+/// Seed library written out the first time the doctrine file is missing.
+pub fn default_doctrines() -> Vec<Doctrine> {
+    let component =
+        |label: &str, type_ids: &[i64], group_ids: &[i64], min_ratio| DoctrineComponent {
+            label: label.to_string(),
+            type_ids: type_ids.to_vec(),
+            group_ids: group_ids.to_vec(),
+            min_ratio,
+        };
+    let logistics = || component("Logistics", &[], LOGISTICS_GROUPS, 0.1);
+
+    vec![
+        Doctrine {
+            name: "Muninn fleet".to_string(),
+            components: vec![component("Muninn", &[12015], &[], 0.5), logistics()],
+        },
+        Doctrine {
+            name: "Eagle fleet".to_string(),
+            components: vec![component("Eagle", &[12011], &[], 0.5), logistics()],
+        },
+        Doctrine {
+            name: "Cerberus fleet".to_string(),
+            components: vec![component("Cerberus", &[11993], &[], 0.5), logistics()],
+        },
+        Doctrine {
+            name: "Bomber wing".to_string(),
+            components: vec![component("Stealth Bomber", &[], &[834], 0.7)],
+        },
+        Doctrine {
+            name: "Interceptor gang".to_string(),
+            components: vec![component("Interceptor", &[], &[831], 0.6)],
+        },
+        Doctrine {
+            name: "Capital escalation".to_string(),
+            components: vec![
+                component("Dreadnought", &[], &[485, 4594], 0.3),
+                component("Force Auxiliary", &[], &[1538], 0.1),
+            ],
+        },
+    ]
+}
+
+/// Match the ship rows of a parsed d-scan against a doctrine library.
+///
+/// Per doctrine, each component scores `min(1, share / min_ratio)` and the
+/// doctrine's fulfilment is the mean of those scores. Confidence is the
+/// fulfilment weighted by coverage (the share of ships that belong to any
+/// component), so a big mixed fleet with a few Muninns doesn't read as a
+/// Muninn fleet.
+pub fn analyze_fleet(doctrines: &[Doctrine], entries: &[DscanEntry]) -> FleetAnalysis {
+    let ships: Vec<&DscanEntry> = entries.iter().filter(|entry| entry.is_ship).collect();
+    let in_groups = |groups: &[i64]| {
+        ships
+            .iter()
+            .filter(|entry| entry.group_id.is_some_and(|id| groups.contains(&id)))
+            .count()
+    };
+    let capital_count = ships
+        .iter()
+        .filter(|entry| match entry.tech_level {
+            // Only ships with a hull profile have a tech level; the rest
+            // fall back to their group.
+            Some(_) => entry.hull_roles.contains(&HullRole::Capital),
+            None => entry
+                .group_id
+                .is_some_and(|id| ship_class(id) == ShipClass::Capital),
+        })
+        .count();
+
+    let mut matches: Vec<DoctrineMatch> = doctrines
+        .iter()
+        .filter_map(|doctrine| match_doctrine(doctrine, &ships))
+        .collect();
+    matches.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    FleetAnalysis {
+        likely_doctrine: matches
+            .first()
+            .filter(|top| top.confidence >= MIN_LIKELY_CONFIDENCE)
+            .cloned(),
+        matches,
+        ship_count: ships.len(),
+        logistics_count: in_groups(LOGISTICS_GROUPS),
+        tackle_count: in_groups(TACKLE_GROUPS),
+        capital_count,
+        has_capitals: capital_count > 0,
+    }
+}
+
+fn match_doctrine(doctrine: &Doctrine, ships: &[&DscanEntry]) -> Option<DoctrineMatch> {
+    if ships.is_empty() || doctrine.components.is_empty() {
+        return None;
+    }
+
+    let total = ships.len() as f64;
+    let fulfilment = doctrine
+        .components
+        .iter()
+        .map(|component| {
+            let share = ships.iter().filter(|ship| component.matches(ship)).count() as f64 / total;
+            if component.min_ratio <= 0.0 {
+                1.0
+            } else {
+                (share / component.min_ratio).min(1.0)
+            }
+        })
+        .sum::<f64>()
+        / doctrine.components.len() as f64;
+
+    let matched_ships = ships
+        .iter()
+        .filter(|ship| doctrine.components.iter().any(|c| c.matches(ship)))
+        .count();
+    if matched_ships == 0 {
+        return None;
+    }
+
+    Some(DoctrineMatch {
+        name: doctrine.name.clone(),
+        confidence: fulfilment * (matched_ships as f64 / total),
+        matched_ships,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DscanRange, ScanObjectKind, TechLevel};
+
+    fn ship(type_id: i64, group_id: i64) -> DscanEntry {
+        DscanEntry {
+            type_id: Some(type_id),
+            name: "Pilot".to_string(),
+            type_name: "Ship".to_string(),
+            display_type_name: None,
+            distance: None,
+            distance_m: None,
+            range: DscanRange::Unknown,
+            group_id: Some(group_id),
+            group_name: None,
+            category_id: Some(6),
+            category_name: Some("Ship".to_string()),
+            kind: ScanObjectKind::Ship,
+            is_ship: true,
+            hull_roles: Vec::new(),
+            tech_level: None,
+        }
+    }
+
+    fn fleet(counts: &[(i64, i64, usize)]) -> Vec<DscanEntry> {
+        counts
+            .iter()
+            .flat_map(|&(type_id, group_id, count)| {
+                (0..count).map(move |_| ship(type_id, group_id))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn muninn_fleet_with_logistics_is_recognized() {
+        // 20 Muninns (HAC), 4 Scimitars (logi), 2 Sabres (dictor).
+        let entries = fleet(&[(12015, 358, 20), (11978, 832, 4), (22456, 541, 2)]);
+        let analysis = analyze_fleet(&default_doctrines(), &entries);
+
+        let likely = analysis.likely_doctrine.expect("a likely doctrine");
+        assert_eq!(likely.name, "Muninn fleet");
+        assert_eq!(likely.matched_ships, 24);
+        assert!(likely.confidence > 0.9);
+        assert_eq!(analysis.ship_count, 26);
+        assert_eq!(analysis.logistics_count, 4);
+        assert_eq!(analysis.tackle_count, 2);
+        assert!(!analysis.has_capitals);
+    }
+
+    #[test]
+    fn mixed_fleet_has_no_likely_doctrine() {
+        // A handful of Muninns in a large unrelated fleet.
+        let entries = fleet(&[(12015, 358, 3), (587, 25, 30)]);
+        let analysis = analyze_fleet(&default_doctrines(), &entries);
+        assert!(analysis.likely_doctrine.is_none());
+        assert_eq!(analysis.matches[0].name, "Muninn fleet");
+    }
+
+    #[test]
+    fn bomber_wing_matches_by_group() {
+        let entries = fleet(&[(12038, 834, 12), (11172, 830, 1)]);
+        let analysis = analyze_fleet(&default_doctrines(), &entries);
+        assert_eq!(
+            analysis.likely_doctrine.map(|m| m.name).as_deref(),
+            Some("Bomber wing")
+        );
+    }
+
+    #[test]
+    fn capitals_are_counted() {
+        let entries = fleet(&[(19720, 485, 5), (37604, 1538, 2), (23913, 659, 1)]);
+        let analysis = analyze_fleet(&default_doctrines(), &entries);
+        assert_eq!(analysis.capital_count, 8);
+        assert!(analysis.has_capitals);
+        assert_eq!(analysis.logistics_count, 2);
+        assert_eq!(
+            analysis.likely_doctrine.map(|m| m.name).as_deref(),
+            Some("Capital escalation")
+        );
+    }
+
+    #[test]
+    fn capitals_are_recognized_by_hull_role() {
+        // A capital in a group the fallback table doesn't know, and a
+        // freighter-like hull in the dreadnought group without the role.
+        let mut new_capital = ship(90001, 9001);
+        new_capital.hull_roles = vec![HullRole::Capital];
+        new_capital.tech_level = Some(TechLevel::Tech1);
+        let mut hauler = ship(90002, 485);
+        hauler.hull_roles = vec![HullRole::Hauler];
+        hauler.tech_level = Some(TechLevel::Tech1);
+
+        let analysis = analyze_fleet(&default_doctrines(), &[new_capital, hauler]);
+        assert_eq!(analysis.capital_count, 1);
+    }
+
+    #[test]
+    fn non_ship_rows_and_empty_scans_are_ignored() {
+        let mut structure = ship(35832, 1657);
+        structure.is_ship = false;
+        let analysis = analyze_fleet(&default_doctrines(), &[structure]);
+        assert_eq!(analysis.ship_count, 0);
+        assert!(analysis.matches.is_empty());
+        assert!(analysis.likely_doctrine.is_none());
+    }
+
+    #[test]
+    fn doctrine_file_format_round_trips() {
+        let json = serde_json::to_string(&default_doctrines()).unwrap();
+        let parsed: Vec<Doctrine> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, default_doctrines());
+
+        // Hand-written entries may omit the optional lists.
+        let parsed: Vec<Doctrine> = serde_json::from_str(
+            r#"[{"name": "Sabres", "components": [{"type_ids": [22456], "min_ratio": 0.5}]}]"#,
+        )
+        .unwrap();
+        assert!(parsed[0].components[0].group_ids.is_empty());
+    }
+}
@@ -0,0 +1,269 @@
+//! Pure extraction of ship hull attributes from SDE dogma data: the slot
+//! layout and navigation stats from `typeDogma`, and tackle/e-war hull
+//! bonuses classified from the English `typeBonus` text.
+
+use std::collections::HashMap;
+
+use crate::models::{HullBonus, HullBonusKind, ShipAttributes, ShipSlots};
+
+const HIGH_SLOTS: i64 = 14;
+const MID_SLOTS: i64 = 13;
+const LOW_SLOTS: i64 = 12;
+const RIG_SLOTS: i64 = 1137;
+const TURRET_HARDPOINTS: i64 = 102;
+const LAUNCHER_HARDPOINTS: i64 = 101;
+const SIGNATURE_RADIUS: i64 = 552;
+const MAX_VELOCITY: i64 = 37;
+const BASE_WARP_SPEED: i64 = 1281;
+const WARP_SPEED_MULTIPLIER: i64 = 600;
+const JUMP_DRIVE_RANGE: i64 = 867;
+
+/// The dogma attribute IDs `ship_attributes` reads; the builder keeps only
+/// these from each ship's attribute list.
+pub const SHIP_ATTRIBUTE_IDS: [i64; 11] = [
+    HIGH_SLOTS,
+    MID_SLOTS,
+    LOW_SLOTS,
+    RIG_SLOTS,
+    TURRET_HARDPOINTS,
+    LAUNCHER_HARDPOINTS,
+    SIGNATURE_RADIUS,
+    MAX_VELOCITY,
+    BASE_WARP_SPEED,
+    WARP_SPEED_MULTIPLIER,
+    JUMP_DRIVE_RANGE,
+];
+
+/// `typeBonus` unit IDs the bonus amount is shown with.
+const UNIT_PERCENT: i64 = 105;
+const UNIT_MULTIPLIER: i64 = 104;
+
+/// Phrases (lowercase, markup stripped) that mark a bonus as affecting
+/// each kind of module. Checked in order; one text can match several.
+const BONUS_PHRASES: [(HullBonusKind, &[&str]); 9] = [
+    (HullBonusKind::WarpScrambler, &["warp scrambler"]),
+    (HullBonusKind::WarpDisruptor, &["warp disruptor"]),
+    (
+        HullBonusKind::Interdiction,
+        &["warp disruption field", "interdiction sphere"],
+    ),
+    (
+        HullBonusKind::StasisWebifier,
+        &["stasis webifier", "stasis grappler"],
+    ),
+    (HullBonusKind::Ecm, &["ecm", "burst jammer"]),
+    (HullBonusKind::SensorDampener, &["sensor dampener"]),
+    (
+        HullBonusKind::WeaponDisruptor,
+        &[
+            "weapon disruptor",
+            "tracking disruptor",
+            "guidance disruptor",
+        ],
+    ),
+    (HullBonusKind::TargetPainter, &["target painter"]),
+    (
+        HullBonusKind::CapacitorWarfare,
+        &["energy neutralizer", "energy nosferatu"],
+    ),
+];
+
+const COVERT_OPS_CLOAK: &str = "covert ops cloaking device";
+
+/// One line of a hull's `typeBonus` entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BonusLine {
+    pub amount: Option<f64>,
+    pub unit_id: Option<i64>,
+    /// English text, possibly with `<a href=showinfo:...>` links.
+    pub text: String,
+    /// A per-skill-level bonus rather than a role bonus.
+    pub per_level: bool,
+}
+
+/// Hull attributes from a ship's dogma attribute values and bonus lines.
+/// Missing attributes read as 0, except the two warp speed factors (1).
+pub fn ship_attributes(attributes: &HashMap<i64, f64>, bonuses: &[BonusLine]) -> ShipAttributes {
+    let value = |attribute_id: i64| attributes.get(&attribute_id).copied().unwrap_or_default();
+    let count = |attribute_id: i64| value(attribute_id).round().clamp(0.0, 255.0) as u8;
+    let factor = |attribute_id: i64| attributes.get(&attribute_id).copied().unwrap_or(1.0);
+    let texts: Vec<String> = bonuses
+        .iter()
+        .map(|bonus| strip_markup(&bonus.text))
+        .collect();
+
+    ShipAttributes {
+        slots: ShipSlots {
+            high: count(HIGH_SLOTS),
+            mid: count(MID_SLOTS),
+            low: count(LOW_SLOTS),
+            rig: count(RIG_SLOTS),
+            turret: count(TURRET_HARDPOINTS),
+            launcher: count(LAUNCHER_HARDPOINTS),
+        },
+        signature_radius: value(SIGNATURE_RADIUS),
+        max_velocity: value(MAX_VELOCITY),
+        warp_speed: factor(BASE_WARP_SPEED) * factor(WARP_SPEED_MULTIPLIER),
+        covert_ops_cloak: texts
+            .iter()
+            .any(|text| text.to_lowercase().contains(COVERT_OPS_CLOAK)),
+        jump_drive: value(JUMP_DRIVE_RANGE) > 0.0,
+        bonuses: bonuses
+            .iter()
+            .zip(texts)
+            .filter_map(|(bonus, text)| {
+                let kinds = bonus_kinds(&text);
+                (!kinds.is_empty()).then(|| HullBonus {
+                    kinds,
+                    text: bonus_text(bonus.amount, bonus.unit_id, &text),
+                    per_level: bonus.per_level,
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Drop `<...>` tags, keeping the text between them.
+pub fn strip_markup(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for char in text.chars() {
+        match char {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => plain.push(char),
+            _ => {}
+        }
+    }
+    plain.trim().to_string()
+}
+
+/// The tackle and e-war modules a plain-text bonus affects.
+pub fn bonus_kinds(text: &str) -> Vec<HullBonusKind> {
+    // Pad words with spaces so "ecm" can't match inside another word.
+    let words: String = text
+        .to_lowercase()
+        .split(|char: char| !char.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .fold(String::from(" "), |words, word| words + word + " ");
+
+    BONUS_PHRASES
+        .iter()
+        .filter(|(_, phrases)| {
+            phrases
+                .iter()
+                .any(|phrase| words.contains(&format!(" {} ", phrase)))
+        })
+        .map(|(kind, _)| *kind)
+        .collect()
+}
+
+/// A bonus as the game shows it, e.g. "10% bonus to Stasis Webifier
+/// maximum velocity penalty".
+fn bonus_text(amount: Option<f64>, unit_id: Option<i64>, text: &str) -> String {
+    match (amount, unit_id) {
+        (Some(amount), Some(UNIT_PERCENT)) => format!("{}% {}", amount, text),
+        (Some(amount), Some(UNIT_MULTIPLIER)) => format!("{}x {}", amount, text),
+        (Some(amount), _) => format!("{} {}", amount, text),
+        (None, _) => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(amount: Option<f64>, text: &str, per_level: bool) -> BonusLine {
+        BonusLine {
+            amount,
+            unit_id: amount.map(|_| UNIT_PERCENT),
+            text: text.to_string(),
+            per_level,
+        }
+    }
+
+    #[test]
+    fn attributes_are_read_from_dogma() {
+        // Sabre, trimmed.
+        let attributes = HashMap::from([
+            (HIGH_SLOTS, 7.0),
+            (MID_SLOTS, 2.0),
+            (LOW_SLOTS, 2.0),
+            (RIG_SLOTS, 2.0),
+            (TURRET_HARDPOINTS, 4.0),
+            (LAUNCHER_HARDPOINTS, 1.0),
+            (SIGNATURE_RADIUS, 54.0),
+            (MAX_VELOCITY, 345.0),
+            (BASE_WARP_SPEED, 1.0),
+            (WARP_SPEED_MULTIPLIER, 4.5),
+        ]);
+        let ship = ship_attributes(&attributes, &[]);
+        assert_eq!(
+            ship.slots,
+            ShipSlots {
+                high: 7,
+                mid: 2,
+                low: 2,
+                rig: 2,
+                turret: 4,
+                launcher: 1,
+            }
+        );
+        assert_eq!(ship.signature_radius, 54.0);
+        assert_eq!(ship.warp_speed, 4.5);
+        assert!(!ship.jump_drive && !ship.covert_ops_cloak);
+
+        let ship = ship_attributes(&HashMap::from([(JUMP_DRIVE_RANGE, 3.5)]), &[]);
+        assert!(ship.jump_drive);
+        assert_eq!(ship.slots, ShipSlots::default());
+    }
+
+    #[test]
+    fn tackle_and_ewar_bonuses_are_classified() {
+        let bonuses = [
+            line(
+                Some(10.0),
+                "bonus to <a href=showinfo:526>Stasis Webifier</a> maximum velocity penalty",
+                true,
+            ),
+            line(Some(5.0), "bonus to Small Projectile Turret damage", true),
+            line(
+                Some(80.0),
+                "reduction in <a href=showinfo:3242>Warp Scrambler</a> and <a href=showinfo:3244>Warp Disruptor</a> capacitor need",
+                false,
+            ),
+            line(None, "Can fit <a href=showinfo:11578>Covert Ops Cloaking Device</a>", false),
+            line(Some(20.0), "bonus to ECM target jammer strength", true),
+            line(Some(5.0), "bonus to kinetic damage (Necromancy)", true),
+        ];
+        let ship = ship_attributes(&HashMap::new(), &bonuses);
+
+        assert!(ship.covert_ops_cloak);
+        let kinds: Vec<&[HullBonusKind]> = ship
+            .bonuses
+            .iter()
+            .map(|bonus| bonus.kinds.as_slice())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                &[HullBonusKind::StasisWebifier][..],
+                &[HullBonusKind::WarpScrambler, HullBonusKind::WarpDisruptor],
+                &[HullBonusKind::Ecm],
+            ]
+        );
+        assert_eq!(
+            ship.bonuses[0].text,
+            "10% bonus to Stasis Webifier maximum velocity penalty"
+        );
+        assert!(ship.bonuses[0].per_level && !ship.bonuses[1].per_level);
+    }
+
+    #[test]
+    fn markup_is_stripped() {
+        assert_eq!(
+            strip_markup(" <a href=showinfo:3244>Warp Disruptor</a> range "),
+            "Warp Disruptor range"
+        );
+    }
+}
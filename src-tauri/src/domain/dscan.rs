@@ -2,16 +2,64 @@
 //! turns raw d-scan paste text into classified rows. All I/O (downloading,
 //! building and caching the index) lives in `crate::sde`.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::OnceLock;
 
-use crate::models::{DscanEntry, DscanParseResult, ScanTypeIndexEntry};
+use crate::domain::type_search::{TypeSearchFilter, TypeSearchIndex};
+use crate::models::{
+    DscanCount, DscanCountChange, DscanDiff, DscanEntry, DscanMove, DscanParseResult, DscanRange,
+    DscanRangeCounts, DscanSummary, HullProfile, HullRole, HullRoleTotals, HullSize,
+    ScanObjectKind, ScanTypeIndexEntry, ShipAttributes, ShipClassTotals, TechLevel,
+    TechLevelTotals, TypeDetails, TypeSearchHit,
+};
 
-const SHIP_CATEGORY_ID: i64 = 6;
+pub const SHIP_CATEGORY_ID: i64 = 6;
+
+pub(crate) mod categories {
+    pub const CELESTIAL: i64 = 2;
+    pub const STATION: i64 = 3;
+    pub const CHARGE: i64 = 8;
+    pub const DRONE: i64 = 18;
+    pub const DEPLOYABLE: i64 = 22;
+    pub const STARBASE: i64 = 23;
+    pub const SOVEREIGNTY_STRUCTURE: i64 = 40;
+    pub const ORBITAL: i64 = 46;
+    pub const STRUCTURE: i64 = 65;
+    pub const FIGHTER: i64 = 87;
+}
+
+mod groups {
+    pub const WRECK: i64 = 186;
+    pub const SCANNER_PROBE: i64 = 479;
+    pub const SURVEY_PROBE: i64 = 492;
+    pub const INTERDICTION_PROBE: i64 = 548;
+}
+
+/// SDE locale code used when no display language is requested.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+const METRES_PER_KM: f64 = 1_000.0;
+const METRES_PER_AU: f64 = 149_597_870_700.0;
+
+/// Objects closer than this are treated as sharing the scanner's grid.
+const ON_GRID_MAX_M: f64 = 7_500.0 * METRES_PER_KM;
+/// Off-grid objects up to this distance count as "in warp-bubble range".
+const WARP_BUBBLE_MAX_M: f64 = METRES_PER_AU;
+
+/// Unit spellings across client languages, lowercased. The d-scan window
+/// localizes the unit suffix along with the number format.
+const METRE_UNITS: &[&str] = &["m", "м", "米"];
+const KILOMETRE_UNITS: &[&str] = &["km", "км", "千米", "公里"];
+const AU_UNITS: &[&str] = &["au", "ua", "ae", "а.е.", "天文单位"];
 
 #[derive(Debug, Default)]
 pub struct SdeIndex {
     by_type_id: HashMap<i64, ScanTypeIndexEntry>,
     name_to_type_id: HashMap<String, i64>,
+    ship_attributes: HashMap<i64, ShipAttributes>,
+    hull_profiles: HashMap<i64, HullProfile>,
+    /// Built on the first search, so loads that only parse scans skip it.
+    search: OnceLock<TypeSearchIndex>,
 }
 
 impl SdeIndex {
@@ -19,6 +67,15 @@ impl SdeIndex {
         let mut by_type_id = HashMap::with_capacity(entries.len());
         let mut name_to_type_id = HashMap::with_capacity(entries.len());
 
+        // Localized names are only a fallback: an English name always wins a
+        // collision, whichever order the entries arrive in.
+        for entry in &entries {
+            for name in entry.type_names.values() {
+                name_to_type_id
+                    .entry(normalize_name(name))
+                    .or_insert(entry.type_id);
+            }
+        }
         for entry in entries {
             name_to_type_id.insert(normalize_name(&entry.type_name), entry.type_id);
             by_type_id.insert(entry.type_id, entry);
@@ -27,11 +84,68 @@ impl SdeIndex {
         SdeIndex {
             by_type_id,
             name_to_type_id,
+            ship_attributes: HashMap::new(),
+            hull_profiles: HashMap::new(),
+            search: OnceLock::new(),
         }
     }
 
-    /// Classify a scan row by type ID, falling back to a name lookup.
-    fn classify(&self, type_id: Option<i64>, type_name: &str) -> Option<&ScanTypeIndexEntry> {
+    /// Attach hull attributes, keyed by ship type ID.
+    pub fn with_ship_attributes(mut self, attributes: Vec<(i64, ShipAttributes)>) -> Self {
+        self.ship_attributes = attributes.into_iter().collect();
+        self
+    }
+
+    /// Attach hull roles and tech levels, keyed by ship type ID.
+    pub fn with_hull_profiles(mut self, profiles: Vec<(i64, HullProfile)>) -> Self {
+        self.hull_profiles = profiles.into_iter().collect();
+        self
+    }
+
+    pub fn hull_profile(&self, type_id: i64) -> Option<&HullProfile> {
+        self.hull_profiles.get(&type_id)
+    }
+
+    /// An indexed type with its hull attributes and profile when it's a
+    /// ship.
+    pub fn type_details(&self, type_id: i64) -> Option<TypeDetails> {
+        Some(TypeDetails {
+            entry: self.by_type_id.get(&type_id)?.clone(),
+            ship: self.ship_attributes.get(&type_id).cloned(),
+            hull: self.hull_profiles.get(&type_id).cloned(),
+        })
+    }
+
+    /// Ranked autocomplete over type names in every SDE language.
+    pub fn search_types(
+        &self,
+        query: &str,
+        filter: &TypeSearchFilter,
+        limit: usize,
+    ) -> Vec<TypeSearchHit> {
+        self.search
+            .get_or_init(|| TypeSearchIndex::new(self.by_type_id.values()))
+            .search(query, filter, limit)
+            .into_iter()
+            .filter_map(|found| {
+                let entry = self.by_type_id.get(&found.type_id)?;
+                Some(TypeSearchHit {
+                    type_id: entry.type_id,
+                    type_name: entry.type_name.clone(),
+                    group_id: entry.group_id,
+                    group_name: entry.group_name.clone(),
+                    category_id: entry.category_id,
+                    category_name: entry.category_name.clone(),
+                    matched_name: found.matched_name.to_string(),
+                    match_kind: found.kind,
+                })
+            })
+            .collect()
+    }
+
+    /// Classify a scan row by type ID, falling back to a name lookup in
+    /// any SDE language.
+    pub fn classify(&self, type_id: Option<i64>, type_name: &str) -> Option<&ScanTypeIndexEntry> {
         type_id.and_then(|id| self.by_type_id.get(&id)).or_else(|| {
             self.name_to_type_id
                 .get(&normalize_name(type_name))
@@ -42,12 +156,23 @@ impl SdeIndex {
 
 /// Parse raw d-scan text against an in-memory index. Pure function.
 pub fn parse_dscan_text(index: &SdeIndex, text: &str) -> DscanParseResult {
+    parse_dscan_text_in(index, text, DEFAULT_LANGUAGE)
+}
+
+/// Like [`parse_dscan_text`], with type and group names reported in the
+/// given SDE language ("de", "fr", "ja", "ru", "zh", ...). Names missing
+/// for that language fall back to English.
+pub fn parse_dscan_text_in(index: &SdeIndex, text: &str, language: &str) -> DscanParseResult {
     let mut entries = Vec::new();
     let mut ship_count = 0;
+    let mut ships_by_range = DscanRangeCounts::default();
+    let mut summary = SummaryBuilder::default();
 
     for raw_line in text.lines() {
-        let line = raw_line.trim();
-        if line.is_empty() {
+        // Keep tabs: a row pasted without a type ID starts with an empty
+        // first column that a plain trim would shift away.
+        let line = raw_line.trim_matches(|char: char| char.is_whitespace() && char != '\t');
+        if line.trim().is_empty() {
             continue;
         }
 
@@ -63,6 +188,8 @@ pub fn parse_dscan_text(index: &SdeIndex, text: &str) -> DscanParseResult {
             .get(3)
             .map(|value| value.to_string())
             .filter(|value| !value.is_empty() && value != "-");
+        let distance_m = distance.as_deref().and_then(parse_distance);
+        let range = range_bucket(distance_m);
 
         let classification = index.classify(type_id, &type_name);
 
@@ -72,24 +199,472 @@ pub fn parse_dscan_text(index: &SdeIndex, text: &str) -> DscanParseResult {
 
         if is_ship {
             ship_count += 1;
+            let bucket = match range {
+                DscanRange::OnGrid => &mut ships_by_range.on_grid,
+                DscanRange::WarpBubble => &mut ships_by_range.warp_bubble,
+                DscanRange::InSystem => &mut ships_by_range.in_system,
+                DscanRange::Unknown => &mut ships_by_range.unknown,
+            };
+            *bucket += 1;
         }
 
+        let hull = classification
+            .filter(|_| is_ship)
+            .and_then(|entry| index.hull_profile(entry.type_id));
+
+        let display_type_name =
+            classification.map(|entry| localized(&entry.type_names, language, &entry.type_name));
+        let group_name =
+            classification.map(|entry| localized(&entry.group_names, language, &entry.group_name));
+
+        summary.add(
+            type_id,
+            &type_name,
+            classification,
+            display_type_name.as_deref(),
+            group_name.as_deref(),
+            hull,
+        );
+
+        let kind = classification.map_or(ScanObjectKind::Other, object_kind);
+
         entries.push(DscanEntry {
             type_id,
             name,
             type_name,
+            display_type_name,
             distance,
-            group_name: classification.map(|entry| entry.group_name.clone()),
+            distance_m,
+            range,
+            group_id: classification.map(|entry| entry.group_id),
+            group_name,
+            category_id: classification.map(|entry| entry.category_id),
             category_name: classification.map(|entry| entry.category_name.clone()),
+            kind,
             is_ship,
+            hull_roles: hull.map(|hull| hull.roles.clone()).unwrap_or_default(),
+            tech_level: hull.map(|hull| hull.tech_level),
         });
     }
 
     DscanParseResult {
         total_rows: entries.len(),
         ship_count,
+        ships_by_range,
+        summary: summary.finish(),
         entries,
+        detected_system: None,
+    }
+}
+
+/// Coarse kind of a classified type, from its category (and group for
+/// the wreck and probe special cases).
+pub fn object_kind(entry: &ScanTypeIndexEntry) -> ScanObjectKind {
+    match (entry.category_id, entry.group_id) {
+        (SHIP_CATEGORY_ID, _) => ScanObjectKind::Ship,
+        (categories::DRONE, _) => ScanObjectKind::Drone,
+        (categories::FIGHTER, _) => ScanObjectKind::Fighter,
+        (categories::DEPLOYABLE, _) => ScanObjectKind::Deployable,
+        (
+            categories::STATION
+            | categories::STARBASE
+            | categories::SOVEREIGNTY_STRUCTURE
+            | categories::ORBITAL
+            | categories::STRUCTURE,
+            _,
+        ) => ScanObjectKind::Structure,
+        (categories::CELESTIAL, groups::WRECK) => ScanObjectKind::Wreck,
+        (categories::CELESTIAL, _) => ScanObjectKind::Celestial,
+        (
+            categories::CHARGE,
+            groups::SCANNER_PROBE | groups::SURVEY_PROBE | groups::INTERDICTION_PROBE,
+        ) => ScanObjectKind::Probe,
+        _ => ScanObjectKind::Other,
+    }
+}
+
+fn localized(names: &BTreeMap<String, String>, language: &str, english: &str) -> String {
+    names
+        .get(language)
+        .filter(|name| !name.is_empty())
+        .map_or(english, String::as_str)
+        .to_string()
+}
+
+/// Hull size class used for the summary's ship-class totals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShipClass {
+    Frigate,
+    Destroyer,
+    Cruiser,
+    Battlecruiser,
+    Battleship,
+    Capital,
+    Industrial,
+    Other,
+}
+
+/// The hull size class of a ship with an SDE hull profile. Freighters and
+/// hulls too small to be combat-sized that haul or mine count as
+/// industrials.
+pub fn hull_class(hull: &HullProfile) -> ShipClass {
+    let has_role = |role| hull.roles.contains(&role);
+    match hull.size {
+        HullSize::Frigate => ShipClass::Frigate,
+        HullSize::Destroyer => ShipClass::Destroyer,
+        HullSize::Cruiser => ShipClass::Cruiser,
+        HullSize::Battlecruiser => ShipClass::Battlecruiser,
+        HullSize::Battleship => ShipClass::Battleship,
+        HullSize::Capital if has_role(HullRole::Hauler) => ShipClass::Industrial,
+        HullSize::Capital => ShipClass::Capital,
+        HullSize::Other if has_role(HullRole::Hauler) || has_role(HullRole::Mining) => {
+            ShipClass::Industrial
+        }
+        HullSize::Other => ShipClass::Other,
+    }
+}
+
+/// Map a ship group ID to its hull size class, for ships without a hull
+/// profile. Groups not listed (shuttles, capsules, corvettes,
+/// special-edition hulls) fall into `Other`.
+pub fn ship_class(group_id: i64) -> ShipClass {
+    match group_id {
+        // Frigate, Assault Frigate, Covert Ops, Interceptor, Stealth Bomber,
+        // Electronic Attack Ship, Expedition Frigate, Logistics Frigate.
+        25 | 324 | 830 | 831 | 834 | 893 | 1283 | 1527 => ShipClass::Frigate,
+        // Destroyer, Interdictor, Tactical Destroyer, Command Destroyer.
+        420 | 541 | 1305 | 1534 => ShipClass::Destroyer,
+        // Cruiser, Heavy Assault Cruiser, Logistics, Force Recon, Heavy
+        // Interdiction Cruiser, Combat Recon, Strategic Cruiser, Flag Cruiser.
+        26 | 358 | 832 | 833 | 894 | 906 | 963 | 1972 => ShipClass::Cruiser,
+        // Combat Battlecruiser, Command Ship, Attack Battlecruiser.
+        419 | 540 | 1201 => ShipClass::Battlecruiser,
+        // Battleship, Black Ops, Marauder.
+        27 | 898 | 900 => ShipClass::Battleship,
+        // Titan, Dreadnought, Carrier, Supercarrier, Capital Industrial,
+        // Force Auxiliary, Lancer Dreadnought.
+        30 | 485 | 547 | 659 | 883 | 1538 | 4594 => ShipClass::Capital,
+        // Hauler, Deep Space Transport, Mining Barge, Freighter, Exhumer,
+        // Jump Freighter, Industrial Command Ship, Blockade Runner.
+        28 | 380 | 463 | 513 | 543 | 902 | 941 | 1202 => ShipClass::Industrial,
+        _ => ShipClass::Other,
+    }
+}
+
+/// Accumulates the composition summary while rows are parsed. Classified
+/// rows are keyed by the index's type ID and display-language name;
+/// unclassified rows by whatever the paste said.
+#[derive(Default)]
+struct SummaryBuilder {
+    by_type: HashMap<(Option<i64>, String), usize>,
+    by_group: HashMap<(i64, String), usize>,
+    by_category: HashMap<(i64, String), usize>,
+    ship_classes: ShipClassTotals,
+    hull_roles: HullRoleTotals,
+    tech_levels: TechLevelTotals,
+}
+
+impl SummaryBuilder {
+    fn add(
+        &mut self,
+        type_id: Option<i64>,
+        type_name: &str,
+        classification: Option<&ScanTypeIndexEntry>,
+        display_type_name: Option<&str>,
+        display_group_name: Option<&str>,
+        hull: Option<&HullProfile>,
+    ) {
+        let Some(entry) = classification else {
+            *self
+                .by_type
+                .entry((type_id, type_name.to_string()))
+                .or_default() += 1;
+            return;
+        };
+
+        let type_name = display_type_name.unwrap_or(&entry.type_name);
+        let group_name = display_group_name.unwrap_or(&entry.group_name);
+        *self
+            .by_type
+            .entry((Some(entry.type_id), type_name.to_string()))
+            .or_default() += 1;
+        *self
+            .by_group
+            .entry((entry.group_id, group_name.to_string()))
+            .or_default() += 1;
+        *self
+            .by_category
+            .entry((entry.category_id, entry.category_name.clone()))
+            .or_default() += 1;
+
+        if entry.category_id == SHIP_CATEGORY_ID {
+            let totals = &mut self.ship_classes;
+            let class = hull.map_or_else(|| ship_class(entry.group_id), hull_class);
+            let total = match class {
+                ShipClass::Frigate => &mut totals.frigates,
+                ShipClass::Destroyer => &mut totals.destroyers,
+                ShipClass::Cruiser => &mut totals.cruisers,
+                ShipClass::Battlecruiser => &mut totals.battlecruisers,
+                ShipClass::Battleship => &mut totals.battleships,
+                ShipClass::Capital => &mut totals.capitals,
+                ShipClass::Industrial => &mut totals.industrials,
+                ShipClass::Other => &mut totals.other,
+            };
+            *total += 1;
+        }
+
+        let Some(hull) = hull else {
+            return;
+        };
+        let roles = &mut self.hull_roles;
+        for role in &hull.roles {
+            let total = match role {
+                HullRole::Tackle => &mut roles.tackle,
+                HullRole::Interdiction => &mut roles.interdiction,
+                HullRole::Logistics => &mut roles.logistics,
+                HullRole::Ewar => &mut roles.ewar,
+                HullRole::Dps => &mut roles.dps,
+                HullRole::Hauler => &mut roles.hauler,
+                HullRole::Mining => &mut roles.mining,
+                HullRole::Covert => &mut roles.covert,
+                HullRole::Capital => &mut roles.capital,
+                HullRole::Super => &mut roles.super_capital,
+            };
+            *total += 1;
+        }
+        let levels = &mut self.tech_levels;
+        let total = match hull.tech_level {
+            TechLevel::Tech1 => &mut levels.t1,
+            TechLevel::Tech2 => &mut levels.t2,
+            TechLevel::Tech3 => &mut levels.t3,
+            TechLevel::Faction => &mut levels.faction,
+        };
+        *total += 1;
+    }
+
+    fn finish(self) -> DscanSummary {
+        DscanSummary {
+            by_type: sorted_counts(self.by_type),
+            by_group: sorted_counts(
+                self.by_group
+                    .into_iter()
+                    .map(|((id, name), count)| ((Some(id), name), count)),
+            ),
+            by_category: sorted_counts(
+                self.by_category
+                    .into_iter()
+                    .map(|((id, name), count)| ((Some(id), name), count)),
+            ),
+            ship_classes: self.ship_classes,
+            hull_roles: self.hull_roles,
+            tech_levels: self.tech_levels,
+        }
+    }
+}
+
+fn sorted_counts(
+    counts: impl IntoIterator<Item = ((Option<i64>, String), usize)>,
+) -> Vec<DscanCount> {
+    let mut counts: Vec<DscanCount> = counts
+        .into_iter()
+        .map(|((id, name), count)| DscanCount { id, name, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts
+}
+
+/// Parse a d-scan distance column ("2,3 km", "12 345 km", "1.234 AU") into
+/// metres. Handles every client locale: comma or dot decimals, and comma,
+/// dot, space, non-breaking or thin-space thousands separators.
+pub fn parse_distance(raw: &str) -> Option<f64> {
+    let raw = raw.trim();
+    let split = raw
+        .char_indices()
+        .find(|(_, char)| !(char.is_ascii_digit() || is_number_separator(*char)))
+        .map(|(index, _)| index)
+        .unwrap_or(raw.len());
+    let (number, unit) = raw.split_at(split);
+    let unit = unit.trim().to_lowercase();
+
+    let multiplier = if METRE_UNITS.contains(&unit.as_str()) {
+        1.0
+    } else if KILOMETRE_UNITS.contains(&unit.as_str()) {
+        METRES_PER_KM
+    } else if AU_UNITS.contains(&unit.as_str()) {
+        METRES_PER_AU
+    } else {
+        return None;
+    };
+
+    // D-scan range tops out around 14 AU, so an AU value never carries a
+    // thousands separator: a lone separator there is always a decimal.
+    let value = parse_localized_number(number, multiplier == METRES_PER_AU)?;
+    Some(value * multiplier)
+}
+
+/// `char::is_whitespace` covers the non-breaking (U+00A0), thin (U+2009)
+/// and narrow non-breaking (U+202F) spaces some locales group digits with.
+fn is_number_separator(char: char) -> bool {
+    matches!(char, ',' | '.') || char.is_whitespace()
+}
+
+/// Normalize a localized number to a plain `f64`. Whitespace is always a
+/// thousands separator. With both `,` and `.` present the last one is the
+/// decimal mark. A single lone `,` or `.` followed by exactly three digits
+/// reads as thousands ("12,345 km"), otherwise as a decimal ("2,3 km"),
+/// unless `lone_is_decimal` forces the decimal reading.
+pub fn parse_localized_number(number: &str, lone_is_decimal: bool) -> Option<f64> {
+    let digits: String = number
+        .chars()
+        .filter(|char| !char.is_whitespace())
+        .collect();
+    if digits.is_empty() {
+        return None;
+    }
+
+    let decimal_at = match (digits.rfind(','), digits.rfind('.')) {
+        (Some(comma), Some(dot)) => Some(comma.max(dot)),
+        (Some(at), None) | (None, Some(at)) => {
+            let mark = digits.as_bytes()[at];
+            let lone = digits.bytes().filter(|byte| *byte == mark).count() == 1;
+            let thousands_shaped = digits.len() - at - 1 == 3;
+            (lone && (lone_is_decimal || !thousands_shaped)).then_some(at)
+        }
+        (None, None) => None,
+    };
+
+    let normalized: String = digits
+        .char_indices()
+        .filter_map(|(index, char)| match char {
+            ',' | '.' if Some(index) == decimal_at => Some('.'),
+            ',' | '.' => None,
+            _ => Some(char),
+        })
+        .collect();
+    normalized.parse().ok()
+}
+
+/// Bucket a parsed distance for the on-grid / in-range summaries.
+pub fn range_bucket(distance_m: Option<f64>) -> DscanRange {
+    match distance_m {
+        None => DscanRange::Unknown,
+        Some(metres) if metres <= ON_GRID_MAX_M => DscanRange::OnGrid,
+        Some(metres) if metres <= WARP_BUBBLE_MAX_M => DscanRange::WarpBubble,
+        Some(_) => DscanRange::InSystem,
+    }
+}
+
+/// Compare two parsed scans of the same spot: per-type arrivals and
+/// departures, per-group count changes, and objects that changed distance
+/// bucket. Pure function over the already-built summaries and rows.
+pub fn diff_dscan(previous: &DscanParseResult, current: &DscanParseResult) -> DscanDiff {
+    let type_changes = count_changes(&previous.summary.by_type, &current.summary.by_type);
+    let (mut arrivals, mut departures): (Vec<_>, Vec<_>) = type_changes
+        .into_iter()
+        .partition(|change| change.delta > 0);
+    arrivals.sort_by(|a, b| b.delta.cmp(&a.delta).then_with(|| a.name.cmp(&b.name)));
+    departures.sort_by(|a, b| a.delta.cmp(&b.delta).then_with(|| a.name.cmp(&b.name)));
+
+    let mut group_changes = count_changes(&previous.summary.by_group, &current.summary.by_group);
+    group_changes.sort_by(|a, b| {
+        b.delta
+            .abs()
+            .cmp(&a.delta.abs())
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    DscanDiff {
+        arrivals,
+        departures,
+        group_changes,
+        moved: moved_objects(&previous.entries, &current.entries),
+    }
+}
+
+fn count_changes(before: &[DscanCount], after: &[DscanCount]) -> Vec<DscanCountChange> {
+    let mut counts: HashMap<(Option<i64>, &str), (usize, usize)> = HashMap::new();
+    for count in before {
+        counts.entry((count.id, &count.name)).or_default().0 += count.count;
+    }
+    for count in after {
+        counts.entry((count.id, &count.name)).or_default().1 += count.count;
+    }
+
+    counts
+        .into_iter()
+        .filter(|(_, (before, after))| before != after)
+        .map(|((id, name), (before, after))| DscanCountChange {
+            id,
+            name: name.to_string(),
+            before,
+            after,
+            delta: after as i64 - before as i64,
+        })
+        .collect()
+}
+
+/// Rows are identified by (type, object name). For each identity, ranges
+/// seen in both scans cancel out; the leftovers are paired up in bucket
+/// order as moves, and anything unpaired is an arrival or departure that
+/// the count changes already report.
+fn moved_objects(previous: &[DscanEntry], current: &[DscanEntry]) -> Vec<DscanMove> {
+    type Identity<'a> = (Option<i64>, &'a str, &'a str);
+    let mut ranges: HashMap<Identity, (Vec<DscanRange>, Vec<DscanRange>)> = HashMap::new();
+    for entry in previous {
+        let key = (entry.type_id, entry.type_name.as_str(), entry.name.as_str());
+        ranges.entry(key).or_default().0.push(entry.range);
+    }
+    for entry in current {
+        let key = (entry.type_id, entry.type_name.as_str(), entry.name.as_str());
+        ranges.entry(key).or_default().1.push(entry.range);
+    }
+
+    let mut moved = Vec::new();
+    for ((type_id, type_name, name), (mut from, mut to)) in ranges {
+        from.sort();
+        to.sort();
+        let (from, to) = without_common(&from, &to);
+        moved.extend(from.into_iter().zip(to).map(|(from, to)| DscanMove {
+            type_id,
+            type_name: type_name.to_string(),
+            name: name.to_string(),
+            from,
+            to,
+        }));
+    }
+
+    moved.sort_by(|a, b| {
+        a.type_name
+            .cmp(&b.type_name)
+            .then_with(|| a.name.cmp(&b.name))
+            .then_with(|| a.from.cmp(&b.from))
+    });
+    moved
+}
+
+/// Multiset difference of two sorted slices, both ways.
+fn without_common(a: &[DscanRange], b: &[DscanRange]) -> (Vec<DscanRange>, Vec<DscanRange>) {
+    let (mut only_a, mut only_b) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Equal => {
+                i += 1;
+                j += 1;
+            }
+            std::cmp::Ordering::Less => {
+                only_a.push(a[i]);
+                i += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                only_b.push(b[j]);
+                j += 1;
+            }
+        }
     }
+    only_a.extend_from_slice(&a[i..]);
+    only_b.extend_from_slice(&b[j..]);
+    (only_a, only_b)
 }
 
 fn normalize_name(name: &str) -> String {
@@ -103,6 +678,7 @@ mod tests {
     fn entry(
         type_id: i64,
         type_name: &str,
+        group_id: i64,
         group_name: &str,
         category_id: i64,
         category_name: &str,
@@ -110,17 +686,43 @@ mod tests {
         ScanTypeIndexEntry {
             type_id,
             type_name: type_name.to_string(),
-            group_id: 1,
+            type_names: BTreeMap::new(),
+            group_id,
             group_name: group_name.to_string(),
+            group_names: BTreeMap::new(),
             category_id,
             category_name: category_name.to_string(),
         }
     }
 
+    fn names(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(language, name)| (language.to_string(), name.to_string()))
+            .collect()
+    }
+
+    fn localized_index() -> SdeIndex {
+        let mut sabre = entry(22456, "Sabre", 541, "Interdictor", 6, "Ship");
+        sabre.type_names = names(&[("en", "Sabre"), ("ru", "Сейбр"), ("zh", "军刀级")]);
+        sabre.group_names = names(&[("en", "Interdictor"), ("de", "Interdictor-Zerstörer")]);
+        let mut rifter = entry(587, "Rifter", 25, "Frigate", 6, "Ship");
+        rifter.type_names = names(&[("en", "Rifter"), ("de", "Rifter")]);
+        // A localized name that collides with another type's English name
+        // must not shadow it.
+        rifter
+            .type_names
+            .insert("fr".to_string(), "Sabre".to_string());
+        SdeIndex::from_entries(vec![rifter, sabre])
+    }
+
     fn test_index() -> SdeIndex {
         SdeIndex::from_entries(vec![
-            entry(587, "Rifter", "Frigate", 6, "Ship"),
-            entry(35832, "Astrahus", "Citadel", 65, "Structure"),
+            entry(587, "Rifter", 25, "Frigate", 6, "Ship"),
+            entry(22456, "Sabre", 541, "Interdictor", 6, "Ship"),
+            entry(12011, "Eagle", 358, "Heavy Assault Cruiser", 6, "Ship"),
+            entry(23913, "Nyx", 659, "Supercarrier", 6, "Ship"),
+            entry(35832, "Astrahus", 1657, "Citadel", 65, "Structure"),
         ])
     }
 
@@ -179,6 +781,355 @@ mod tests {
         assert!(!row.is_ship);
     }
 
+    #[test]
+    fn parse_distance_handles_locale_number_formats() {
+        let km = |value: f64| Some(value * METRES_PER_KM);
+        assert_eq!(parse_distance("2,3 km"), km(2.3));
+        assert_eq!(parse_distance("2.3 km"), km(2.3));
+        assert_eq!(parse_distance("12,345 km"), km(12_345.0));
+        assert_eq!(parse_distance("12.345 km"), km(12_345.0));
+        assert_eq!(parse_distance("1,234,567 km"), km(1_234_567.0));
+        assert_eq!(parse_distance("1.234.567,5 km"), km(1_234_567.5));
+        assert_eq!(parse_distance("1,234,567.5 km"), km(1_234_567.5));
+        assert_eq!(parse_distance("12\u{00A0}345 km"), km(12_345.0));
+        assert_eq!(parse_distance("12\u{2009}345 km"), km(12_345.0));
+        assert_eq!(parse_distance("12\u{202F}345,5 км"), km(12_345.5));
+        assert_eq!(parse_distance("2,500 m"), Some(2_500.0));
+        assert_eq!(parse_distance("850 м"), Some(850.0));
+    }
+
+    #[test]
+    fn parse_distance_reads_au_separators_as_decimals() {
+        assert_eq!(parse_distance("1.234 AU"), Some(1.234 * METRES_PER_AU));
+        assert_eq!(parse_distance("1,234 AE"), Some(1.234 * METRES_PER_AU));
+        assert_eq!(parse_distance("3,2 UA"), Some(3.2 * METRES_PER_AU));
+        assert_eq!(parse_distance("14 а.е."), Some(14.0 * METRES_PER_AU));
+    }
+
+    #[test]
+    fn parse_distance_rejects_unknown_units_and_empty_numbers() {
+        assert_eq!(parse_distance("-"), None);
+        assert_eq!(parse_distance("km"), None);
+        assert_eq!(parse_distance("12 parsecs"), None);
+        assert_eq!(parse_distance(""), None);
+    }
+
+    #[test]
+    fn range_bucket_thresholds() {
+        assert_eq!(range_bucket(None), DscanRange::Unknown);
+        assert_eq!(range_bucket(Some(2_300.0)), DscanRange::OnGrid);
+        assert_eq!(range_bucket(Some(ON_GRID_MAX_M)), DscanRange::OnGrid);
+        assert_eq!(
+            range_bucket(Some(ON_GRID_MAX_M + 1.0)),
+            DscanRange::WarpBubble
+        );
+        assert_eq!(range_bucket(Some(METRES_PER_AU)), DscanRange::WarpBubble);
+        assert_eq!(
+            range_bucket(Some(2.0 * METRES_PER_AU)),
+            DscanRange::InSystem
+        );
+    }
+
+    #[test]
+    fn parse_dscan_counts_ships_per_range_bucket() {
+        let text = "587\tA\tRifter\t2,3 km\n\
+                    587\tB\tRifter\t45.000 km\n\
+                    587\tC\tRifter\t4,1 AU\n\
+                    587\tD\tRifter\t-\n\
+                    35832\tHome\tAstrahus\t10 km";
+        let result = parse_dscan_text(&test_index(), text);
+        assert_eq!(
+            result.ships_by_range,
+            DscanRangeCounts {
+                on_grid: 1,
+                warp_bubble: 1,
+                in_system: 1,
+                unknown: 1,
+            }
+        );
+        assert_eq!(result.entries[0].distance_m, Some(2_300.0));
+        assert_eq!(result.entries[4].range, DscanRange::OnGrid);
+    }
+
+    #[test]
+    fn summary_counts_by_type_group_and_category() {
+        let text = "22456\tA\tSabre\t10 km\n\
+                    22456\tB\tSabre\t10 km\n\
+                    587\tC\tRifter\t10 km\n\
+                    35832\tHome\tAstrahus\t10 km\n\
+                    1\tThing\tMystery Object\t10 km";
+        let summary = parse_dscan_text(&test_index(), text).summary;
+
+        let counts = |list: &[DscanCount]| -> Vec<(String, usize)> {
+            list.iter()
+                .map(|count| (count.name.clone(), count.count))
+                .collect()
+        };
+        assert_eq!(
+            counts(&summary.by_type),
+            vec![
+                ("Sabre".to_string(), 2),
+                ("Astrahus".to_string(), 1),
+                ("Mystery Object".to_string(), 1),
+                ("Rifter".to_string(), 1),
+            ]
+        );
+        assert_eq!(summary.by_type[0].id, Some(22456));
+        assert_eq!(
+            counts(&summary.by_group),
+            vec![
+                ("Interdictor".to_string(), 2),
+                ("Citadel".to_string(), 1),
+                ("Frigate".to_string(), 1),
+            ]
+        );
+        assert_eq!(
+            counts(&summary.by_category),
+            vec![("Ship".to_string(), 3), ("Structure".to_string(), 1)]
+        );
+    }
+
+    #[test]
+    fn summary_uses_canonical_name_for_name_fallback_rows() {
+        let summary = parse_dscan_text(&test_index(), "0\tX\tsabre\t-").summary;
+        assert_eq!(
+            summary.by_type,
+            vec![DscanCount {
+                id: Some(22456),
+                name: "Sabre".to_string(),
+                count: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn summary_totals_ship_classes() {
+        let text = "587\tA\tRifter\t-\n\
+                    22456\tB\tSabre\t-\n\
+                    12011\tC\tEagle\t-\n\
+                    23913\tD\tNyx\t-\n\
+                    35832\tE\tAstrahus\t-";
+        let totals = parse_dscan_text(&test_index(), text).summary.ship_classes;
+        assert_eq!(
+            totals,
+            ShipClassTotals {
+                frigates: 1,
+                destroyers: 1,
+                cruisers: 1,
+                capitals: 1,
+                ..ShipClassTotals::default()
+            }
+        );
+    }
+
+    #[test]
+    fn ship_classes_come_from_hull_profiles() {
+        let profile = |roles: &[HullRole], size| HullProfile {
+            roles: roles.to_vec(),
+            tech_level: TechLevel::Tech1,
+            size,
+        };
+        let index = SdeIndex::from_entries(vec![
+            // A group the fallback table doesn't know.
+            entry(90001, "New Hull", 9001, "New Cruiser", 6, "Ship"),
+            entry(648, "Badger", 28, "Hauler", 6, "Ship"),
+            entry(20185, "Charon", 513, "Freighter", 6, "Ship"),
+            entry(23913, "Nyx", 659, "Supercarrier", 6, "Ship"),
+            entry(587, "Rifter", 25, "Frigate", 6, "Ship"),
+        ])
+        .with_hull_profiles(vec![
+            (90001, profile(&[HullRole::Dps], HullSize::Cruiser)),
+            (648, profile(&[HullRole::Hauler], HullSize::Other)),
+            (20185, profile(&[HullRole::Hauler], HullSize::Capital)),
+            (
+                23913,
+                profile(&[HullRole::Capital, HullRole::Super], HullSize::Capital),
+            ),
+        ]);
+        // No profile for the Rifter: its group still makes it a frigate.
+        let text = "90001\tA\tNew Hull\t-\n\
+                    648\tB\tBadger\t-\n\
+                    20185\tC\tCharon\t-\n\
+                    23913\tD\tNyx\t-\n\
+                    587\tE\tRifter\t-";
+        let totals = parse_dscan_text(&index, text).summary.ship_classes;
+        assert_eq!(
+            totals,
+            ShipClassTotals {
+                frigates: 1,
+                cruisers: 1,
+                capitals: 1,
+                industrials: 2,
+                ..ShipClassTotals::default()
+            }
+        );
+    }
+
+    #[test]
+    fn hull_profiles_tag_rows_and_total_roles() {
+        let profile = |roles: &[HullRole], tech_level| HullProfile {
+            roles: roles.to_vec(),
+            tech_level,
+            size: crate::models::HullSize::Other,
+        };
+        let index = test_index().with_hull_profiles(vec![
+            (587, profile(&[HullRole::Dps], TechLevel::Tech1)),
+            (22456, profile(&[HullRole::Interdiction], TechLevel::Tech2)),
+            (
+                23913,
+                profile(&[HullRole::Capital, HullRole::Super], TechLevel::Tech1),
+            ),
+        ]);
+        let text = "22456\tA\tSabre\t-\n\
+                    22456\tB\tSabre\t-\n\
+                    23913\tC\tNyx\t-\n\
+                    12011\tD\tEagle\t-\n\
+                    35832\tE\tAstrahus\t-";
+        let result = parse_dscan_text(&index, text);
+
+        assert_eq!(result.entries[0].hull_roles, [HullRole::Interdiction]);
+        assert_eq!(result.entries[0].tech_level, Some(TechLevel::Tech2));
+        // No profile for the Eagle; structures never have one.
+        assert!(result.entries[3].hull_roles.is_empty());
+        assert_eq!(result.entries[4].tech_level, None);
+        assert_eq!(
+            result.summary.hull_roles,
+            HullRoleTotals {
+                interdiction: 2,
+                capital: 1,
+                super_capital: 1,
+                ..HullRoleTotals::default()
+            }
+        );
+        assert_eq!(
+            result.summary.tech_levels,
+            TechLevelTotals {
+                t1: 1,
+                t2: 2,
+                ..TechLevelTotals::default()
+            }
+        );
+    }
+
+    #[test]
+    fn diff_reports_arrivals_departures_and_group_changes() {
+        let index = test_index();
+        let previous = parse_dscan_text(
+            &index,
+            "22456\tA\tSabre\t-\n587\tB\tRifter\t-\n587\tC\tRifter\t-",
+        );
+        let current = parse_dscan_text(
+            &index,
+            "22456\tA\tSabre\t-\n22456\tD\tSabre\t-\n22456\tE\tSabre\t-\n\
+             22456\tF\tSabre\t-\n587\tB\tRifter\t-",
+        );
+        let diff = diff_dscan(&previous, &current);
+
+        assert_eq!(
+            diff.arrivals,
+            vec![DscanCountChange {
+                id: Some(22456),
+                name: "Sabre".to_string(),
+                before: 1,
+                after: 4,
+                delta: 3,
+            }]
+        );
+        assert_eq!(diff.departures.len(), 1);
+        assert_eq!(diff.departures[0].name, "Rifter");
+        assert_eq!(diff.departures[0].delta, -1);
+
+        let groups: Vec<(&str, i64)> = diff
+            .group_changes
+            .iter()
+            .map(|change| (change.name.as_str(), change.delta))
+            .collect();
+        assert_eq!(groups, vec![("Interdictor", 3), ("Frigate", -1)]);
+        assert!(diff.moved.is_empty());
+    }
+
+    #[test]
+    fn diff_reports_objects_that_changed_range_bucket() {
+        let index = test_index();
+        let previous = parse_dscan_text(
+            &index,
+            "22456\tA\tSabre\t3 AU\n22456\tA\tSabre\t3 AU\n587\tB\tRifter\t5 km",
+        );
+        let current = parse_dscan_text(
+            &index,
+            "22456\tA\tSabre\t3 AU\n22456\tA\tSabre\t20 km\n587\tB\tRifter\t6 km",
+        );
+        let diff = diff_dscan(&previous, &current);
+
+        assert!(diff.arrivals.is_empty());
+        assert!(diff.departures.is_empty());
+        assert_eq!(
+            diff.moved,
+            vec![DscanMove {
+                type_id: Some(22456),
+                type_name: "Sabre".to_string(),
+                name: "A".to_string(),
+                from: DscanRange::InSystem,
+                to: DscanRange::OnGrid,
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_dscan_classifies_localized_names_without_type_id() {
+        let index = localized_index();
+        let result = parse_dscan_text(&index, "\tПилот\tсейбр\t-\n\tX\t军刀级\t-\n\tY\tSabre\t-");
+        assert_eq!(result.ship_count, 3);
+        assert!(result.entries.iter().all(|row| row.group_id == Some(541)));
+        assert_eq!(result.summary.by_type[0].count, 3);
+    }
+
+    #[test]
+    fn parse_dscan_reports_names_in_display_language() {
+        let index = localized_index();
+        let result = parse_dscan_text_in(&index, "22456\tA\tSabre\t-\n587\tB\tRifter\t-", "de");
+        let sabre = &result.entries[0];
+        assert_eq!(sabre.display_type_name.as_deref(), Some("Sabre"));
+        assert_eq!(sabre.group_name.as_deref(), Some("Interdictor-Zerstörer"));
+        // No German group name for Frigate: falls back to English.
+        assert_eq!(result.entries[1].group_name.as_deref(), Some("Frigate"));
+
+        let result = parse_dscan_text_in(&index, "22456\tA\tSabre\t-", "ru");
+        assert_eq!(
+            result.entries[0].display_type_name.as_deref(),
+            Some("Сейбр")
+        );
+        assert_eq!(result.summary.by_type[0].name, "Сейбр");
+        assert_eq!(result.entries[0].type_name, "Sabre");
+    }
+
+    #[test]
+    fn object_kind_follows_category_and_group() {
+        let kind = |category_id, group_id| {
+            object_kind(&entry(1, "X", group_id, "Group", category_id, "Category"))
+        };
+        assert_eq!(kind(6, 25), ScanObjectKind::Ship);
+        assert_eq!(kind(65, 1657), ScanObjectKind::Structure);
+        assert_eq!(kind(23, 365), ScanObjectKind::Structure);
+        assert_eq!(kind(40, 1012), ScanObjectKind::Structure);
+        assert_eq!(kind(22, 361), ScanObjectKind::Deployable);
+        assert_eq!(kind(18, 100), ScanObjectKind::Drone);
+        assert_eq!(kind(87, 1652), ScanObjectKind::Fighter);
+        assert_eq!(kind(2, 7), ScanObjectKind::Celestial);
+        assert_eq!(kind(2, 186), ScanObjectKind::Wreck);
+        assert_eq!(kind(8, 479), ScanObjectKind::Probe);
+        assert_eq!(kind(8, 548), ScanObjectKind::Probe);
+        assert_eq!(kind(8, 83), ScanObjectKind::Other);
+    }
+
+    #[test]
+    fn parse_dscan_unclassified_rows_are_other() {
+        let result = parse_dscan_text(&test_index(), "1\tThing\tMystery Object\t-");
+        assert_eq!(result.entries[0].kind, ScanObjectKind::Other);
+        let result = parse_dscan_text(&test_index(), "35832\tHome\tAstrahus\t-");
+        assert_eq!(result.entries[0].kind, ScanObjectKind::Structure);
+    }
+
     #[test]
     fn normalize_name_trims_and_lowercases() {
         assert_eq!(normalize_name("  Rifter  "), "rifter");
@@ -0,0 +1,246 @@
+//! Pure fleet-composition parsing: turns a paste of the fleet composition
+//! window into members with SDE-classified ships, per-wing breakdowns and
+//! the pilots who are not where the fleet is.
+//!
+//! A row is tab separated: pilot, solar system, ship, ship class,
+//! position, skills (optional, "5 - 5 - 5") and "Wing / Squad".
+
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::dscan::SdeIndex;
+use crate::models::{DscanCount, FleetComposition, FleetMember, FleetWing};
+
+const FLEET_COMMANDER: &str = "Fleet Commander";
+
+/// Parse raw fleet-composition text. Rows with fewer than five columns are
+/// skipped. The fleet's system is `expected_system` when given, otherwise
+/// the fleet commander's, otherwise the most common one; members anywhere
+/// else are reported as out of position. Pure function.
+pub fn parse_fleet_text(
+    index: &SdeIndex,
+    text: &str,
+    expected_system: Option<&str>,
+) -> FleetComposition {
+    let members: Vec<FleetMember> = text
+        .lines()
+        .filter_map(|line| parse_member(index, line))
+        .collect();
+
+    let reference_system = expected_system
+        .map(str::to_string)
+        .or_else(|| {
+            members
+                .iter()
+                .find(|member| member.position.eq_ignore_ascii_case(FLEET_COMMANDER))
+                .map(|member| member.system.clone())
+        })
+        .or_else(|| most_common_system(&members));
+
+    let out_of_position = reference_system
+        .as_deref()
+        .map(|system| {
+            members
+                .iter()
+                .filter(|member| !member.system.eq_ignore_ascii_case(system))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    FleetComposition {
+        wings: wing_breakdown(&members),
+        pilot_names: pilot_names(&members),
+        reference_system,
+        out_of_position,
+        members,
+    }
+}
+
+fn parse_member(index: &SdeIndex, line: &str) -> Option<FleetMember> {
+    let columns: Vec<&str> = line.split('\t').map(str::trim).collect();
+    if columns.len() < 5 || columns[0].is_empty() {
+        return None;
+    }
+
+    // The skills column is optional; "Wing / Squad" is always last when
+    // present.
+    let (wing, squad) = columns[5..]
+        .iter()
+        .rev()
+        .find(|column| column.contains('/'))
+        .map(|column| {
+            let (wing, squad) = column.split_once('/').unwrap_or((column, ""));
+            (non_empty(wing), non_empty(squad))
+        })
+        .unwrap_or((None, None));
+
+    let ship_type_name = columns[2].to_string();
+    let classification = index.classify(None, &ship_type_name);
+
+    Some(FleetMember {
+        pilot: columns[0].to_string(),
+        system: columns[1].to_string(),
+        type_id: classification.map(|entry| entry.type_id),
+        group_id: classification.map(|entry| entry.group_id),
+        group_name: classification
+            .map(|entry| entry.group_name.clone())
+            .or_else(|| non_empty(columns[3])),
+        ship_type_name,
+        position: columns[4].to_string(),
+        wing,
+        squad,
+    })
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn most_common_system(members: &[FleetMember]) -> Option<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for member in members {
+        *counts.entry(member.system.as_str()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(system, _)| system.to_string())
+}
+
+/// Unique pilot names in paste order, ready for the lookup pipeline.
+fn pilot_names(members: &[FleetMember]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    members
+        .iter()
+        .filter(|member| seen.insert(member.pilot.to_lowercase()))
+        .map(|member| member.pilot.clone())
+        .collect()
+}
+
+/// Ship counts per wing, wings in paste order. Members without a wing
+/// (the fleet commander) are grouped under "Fleet".
+fn wing_breakdown(members: &[FleetMember]) -> Vec<FleetWing> {
+    let mut wings: Vec<(String, Vec<&FleetMember>)> = Vec::new();
+    for member in members {
+        let wing = member.wing.clone().unwrap_or_else(|| "Fleet".to_string());
+        match wings.iter_mut().find(|(name, _)| *name == wing) {
+            Some((_, list)) => list.push(member),
+            None => wings.push((wing, vec![member])),
+        }
+    }
+
+    wings
+        .into_iter()
+        .map(|(name, list)| {
+            let mut counts: HashMap<(Option<i64>, &str), usize> = HashMap::new();
+            for member in &list {
+                *counts
+                    .entry((member.type_id, member.ship_type_name.as_str()))
+                    .or_default() += 1;
+            }
+            let mut ships: Vec<DscanCount> = counts
+                .into_iter()
+                .map(|((id, name), count)| DscanCount {
+                    id,
+                    name: name.to_string(),
+                    count,
+                })
+                .collect();
+            ships.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+
+            FleetWing {
+                name,
+                member_count: list.len(),
+                ships,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ScanTypeIndexEntry;
+    use std::collections::BTreeMap;
+
+    fn index() -> SdeIndex {
+        let entry = |type_id, type_name: &str, group_id, group_name: &str| ScanTypeIndexEntry {
+            type_id,
+            type_name: type_name.to_string(),
+            type_names: BTreeMap::new(),
+            group_id,
+            group_name: group_name.to_string(),
+            group_names: BTreeMap::new(),
+            category_id: 6,
+            category_name: "Ship".to_string(),
+        };
+        SdeIndex::from_entries(vec![
+            entry(12015, "Muninn", 358, "Heavy Assault Cruiser"),
+            entry(11978, "Scimitar", 832, "Logistics"),
+            entry(22456, "Sabre", 541, "Interdictor"),
+        ])
+    }
+
+    const PASTE: &str = "Boss Pilot\tJita\tSabre\tInterdictor\tFleet Commander\t5 - 5 - 5\t\n\
+                         Alpha One\tJita\tMuninn\tHeavy Assault Cruiser\tSquad Member\t0 - 0 - 5\tWing 1 / Squad 1\n\
+                         Alpha Two\tJita\tMuninn\tHeavy Assault Cruiser\tSquad Member\t0 - 0 - 5\tWing 1 / Squad 1\n\
+                         Logi Guy\tPerimeter\tScimitar\tLogistics\tSquad Member\t0 - 0 - 5\tWing 2 / Squad 1\n\
+                         Too\tfew\tcolumns\n";
+
+    #[test]
+    fn parse_fleet_classifies_members() {
+        let fleet = parse_fleet_text(&index(), PASTE, None);
+        assert_eq!(fleet.members.len(), 4);
+
+        let member = &fleet.members[1];
+        assert_eq!(member.pilot, "Alpha One");
+        assert_eq!(member.type_id, Some(12015));
+        assert_eq!(member.group_id, Some(358));
+        assert_eq!(member.wing.as_deref(), Some("Wing 1"));
+        assert_eq!(member.squad.as_deref(), Some("Squad 1"));
+        assert_eq!(fleet.members[0].wing, None);
+        assert_eq!(
+            fleet.pilot_names,
+            vec!["Boss Pilot", "Alpha One", "Alpha Two", "Logi Guy"]
+        );
+    }
+
+    #[test]
+    fn parse_fleet_breaks_ships_down_per_wing() {
+        let fleet = parse_fleet_text(&index(), PASTE, None);
+        let wings: Vec<(&str, usize)> = fleet
+            .wings
+            .iter()
+            .map(|wing| (wing.name.as_str(), wing.member_count))
+            .collect();
+        assert_eq!(wings, vec![("Fleet", 1), ("Wing 1", 2), ("Wing 2", 1)]);
+        assert_eq!(fleet.wings[1].ships[0].name, "Muninn");
+        assert_eq!(fleet.wings[1].ships[0].count, 2);
+    }
+
+    #[test]
+    fn out_of_position_is_relative_to_the_fleet_commander() {
+        let fleet = parse_fleet_text(&index(), PASTE, None);
+        assert_eq!(fleet.reference_system.as_deref(), Some("Jita"));
+        assert_eq!(fleet.out_of_position.len(), 1);
+        assert_eq!(fleet.out_of_position[0].pilot, "Logi Guy");
+
+        // An explicit staging system overrides the commander's.
+        let fleet = parse_fleet_text(&index(), PASTE, Some("Perimeter"));
+        assert_eq!(fleet.out_of_position.len(), 3);
+    }
+
+    #[test]
+    fn without_a_commander_the_most_common_system_wins() {
+        let text = "A\tAmarr\tSabre\tInterdictor\tSquad Member\tWing 1 / Squad 1\n\
+                    B\tJita\tSabre\tInterdictor\tSquad Member\tWing 1 / Squad 1\n\
+                    C\tJita\tUnknown Hull\tFrigate\tSquad Member\tWing 1 / Squad 1";
+        let fleet = parse_fleet_text(&index(), text, None);
+        assert_eq!(fleet.reference_system.as_deref(), Some("Jita"));
+        assert_eq!(fleet.out_of_position[0].pilot, "A");
+        // Unknown to the index: keep the pasted ship class.
+        assert_eq!(fleet.members[2].type_id, None);
+        assert_eq!(fleet.members[2].group_name.as_deref(), Some("Frigate"));
+    }
+}
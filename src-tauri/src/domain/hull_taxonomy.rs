@@ -0,0 +1,408 @@
+//! Pure hull classification: the roles and tech level of a ship type from
+//! SDE data alone (group and market group names, meta group, and the hull
+//! attributes from `domain::dogma`), so a hull added by an SDE update is
+//! classified without a code change.
+
+use crate::models::{HullBonusKind, HullProfile, HullRole, HullSize, ShipAttributes, TechLevel};
+
+/// What the builder knows about a ship type when classifying it.
+#[derive(Debug, Clone, Copy)]
+pub struct HullFacts<'a> {
+    pub group_name: &'a str,
+    /// English market group names from the root ("Ships") down to the
+    /// hull's own group.
+    pub market_path: &'a [String],
+    pub meta_group_id: Option<i64>,
+    pub attributes: Option<&'a ShipAttributes>,
+}
+
+/// Lowercase fragments of group and market group names that put a hull in
+/// a role ("interdict" covers Interdictors and Heavy Interdiction Cruisers,
+/// "recon" the Recon Ships market group).
+const ROLE_NAMES: [(HullRole, &[&str]); 9] = [
+    (HullRole::Tackle, &["interceptor"]),
+    (HullRole::Interdiction, &["interdict"]),
+    (HullRole::Logistics, &["logistic", "force auxiliar"]),
+    (HullRole::Ewar, &["electronic attack", "recon"]),
+    (
+        HullRole::Hauler,
+        &["hauler", "transport", "blockade runner", "freighter"],
+    ),
+    (
+        HullRole::Mining,
+        &[
+            "mining",
+            "exhumer",
+            "expedition",
+            "industrial command",
+            "capital industrial",
+        ],
+    ),
+    (
+        HullRole::Covert,
+        &[
+            "covert ops",
+            "stealth bomber",
+            "black ops",
+            "force recon",
+            "blockade runner",
+            "expedition",
+        ],
+    ),
+    (
+        HullRole::Capital,
+        &[
+            "capital",
+            "carrier",
+            "dreadnought",
+            "force auxiliar",
+            "titan",
+        ],
+    ),
+    (HullRole::Super, &["supercarrier", "titan"]),
+];
+
+/// Hull bonuses that put a hull in a role whatever its group is called.
+/// Neutralizer bonuses aren't among them: neut battleships like the
+/// Bhaalgorn are damage dealers first.
+const ROLE_BONUSES: [(HullRole, &[HullBonusKind]); 3] = [
+    (
+        HullRole::Tackle,
+        &[
+            HullBonusKind::WarpScrambler,
+            HullBonusKind::WarpDisruptor,
+            HullBonusKind::StasisWebifier,
+        ],
+    ),
+    (HullRole::Interdiction, &[HullBonusKind::Interdiction]),
+    (
+        HullRole::Ewar,
+        &[
+            HullBonusKind::Ecm,
+            HullBonusKind::SensorDampener,
+            HullBonusKind::WeaponDisruptor,
+            HullBonusKind::TargetPainter,
+        ],
+    ),
+];
+
+/// Lowercase name fragments per size, checked in order against the group
+/// name and then the market groups from the hull's own upwards; the first
+/// match wins. "Industrial" comes early so an Orca ("Industrial Command
+/// Ship") isn't a command ship.
+const SIZE_NAMES: [(HullSize, &[&str]); 7] = [
+    (
+        HullSize::Capital,
+        &[
+            "capital",
+            "carrier",
+            "dreadnought",
+            "force auxiliar",
+            "titan",
+            "freighter",
+        ],
+    ),
+    (
+        HullSize::Other,
+        &[
+            "industrial",
+            "shuttle",
+            "capsule",
+            "mining barge",
+            "exhumer",
+        ],
+    ),
+    (HullSize::Battlecruiser, &["battlecruiser", "command ship"]),
+    (
+        HullSize::Battleship,
+        &["battleship", "black ops", "marauder"],
+    ),
+    (HullSize::Cruiser, &["cruiser", "recon"]),
+    (HullSize::Destroyer, &["destroyer", "interdictor"]),
+    (
+        HullSize::Frigate,
+        &["frigate", "interceptor", "covert ops", "stealth bomber"],
+    ),
+];
+
+/// Roles that make a hull something other than a damage dealer.
+const SUPPORT_ROLES: [HullRole; 6] = [
+    HullRole::Tackle,
+    HullRole::Interdiction,
+    HullRole::Logistics,
+    HullRole::Ewar,
+    HullRole::Hauler,
+    HullRole::Mining,
+];
+
+const TECH_2_META_GROUP: i64 = 2;
+const TECH_3_META_GROUP: i64 = 14;
+/// Storyline, Faction, Officer, Deadspace.
+const FACTION_META_GROUPS: [i64; 4] = [3, 4, 5, 6];
+
+pub fn hull_profile(facts: &HullFacts) -> HullProfile {
+    let names: Vec<String> = std::iter::once(facts.group_name)
+        .chain(facts.market_path.iter().map(String::as_str))
+        .map(str::to_lowercase)
+        .collect();
+    let named = |fragments: &[&str]| {
+        names
+            .iter()
+            .any(|name| fragments.iter().any(|fragment| name.contains(fragment)))
+    };
+
+    let mut roles: Vec<HullRole> = ROLE_NAMES
+        .iter()
+        .filter(|(_, fragments)| named(fragments))
+        .map(|(role, _)| *role)
+        .collect();
+    if let Some(attributes) = facts.attributes {
+        let has_bonus = |kinds: &[HullBonusKind]| {
+            attributes
+                .bonuses
+                .iter()
+                .any(|bonus| bonus.kinds.iter().any(|kind| kinds.contains(kind)))
+        };
+        roles.extend(
+            ROLE_BONUSES
+                .iter()
+                .filter(|(_, kinds)| has_bonus(kinds))
+                .map(|(role, _)| *role),
+        );
+        if attributes.covert_ops_cloak {
+            roles.push(HullRole::Covert);
+        }
+    }
+    if roles.contains(&HullRole::Super) {
+        roles.push(HullRole::Capital);
+    }
+    // Freighters are capital-sized, but a hauler isn't a capital threat.
+    if roles.contains(&HullRole::Hauler) {
+        roles.retain(|role| *role != HullRole::Capital);
+    }
+
+    // Carriers fight with fighters, not hardpoints.
+    let armed = facts
+        .attributes
+        .is_some_and(|attributes| attributes.slots.turret > 0 || attributes.slots.launcher > 0)
+        || named(&["carrier"]);
+    if armed && !roles.iter().any(|role| SUPPORT_ROLES.contains(role)) {
+        roles.push(HullRole::Dps);
+    }
+
+    roles.sort_unstable();
+    roles.dedup();
+    HullProfile {
+        roles,
+        tech_level: tech_level(facts.meta_group_id, &names),
+        size: hull_size(&names),
+    }
+}
+
+/// `names` is the group name followed by the market path from the root.
+fn hull_size(names: &[String]) -> HullSize {
+    names
+        .iter()
+        .take(1)
+        .chain(names.iter().skip(1).rev())
+        .find_map(|name| {
+            SIZE_NAMES
+                .iter()
+                .find(|(_, fragments)| fragments.iter().any(|fragment| name.contains(fragment)))
+                .map(|(size, _)| *size)
+        })
+        .unwrap_or(HullSize::Other)
+}
+
+fn tech_level(meta_group_id: Option<i64>, names: &[String]) -> TechLevel {
+    match meta_group_id {
+        Some(TECH_2_META_GROUP) => TechLevel::Tech2,
+        Some(TECH_3_META_GROUP) => TechLevel::Tech3,
+        Some(id) if FACTION_META_GROUPS.contains(&id) => TechLevel::Faction,
+        // Some faction hulls have no meta group; their market group says.
+        _ if names.iter().any(|name| name.contains("faction")) => TechLevel::Faction,
+        _ => TechLevel::Tech1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{HullBonus, ShipSlots};
+
+    fn path(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn armed(bonuses: Vec<HullBonus>, covert_ops_cloak: bool) -> ShipAttributes {
+        ShipAttributes {
+            slots: ShipSlots {
+                high: 4,
+                turret: 2,
+                ..ShipSlots::default()
+            },
+            signature_radius: 40.0,
+            max_velocity: 400.0,
+            warp_speed: 5.0,
+            covert_ops_cloak,
+            jump_drive: false,
+            bonuses,
+        }
+    }
+
+    fn bonus(kind: HullBonusKind) -> HullBonus {
+        HullBonus {
+            kinds: vec![kind],
+            text: String::new(),
+            per_level: true,
+        }
+    }
+
+    fn profile(
+        group_name: &str,
+        market_path: &[&str],
+        meta_group_id: Option<i64>,
+        attributes: Option<&ShipAttributes>,
+    ) -> HullProfile {
+        hull_profile(&HullFacts {
+            group_name,
+            market_path: &path(market_path),
+            meta_group_id,
+            attributes,
+        })
+    }
+
+    #[test]
+    fn roles_come_from_group_and_market_names() {
+        let attributes = armed(Vec::new(), false);
+        let sabre = profile(
+            "Interdictor",
+            &["Ships", "Destroyers", "Advanced Destroyers", "Interdictors"],
+            Some(2),
+            Some(&attributes),
+        );
+        assert_eq!(sabre.roles, [HullRole::Interdiction]);
+        assert_eq!(sabre.tech_level, TechLevel::Tech2);
+
+        let rifter = profile(
+            "Frigate",
+            &["Ships", "Frigates", "Standard Frigates", "Minmatar"],
+            None,
+            Some(&attributes),
+        );
+        assert_eq!(rifter.roles, [HullRole::Dps]);
+        assert_eq!(rifter.tech_level, TechLevel::Tech1);
+
+        let avatar = profile(
+            "Titan",
+            &["Ships", "Capital Ships", "Titans", "Amarr"],
+            None,
+            Some(&attributes),
+        );
+        assert_eq!(
+            avatar.roles,
+            [HullRole::Dps, HullRole::Capital, HullRole::Super]
+        );
+
+        let charon = profile(
+            "Freighter",
+            &["Ships", "Capital Ships", "Freighters", "Caldari"],
+            None,
+            None,
+        );
+        assert_eq!(charon.roles, [HullRole::Hauler]);
+    }
+
+    #[test]
+    fn dogma_adds_roles_an_unfamiliar_group_name_would_miss() {
+        // A hypothetical new hull in a generically named group.
+        let attributes = armed(vec![bonus(HullBonusKind::SensorDampener)], true);
+        let hull = profile(
+            "Experimental Cruiser",
+            &["Ships", "Cruisers", "Advanced Cruisers"],
+            Some(2),
+            Some(&attributes),
+        );
+        assert_eq!(hull.roles, [HullRole::Ewar, HullRole::Covert]);
+
+        let attributes = armed(vec![bonus(HullBonusKind::StasisWebifier)], false);
+        let hull = profile("Battleship", &[], Some(4), Some(&attributes));
+        assert_eq!(hull.roles, [HullRole::Tackle]);
+        assert_eq!(hull.tech_level, TechLevel::Faction);
+    }
+
+    #[test]
+    fn neut_bonuses_leave_a_damage_dealer() {
+        let attributes = armed(vec![bonus(HullBonusKind::CapacitorWarfare)], false);
+        let bhaalgorn = profile(
+            "Battleship",
+            &["Ships", "Battleships", "Faction Battleships"],
+            Some(4),
+            Some(&attributes),
+        );
+        assert_eq!(bhaalgorn.roles, [HullRole::Dps]);
+    }
+
+    #[test]
+    fn size_comes_from_the_most_specific_name() {
+        let size =
+            |group_name, market_path: &[&str]| profile(group_name, market_path, None, None).size;
+        let recon_path = ["Ships", "Cruisers", "Advanced Cruisers", "Recon Ships"];
+        assert_eq!(size("Force Recon Ship", &recon_path), HullSize::Cruiser);
+        assert_eq!(
+            size(
+                "Black Ops",
+                &["Ships", "Battleships", "Advanced Battleships"]
+            ),
+            HullSize::Battleship
+        );
+        assert_eq!(size("Interdictor", &[]), HullSize::Destroyer);
+        assert_eq!(
+            size("Battlecruiser", &["Ships", "Battlecruisers"]),
+            HullSize::Battlecruiser
+        );
+        assert_eq!(
+            size(
+                "Frigate",
+                &["Ships", "Frigates", "Standard Frigates", "Minmatar"]
+            ),
+            HullSize::Frigate
+        );
+        assert_eq!(
+            size("Titan", &["Ships", "Capital Ships"]),
+            HullSize::Capital
+        );
+        assert_eq!(
+            size(
+                "Industrial Command Ship",
+                &["Ships", "Industrial Command Ships"]
+            ),
+            HullSize::Other
+        );
+        // A new group name still sizes by its market group.
+        assert_eq!(
+            size(
+                "Precursor Skirmisher",
+                &["Ships", "Destroyers", "Precursor"]
+            ),
+            HullSize::Destroyer
+        );
+    }
+
+    #[test]
+    fn tech_level_follows_meta_group_then_market_group() {
+        let level = |meta_group_id, market_path: &[&str]| {
+            profile("Cruiser", market_path, meta_group_id, None).tech_level
+        };
+        assert_eq!(level(Some(14), &[]), TechLevel::Tech3);
+        assert_eq!(level(Some(3), &[]), TechLevel::Faction);
+        assert_eq!(
+            level(None, &["Ships", "Faction Cruisers"]),
+            TechLevel::Faction
+        );
+        assert_eq!(
+            level(Some(1), &["Ships", "Standard Cruisers"]),
+            TechLevel::Tech1
+        );
+    }
+}
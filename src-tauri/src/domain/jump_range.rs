@@ -0,0 +1,317 @@
+//! Pure jump-drive range math over SDE system coordinates: light-year
+//! distances, systems a jump-capable hull can reach, and capital pilots
+//! whose usual systems are within range of a staging system.
+
+use crate::domain::map::{is_highsec, MapIndex};
+use crate::models::{CapitalThreat, JumpRangeSystem, JumpShipClass, PilotIntel, SolarSystemEntry};
+
+const METRES_PER_LIGHT_YEAR: f64 = 9_460_730_472_580_800.0;
+
+/// Jump Drive Calibration adds 20% of the base range per level.
+const CALIBRATION_BONUS_PER_LEVEL: f64 = 0.2;
+pub const MAX_SKILL_LEVEL: u8 = 5;
+
+/// Wormhole classes of known space (high, low, null). Anything else with a
+/// class is J-space, Pochven or similar, where cynos can't be lit.
+const KNOWN_SPACE_CLASSES: [i64; 3] = [7, 8, 9];
+
+fn base_range_ly(class: JumpShipClass) -> f64 {
+    match class {
+        JumpShipClass::Supercapital => 3.0,
+        JumpShipClass::Capital => 3.5,
+        JumpShipClass::BlackOps => 4.0,
+        JumpShipClass::JumpFreighter | JumpShipClass::Rorqual => 5.0,
+    }
+}
+
+/// Maximum jump range in light-years with Jump Drive Calibration at
+/// `calibration_level` (clamped to 0..=5).
+pub fn max_range_ly(class: JumpShipClass, calibration_level: u8) -> f64 {
+    let level = f64::from(calibration_level.min(MAX_SKILL_LEVEL));
+    base_range_ly(class) * (1.0 + CALIBRATION_BONUS_PER_LEVEL * level)
+}
+
+/// Straight-line distance in light-years, when both systems have
+/// coordinates.
+pub fn distance_ly(a: &SolarSystemEntry, b: &SolarSystemEntry) -> Option<f64> {
+    let (a, b) = (a.position?, b.position?);
+    let squared: f64 = a.iter().zip(b).map(|(a, b)| (a - b).powi(2)).sum();
+    Some(squared.sqrt() / METRES_PER_LIGHT_YEAR)
+}
+
+/// Whether a jump drive can land in `system`: no high-sec, and only known
+/// space.
+pub fn is_jump_destination(system: &SolarSystemEntry) -> bool {
+    !is_highsec(system.security_status)
+        && system
+            .wormhole_class
+            .is_none_or(|class| KNOWN_SPACE_CLASSES.contains(&class))
+}
+
+/// Every valid jump destination within range of `origin`, nearest first;
+/// none when `origin` itself is somewhere a jump drive can't be used.
+pub fn reachable_systems(
+    map: &MapIndex,
+    origin: i64,
+    class: JumpShipClass,
+    calibration_level: u8,
+) -> Vec<JumpRangeSystem> {
+    let Some(origin) = map
+        .system(origin)
+        .filter(|system| is_jump_destination(system))
+    else {
+        return Vec::new();
+    };
+    let range = max_range_ly(class, calibration_level);
+
+    let mut reachable: Vec<JumpRangeSystem> = map
+        .systems()
+        .filter(|system| system.system_id != origin.system_id && is_jump_destination(system))
+        .filter_map(|system| {
+            let distance_ly = distance_ly(origin, system)?;
+            (distance_ly <= range).then(|| JumpRangeSystem {
+                system: system.clone(),
+                distance_ly,
+            })
+        })
+        .collect();
+    reachable.sort_by(|a, b| a.distance_ly.total_cmp(&b.distance_ly));
+    reachable
+}
+
+/// Capital and super pilots with a top system one jump (at max skills)
+/// away from `staging`. Only top systems a pilot's hull can jump from
+/// count, and a staging it can't jump into rules the pilot out; supers
+/// are limited to null-sec at both ends.
+pub fn capital_threats(map: &MapIndex, staging: i64, pilots: &[PilotIntel]) -> Vec<CapitalThreat> {
+    let Some(staging) = map
+        .system(staging)
+        .filter(|system| is_jump_destination(system))
+    else {
+        return Vec::new();
+    };
+
+    pilots
+        .iter()
+        .filter(|pilot| pilot.flags.is_capital || pilot.flags.is_super)
+        .filter_map(|pilot| {
+            let class = if pilot.flags.is_super {
+                JumpShipClass::Supercapital
+            } else {
+                JumpShipClass::Capital
+            };
+            // Supercarriers and titans can't enter low-sec either.
+            let can_jump = |system: &SolarSystemEntry| {
+                is_jump_destination(system)
+                    && (!pilot.flags.is_super || system.security_status < 0.0)
+            };
+            if !can_jump(staging) {
+                return None;
+            }
+            let range = max_range_ly(class, MAX_SKILL_LEVEL);
+            let mut systems_in_range: Vec<JumpRangeSystem> = pilot
+                .zkill
+                .as_ref()?
+                .top_systems
+                .iter()
+                .filter_map(|stats| {
+                    let system = map
+                        .system(stats.system_id)
+                        .filter(|system| can_jump(system))?;
+                    let distance_ly = distance_ly(system, staging)?;
+                    (distance_ly <= range).then(|| JumpRangeSystem {
+                        system: system.clone(),
+                        distance_ly,
+                    })
+                })
+                .collect();
+            if systems_in_range.is_empty() {
+                return None;
+            }
+            systems_in_range.sort_by(|a, b| a.distance_ly.total_cmp(&b.distance_ly));
+
+            Some(CapitalThreat {
+                character_id: pilot.character.id,
+                character_name: pilot.character.name.clone(),
+                is_super: pilot.flags.is_super,
+                systems_in_range,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CharacterInfo, PilotFlags, SystemStats, ZkillStats};
+
+    const LY: f64 = METRES_PER_LIGHT_YEAR;
+
+    fn system(
+        system_id: i64,
+        security_status: f64,
+        wormhole_class: Option<i64>,
+        x_ly: f64,
+    ) -> SolarSystemEntry {
+        SolarSystemEntry {
+            system_id,
+            name: format!("System {system_id}"),
+            constellation_id: 1,
+            constellation_name: String::new(),
+            region_id: 1,
+            region_name: String::new(),
+            security_status,
+            wormhole_class,
+            position: Some([x_ly * LY, 0.0, 0.0]),
+        }
+    }
+
+    /// Staging at x = 0; systems laid out along the x axis.
+    fn map() -> MapIndex {
+        MapIndex::from_systems(
+            vec![
+                system(1, -0.5, Some(9), 0.0),
+                system(2, 0.2, Some(8), 3.0),
+                system(3, -0.1, Some(9), 6.5),
+                system(4, 0.9, Some(7), 1.0),
+                system(5, -1.0, Some(3), 2.0),
+                system(6, -0.3, None, 9.0),
+                system(7, 0.8, Some(7), 0.5),
+                system(8, -0.2, Some(9), 4.0),
+            ],
+            &[],
+        )
+    }
+
+    fn pilot(id: i64, flags: PilotFlags, top_systems: &[i64]) -> PilotIntel {
+        PilotIntel {
+            character: CharacterInfo {
+                id,
+                name: format!("Pilot {id}"),
+                corporation_id: None,
+                corporation_name: None,
+                corporation_ticker: None,
+                alliance_id: None,
+                alliance_name: None,
+                alliance_ticker: None,
+            },
+            zkill: Some(ZkillStats {
+                top_systems: top_systems
+                    .iter()
+                    .map(|&system_id| SystemStats {
+                        system_id,
+                        system_name: String::new(),
+                        kills: 1,
+                        jumps: None,
+                    })
+                    .collect(),
+                ..ZkillStats::default()
+            }),
+            threat_level: String::new(),
+            flags,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn range_scales_with_calibration() {
+        assert_eq!(max_range_ly(JumpShipClass::Capital, 0), 3.5);
+        assert!((max_range_ly(JumpShipClass::Capital, 5) - 7.0).abs() < 1e-9);
+        assert!((max_range_ly(JumpShipClass::Supercapital, 5) - 6.0).abs() < 1e-9);
+        assert!((max_range_ly(JumpShipClass::JumpFreighter, 5) - 10.0).abs() < 1e-9);
+        assert!((max_range_ly(JumpShipClass::BlackOps, 9) - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn distance_is_measured_in_light_years() {
+        let map = map();
+        let distance = distance_ly(map.system(1).unwrap(), map.system(3).unwrap()).unwrap();
+        assert!((distance - 6.5).abs() < 1e-9);
+
+        let mut unplaced = system(8, -0.5, None, 0.0);
+        unplaced.position = None;
+        assert_eq!(distance_ly(&unplaced, map.system(1).unwrap()), None);
+    }
+
+    #[test]
+    fn reachable_systems_skip_highsec_and_wormholes() {
+        let ids = |level| -> Vec<i64> {
+            reachable_systems(&map(), 1, JumpShipClass::Capital, level)
+                .iter()
+                .map(|entry| entry.system.system_id)
+                .collect()
+        };
+        // 3.5 LY at level 0: only system 2 (3 LY); 4 and 7 are high-sec,
+        // 5 is J-space.
+        assert_eq!(ids(0), vec![2]);
+        // 7 LY at level 5 adds systems 8 and 3; system 6 at 9 LY stays out.
+        assert_eq!(ids(5), vec![2, 8, 3]);
+        assert!(reachable_systems(&map(), 99, JumpShipClass::Capital, 5).is_empty());
+    }
+
+    #[test]
+    fn nothing_is_reachable_from_where_a_jump_drive_cant_be_used() {
+        // High-sec (4) and J-space (5) origins, each with system 2 in range.
+        assert!(reachable_systems(&map(), 4, JumpShipClass::Capital, 5).is_empty());
+        assert!(reachable_systems(&map(), 5, JumpShipClass::Capital, 5).is_empty());
+    }
+
+    #[test]
+    fn capital_pilots_near_staging_are_flagged() {
+        let capital = PilotFlags {
+            is_capital: true,
+            ..PilotFlags::default()
+        };
+        let pilots = vec![
+            pilot(1, capital.clone(), &[6, 3, 2]),
+            pilot(2, PilotFlags::default(), &[2]),
+            pilot(3, capital, &[6]),
+        ];
+        let threats = capital_threats(&map(), 1, &pilots);
+        assert_eq!(threats.len(), 1);
+        assert_eq!(threats[0].character_id, 1);
+        let systems: Vec<i64> = threats[0]
+            .systems_in_range
+            .iter()
+            .map(|entry| entry.system.system_id)
+            .collect();
+        assert_eq!(systems, vec![2, 3]);
+
+        // A high-sec staging can't be jumped to at all.
+        assert!(capital_threats(&map(), 4, &pilots).is_empty());
+    }
+
+    #[test]
+    fn top_systems_a_hull_cant_jump_from_are_ignored() {
+        let capital = PilotFlags {
+            is_capital: true,
+            ..PilotFlags::default()
+        };
+        // High-sec 4 (1 LY) and J-space 5 (2 LY) are in range but can't
+        // hold a lit cyno.
+        assert!(capital_threats(&map(), 1, &[pilot(1, capital.clone(), &[4, 5])]).is_empty());
+
+        // Supers and titans jump 6 LY at most and stay in null-sec: system
+        // 3 (6.5 LY) is out of range and low-sec system 2 is closed to them.
+        let titan = PilotFlags {
+            is_capital: true,
+            is_super: true,
+            ..PilotFlags::default()
+        };
+        let threats = capital_threats(&map(), 1, &[pilot(2, titan.clone(), &[3, 2, 8])]);
+        let systems: Vec<i64> = threats[0]
+            .systems_in_range
+            .iter()
+            .map(|entry| entry.system.system_id)
+            .collect();
+        assert_eq!(systems, vec![8]);
+        assert!(capital_threats(&map(), 1, &[pilot(3, titan.clone(), &[2, 4, 5])]).is_empty());
+
+        // Nor can they jump into a low-sec staging a capital could reach.
+        assert!(capital_threats(&map(), 2, &[pilot(4, titan, &[8])]).is_empty());
+        assert_eq!(
+            capital_threats(&map(), 2, &[pilot(5, capital, &[8])]).len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Pure solar-system detection from the names on a d-scan. No planet, moon
+//! or station data is ingested: detection relies on celestials and NPC
+//! stations being named after their system ("Jita IV - Moon 4", "Jita -
+//! Star"), checked against system names only, and on stargates being named
+//! after the system they lead to ("Stargate (Perimeter)"), which pins the
+//! scanner to one of that system's neighbours. Player-named structures
+//! follow no convention and are ignored.
+
+use std::collections::HashMap;
+
+use crate::domain::dscan::categories;
+use crate::domain::map::MapIndex;
+use crate::models::{DetectedSystem, DscanEntry, ScanObjectKind, SolarSystemEntry};
+
+/// Below this confidence a detected system is reported but not used to
+/// fill in a shared scan's system.
+pub const MIN_AUTOFILL_CONFIDENCE: f64 = 0.6;
+
+/// Infer the scanner's system from celestial and NPC station rows.
+///
+/// Every row that names a known system is one piece of evidence: a
+/// celestial votes for its system, a stargate for each neighbour of its
+/// destination. Confidence is the winner's share of the evidence, split
+/// between systems that tie for the lead.
+pub fn detect_system(map: &MapIndex, entries: &[DscanEntry]) -> Option<DetectedSystem> {
+    let mut votes: HashMap<i64, usize> = HashMap::new();
+    let mut evidence = 0;
+
+    for entry in entries.iter().filter(|entry| {
+        entry.kind == ScanObjectKind::Celestial || entry.category_id == Some(categories::STATION)
+    }) {
+        if let Some(destination) = stargate_destination(&entry.name) {
+            let Some(destination) = map.system_by_name(destination) else {
+                continue;
+            };
+            let neighbours = map.neighbours(destination.system_id);
+            if neighbours.is_empty() {
+                continue;
+            }
+            evidence += 1;
+            for &system_id in neighbours {
+                *votes.entry(system_id).or_default() += 1;
+            }
+        } else if let Some(system) = celestial_system(map, &entry.name) {
+            evidence += 1;
+            *votes.entry(system.system_id).or_default() += 1;
+        }
+    }
+
+    let top = votes.values().copied().max()?;
+    let leaders: Vec<i64> = votes
+        .iter()
+        .filter(|(_, count)| **count == top)
+        .map(|(system_id, _)| *system_id)
+        .collect();
+    let system = leaders
+        .iter()
+        .filter_map(|system_id| map.system(*system_id))
+        .min_by_key(|system| system.system_id)?;
+
+    Some(DetectedSystem {
+        system_id: system.system_id,
+        name: system.name.clone(),
+        confidence: top as f64 / evidence as f64 / leaders.len() as f64,
+        evidence,
+    })
+}
+
+/// "Stargate (Perimeter)" -> "Perimeter".
+fn stargate_destination(name: &str) -> Option<&str> {
+    name.trim()
+        .strip_prefix("Stargate (")?
+        .strip_suffix(')')
+        .map(str::trim)
+}
+
+/// The system a celestial or station is named after: the part before the
+/// first " - ", without a trailing "(Oris)"-style planet name or roman
+/// numeral.
+fn celestial_system<'a>(map: &'a MapIndex, name: &str) -> Option<&'a SolarSystemEntry> {
+    let head = name.split(" - ").next()?.trim();
+    let head = match head.find(" (") {
+        Some(index) if head.ends_with(')') => &head[..index],
+        _ => head,
+    };
+
+    map.system_by_name(head).or_else(|| {
+        let (system, numeral) = head.rsplit_once(' ')?;
+        numeral
+            .chars()
+            .all(|char| matches!(char, 'I' | 'V' | 'X' | 'L'))
+            .then(|| map.system_by_name(system))
+            .flatten()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DscanRange;
+
+    fn system(system_id: i64, name: &str) -> SolarSystemEntry {
+        SolarSystemEntry {
+            system_id,
+            name: name.to_string(),
+            constellation_id: 1,
+            constellation_name: String::new(),
+            region_id: 1,
+            region_name: String::new(),
+            security_status: 0.9,
+            wormhole_class: None,
+            position: None,
+        }
+    }
+
+    /// Jita connects to Perimeter, Maurasi and New Caldari; Perimeter also
+    /// connects to Urlen.
+    fn map() -> MapIndex {
+        MapIndex::from_systems(
+            vec![
+                system(30000142, "Jita"),
+                system(30000144, "Perimeter"),
+                system(30000140, "Maurasi"),
+                system(30000145, "New Caldari"),
+                system(30000139, "Urlen"),
+                system(30002187, "Amarr"),
+            ],
+            &[
+                (30000142, 30000144),
+                (30000142, 30000140),
+                (30000142, 30000145),
+                (30000144, 30000139),
+            ],
+        )
+    }
+
+    fn row(name: &str, kind: ScanObjectKind) -> DscanEntry {
+        DscanEntry {
+            type_id: None,
+            name: name.to_string(),
+            type_name: String::new(),
+            display_type_name: None,
+            distance: None,
+            distance_m: None,
+            range: DscanRange::Unknown,
+            group_id: None,
+            group_name: None,
+            category_id: None,
+            category_name: None,
+            kind,
+            is_ship: false,
+            hull_roles: Vec::new(),
+            tech_level: None,
+        }
+    }
+
+    fn celestial(name: &str) -> DscanEntry {
+        row(name, ScanObjectKind::Celestial)
+    }
+
+    fn station(name: &str) -> DscanEntry {
+        DscanEntry {
+            category_id: Some(categories::STATION),
+            ..row(name, ScanObjectKind::Structure)
+        }
+    }
+
+    #[test]
+    fn celestial_names_pin_the_system() {
+        let entries = vec![
+            celestial("Jita IV - Moon 4"),
+            celestial("Jita - Star"),
+            celestial("Jita IV"),
+            station("Jita IV - Moon 4 - Caldari Navy Assembly Plant"),
+            // Ship and player structure names never count, even when they
+            // look like celestials.
+            row("Amarr VIII", ScanObjectKind::Ship),
+            row("Amarr - Trade Hub", ScanObjectKind::Structure),
+        ];
+        let detected = detect_system(&map(), &entries).unwrap();
+        assert_eq!(detected.system_id, 30000142);
+        assert_eq!(detected.name, "Jita");
+        assert_eq!(detected.evidence, 4);
+        assert_eq!(detected.confidence, 1.0);
+    }
+
+    #[test]
+    fn planet_names_and_multi_word_systems_resolve() {
+        let detected = detect_system(
+            &map(),
+            &[
+                celestial("New Caldari - Star"),
+                celestial("New Caldari II (Prime)"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(detected.name, "New Caldari");
+        assert_eq!(detected.evidence, 2);
+    }
+
+    #[test]
+    fn stargates_narrow_down_to_common_neighbours() {
+        // A single gate to Perimeter: Jita or Urlen.
+        let detected = detect_system(&map(), &[celestial("Stargate (Perimeter)")]).unwrap();
+        assert_eq!(detected.confidence, 0.5);
+
+        // Gates to Perimeter and Maurasi: only Jita borders both.
+        let detected = detect_system(
+            &map(),
+            &[
+                celestial("Stargate (Perimeter)"),
+                celestial("Stargate (Maurasi)"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(detected.name, "Jita");
+        assert_eq!(detected.confidence, 1.0);
+    }
+
+    #[test]
+    fn conflicting_evidence_lowers_confidence() {
+        let detected = detect_system(
+            &map(),
+            &[
+                celestial("Jita IV"),
+                celestial("Jita V"),
+                celestial("Jita VI"),
+                celestial("Amarr VIII (Oris)"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(detected.name, "Jita");
+        assert_eq!(detected.confidence, 0.75);
+        assert!(detected.confidence >= MIN_AUTOFILL_CONFIDENCE);
+    }
+
+    #[test]
+    fn nothing_recognizable_detects_nothing() {
+        assert!(detect_system(&map(), &[celestial("Unknown Anomaly")]).is_none());
+        assert!(detect_system(&map(), &[]).is_none());
+    }
+}
@@ -0,0 +1,413 @@
+//! Pure solar-system index: lookups by ID and name, the ranked
+//! prefix/fuzzy search behind `resolve_system`, and stargate routing.
+//! Built by `crate::sde` from the SDE map files.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+
+use crate::models::{Route, RoutePreference, SolarSystemEntry, SystemStats};
+
+/// Longest typo a fuzzy match forgives.
+const MAX_FUZZY_DISTANCE: usize = 2;
+
+/// Cost of entering a low/null/J-space system on a safer route. Large
+/// enough that the route minimizes such systems first and jumps second.
+const UNSAFE_SYSTEM_COST: u64 = 1_000;
+
+#[derive(Debug, Default)]
+pub struct MapIndex {
+    systems: HashMap<i64, SolarSystemEntry>,
+    name_to_system_id: HashMap<String, i64>,
+    /// Stargate connections, both directions.
+    gates: HashMap<i64, Vec<i64>>,
+}
+
+/// How a query matched a system name; earlier variants rank higher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchTier {
+    Exact,
+    Prefix,
+    Contains,
+    Fuzzy(usize),
+}
+
+impl MapIndex {
+    /// `stargates` lists each connection once or twice, in either direction.
+    pub fn from_systems(systems: Vec<SolarSystemEntry>, stargates: &[(i64, i64)]) -> Self {
+        let name_to_system_id = systems
+            .iter()
+            .map(|system| (normalize(&system.name), system.system_id))
+            .collect();
+        let systems = systems
+            .into_iter()
+            .map(|system| (system.system_id, system))
+            .collect();
+
+        let mut gates: HashMap<i64, Vec<i64>> = HashMap::new();
+        for &(from, to) in stargates {
+            gates.entry(from).or_default().push(to);
+            gates.entry(to).or_default().push(from);
+        }
+        for neighbours in gates.values_mut() {
+            neighbours.sort_unstable();
+            neighbours.dedup();
+        }
+
+        MapIndex {
+            systems,
+            name_to_system_id,
+            gates,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.systems.is_empty()
+    }
+
+    pub fn systems(&self) -> impl Iterator<Item = &SolarSystemEntry> {
+        self.systems.values()
+    }
+
+    pub fn system(&self, system_id: i64) -> Option<&SolarSystemEntry> {
+        self.systems.get(&system_id)
+    }
+
+    /// Exact, case-insensitive name lookup.
+    pub fn system_by_name(&self, name: &str) -> Option<&SolarSystemEntry> {
+        self.name_to_system_id
+            .get(&normalize(name))
+            .and_then(|id| self.systems.get(id))
+    }
+
+    /// Systems matching `query`, best first: exact name, then prefix, then
+    /// substring, then names within a couple of typos. Shorter names win
+    /// ties, so "Jita" ranks above "Jitanen" for "jit".
+    pub fn resolve(&self, query: &str, limit: usize) -> Vec<SolarSystemEntry> {
+        let query = normalize(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<(MatchTier, &SolarSystemEntry)> = self
+            .systems
+            .values()
+            .filter_map(|system| Some((match_tier(&query, &normalize(&system.name))?, system)))
+            .collect();
+        matches.sort_by(|(tier_a, a), (tier_b, b)| {
+            tier_a
+                .cmp(tier_b)
+                .then_with(|| a.name.len().cmp(&b.name.len()))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, system)| system.clone())
+            .collect()
+    }
+
+    /// Gate route from `origin` to `destination`, never entering a system
+    /// in `avoid` (the origin itself is always allowed). `Safer` avoids
+    /// leaving high-sec wherever a longer route allows it. `None` when
+    /// either end is unknown or no route exists.
+    pub fn route(
+        &self,
+        origin: i64,
+        destination: i64,
+        preference: RoutePreference,
+        avoid: &[i64],
+    ) -> Option<Route> {
+        if !self.systems.contains_key(&origin) || !self.systems.contains_key(&destination) {
+            return None;
+        }
+        let avoid: HashSet<i64> = avoid.iter().copied().filter(|id| *id != origin).collect();
+        let cost = |system_id: i64| match preference {
+            RoutePreference::Shortest => 1,
+            RoutePreference::Safer => match self.systems.get(&system_id) {
+                Some(system) if is_highsec(system.security_status) => 1,
+                _ => UNSAFE_SYSTEM_COST,
+            },
+        };
+
+        // Dijkstra; with unit costs it degenerates to BFS order.
+        let mut best: HashMap<i64, u64> = HashMap::from([(origin, 0)]);
+        let mut previous: HashMap<i64, i64> = HashMap::new();
+        let mut frontier = BinaryHeap::from([Reverse((0u64, origin))]);
+        while let Some(Reverse((distance, system_id))) = frontier.pop() {
+            if system_id == destination {
+                break;
+            }
+            if best.get(&system_id).is_some_and(|known| distance > *known) {
+                continue;
+            }
+            for &next in self.neighbours(system_id) {
+                if avoid.contains(&next) {
+                    continue;
+                }
+                let candidate = distance + cost(next);
+                if best.get(&next).is_none_or(|known| candidate < *known) {
+                    best.insert(next, candidate);
+                    previous.insert(next, system_id);
+                    frontier.push(Reverse((candidate, next)));
+                }
+            }
+        }
+
+        if !best.contains_key(&destination) {
+            return None;
+        }
+        let mut path = vec![destination];
+        while let Some(&step) = previous.get(path.last()?) {
+            path.push(step);
+        }
+        path.reverse();
+
+        Some(Route {
+            jumps: path.len() - 1,
+            systems: path
+                .iter()
+                .filter_map(|id| self.systems.get(id).cloned())
+                .collect(),
+        })
+    }
+
+    /// Systems one gate jump away.
+    pub fn neighbours(&self, system_id: i64) -> &[i64] {
+        self.gates.get(&system_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Shortest gate distance from `origin` to every reachable system.
+    pub fn jump_distances(&self, origin: i64) -> HashMap<i64, usize> {
+        let mut distances = HashMap::from([(origin, 0)]);
+        let mut queue = VecDeque::from([origin]);
+        while let Some(system_id) = queue.pop_front() {
+            let next_distance = distances[&system_id] + 1;
+            for &next in self.neighbours(system_id) {
+                if let Entry::Vacant(entry) = distances.entry(next) {
+                    entry.insert(next_distance);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+}
+
+/// High-sec as the client shows it: security rounded to one decimal is at
+/// least 0.5.
+pub fn is_highsec(security_status: f64) -> bool {
+    (security_status * 10.0).round() >= 5.0
+}
+
+/// Set each system's `jumps` from precomputed [`MapIndex::jump_distances`];
+/// unreachable systems get `None`.
+pub fn annotate_jumps(systems: &mut [SystemStats], distances: &HashMap<i64, usize>) {
+    for system in systems {
+        system.jumps = distances.get(&system.system_id).copied();
+    }
+}
+
+fn match_tier(query: &str, name: &str) -> Option<MatchTier> {
+    if name == query {
+        return Some(MatchTier::Exact);
+    }
+    if name.starts_with(query) {
+        return Some(MatchTier::Prefix);
+    }
+    if name.contains(query) {
+        return Some(MatchTier::Contains);
+    }
+
+    // Short queries would fuzzy-match half the map.
+    let allowed = (query.chars().count() / 4).min(MAX_FUZZY_DISTANCE);
+    if allowed == 0 {
+        return None;
+    }
+    // Compare against the whole name and against its prefix of the query's
+    // length, so a typo in a partial name still matches.
+    let prefix: String = name.chars().take(query.chars().count()).collect();
+    let distance = edit_distance(query, name).min(edit_distance(query, &prefix));
+    (distance <= allowed).then_some(MatchTier::Fuzzy(distance))
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, char_a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(char_a != *char_b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system(system_id: i64, name: &str, security_status: f64) -> SolarSystemEntry {
+        SolarSystemEntry {
+            system_id,
+            name: name.to_string(),
+            constellation_id: 20000020,
+            constellation_name: "Kimotoro".to_string(),
+            region_id: 10000002,
+            region_name: "The Forge".to_string(),
+            security_status,
+            wormhole_class: None,
+            position: None,
+        }
+    }
+
+    fn index() -> MapIndex {
+        MapIndex::from_systems(
+            vec![
+                system(30000142, "Jita", 0.95),
+                system(30000140, "Maurasi", 0.89),
+                system(30000144, "Perimeter", 0.95),
+                system(30002187, "Amarr", 1.0),
+                system(30045349, "Jitanen", 0.3),
+            ],
+            &[],
+        )
+    }
+
+    /// A(hs) - B(ls) - D(hs), and the long way round A - C - E - D, all
+    /// high-sec. F is disconnected.
+    fn graph() -> MapIndex {
+        MapIndex::from_systems(
+            vec![
+                system(1, "A", 1.0),
+                system(2, "B", 0.3),
+                system(3, "C", 0.5),
+                system(4, "D", 0.45),
+                system(5, "E", 0.7),
+                system(6, "F", 0.9),
+            ],
+            &[(1, 2), (2, 1), (2, 4), (1, 3), (3, 5), (5, 4)],
+        )
+    }
+
+    fn route_ids(route: Option<Route>) -> Option<Vec<i64>> {
+        route.map(|route| route.systems.iter().map(|s| s.system_id).collect())
+    }
+
+    fn names(systems: &[SolarSystemEntry]) -> Vec<&str> {
+        systems.iter().map(|system| system.name.as_str()).collect()
+    }
+
+    #[test]
+    fn lookups_by_id_and_name() {
+        let index = index();
+        assert_eq!(
+            index.system(30000142).map(|s| s.name.as_str()),
+            Some("Jita")
+        );
+        assert_eq!(
+            index.system_by_name("  jita ").map(|s| s.system_id),
+            Some(30000142)
+        );
+        assert!(index.system_by_name("Jit").is_none());
+    }
+
+    #[test]
+    fn resolve_ranks_exact_then_prefix_then_substring() {
+        let index = index();
+        assert_eq!(names(&index.resolve("jita", 10)), vec!["Jita", "Jitanen"]);
+        assert_eq!(names(&index.resolve("ji", 10)), vec!["Jita", "Jitanen"]);
+        assert_eq!(names(&index.resolve("meter", 10)), vec!["Perimeter"]);
+        assert_eq!(index.resolve("ji", 1).len(), 1);
+        assert!(index.resolve("  ", 10).is_empty());
+    }
+
+    #[test]
+    fn resolve_forgives_typos_in_longer_queries() {
+        let index = index();
+        assert_eq!(names(&index.resolve("Perimiter", 10)), vec!["Perimeter"]);
+        assert_eq!(names(&index.resolve("Mauras1", 10)), vec!["Maurasi"]);
+        // Partial name with a typo.
+        assert_eq!(names(&index.resolve("Perim1t", 10)), vec!["Perimeter"]);
+        // Too short to guess at.
+        assert!(index.resolve("Jxa", 10).is_empty());
+    }
+
+    #[test]
+    fn shortest_route_takes_fewest_jumps() {
+        let route = graph().route(1, 4, RoutePreference::Shortest, &[]).unwrap();
+        assert_eq!(route.jumps, 2);
+        assert_eq!(route_ids(Some(route)), Some(vec![1, 2, 4]));
+    }
+
+    #[test]
+    fn safer_route_stays_in_highsec() {
+        let route = graph().route(1, 4, RoutePreference::Safer, &[]);
+        assert_eq!(route_ids(route), Some(vec![1, 3, 5, 4]));
+    }
+
+    #[test]
+    fn avoided_systems_are_routed_around() {
+        let map = graph();
+        assert_eq!(
+            route_ids(map.route(1, 4, RoutePreference::Shortest, &[2])),
+            Some(vec![1, 3, 5, 4])
+        );
+        assert_eq!(
+            map.route(1, 4, RoutePreference::Shortest, &[2, 5])
+                .map(|r| r.jumps),
+            None
+        );
+        // Avoiding the origin is meaningless.
+        assert!(map.route(1, 4, RoutePreference::Shortest, &[1]).is_some());
+        assert!(map.route(1, 6, RoutePreference::Shortest, &[]).is_none());
+        assert!(map.route(1, 99, RoutePreference::Shortest, &[]).is_none());
+        assert_eq!(
+            map.route(1, 1, RoutePreference::Shortest, &[])
+                .map(|r| r.jumps),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn jump_distances_annotate_top_systems() {
+        let distances = graph().jump_distances(1);
+        assert_eq!(distances.get(&4), Some(&2));
+        assert_eq!(distances.get(&6), None);
+
+        let stats = |system_id| SystemStats {
+            system_id,
+            system_name: String::new(),
+            kills: 1,
+            jumps: None,
+        };
+        let mut systems = vec![stats(5), stats(6)];
+        annotate_jumps(&mut systems, &distances);
+        assert_eq!(systems[0].jumps, Some(2));
+        assert_eq!(systems[1].jumps, None);
+    }
+
+    #[test]
+    fn highsec_uses_the_rounded_security_status() {
+        assert!(is_highsec(0.45));
+        assert!(!is_highsec(0.44));
+        assert!(!is_highsec(-0.2));
+    }
+
+    #[test]
+    fn edit_distance_counts_single_char_edits() {
+        assert_eq!(edit_distance("jita", "jita"), 0);
+        assert_eq!(edit_distance("jita", "jiat"), 2);
+        assert_eq!(edit_distance("amarr", "amar"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
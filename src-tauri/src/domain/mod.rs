@@ -4,9 +4,21 @@
 //! by the command/service layer that drives these machines.
 
 pub mod deeplink;
+pub mod doctrine;
+pub mod dogma;
 pub mod dscan;
+pub mod fleet;
+pub mod hull_taxonomy;
 pub mod intel_reducer;
+pub mod jump_range;
+pub mod locate;
 pub mod lookup;
+pub mod map;
+pub mod overview;
+pub mod paste;
+pub mod probe;
 pub mod sde_lifecycle;
 pub mod threat;
+pub mod type_search;
 pub mod version;
+pub mod zip_directory;
@@ -0,0 +1,272 @@
+//! Pure overview parsing. Overview rows are tab separated, but which
+//! columns exist (and in what order) depends on the player's overview
+//! settings, so the layout is read from a header row when one is pasted and
+//! inferred from the cell contents otherwise.
+
+use std::collections::HashMap;
+
+use crate::domain::dscan::{object_kind, parse_distance, range_bucket, SdeIndex};
+use crate::models::{
+    DscanCount, OverviewColumns, OverviewEntry, OverviewParseResult, ScanObjectKind,
+};
+
+/// Share of a column's non-empty cells that must fit a kind for the column
+/// to be inferred as that kind.
+const MIN_COLUMN_SHARE: f64 = 0.5;
+/// Corporation and alliance tickers are at most five characters.
+const MAX_TICKER_LEN: usize = 5;
+
+/// Parse raw overview text against the type index. Pure function.
+pub fn parse_overview_text(index: &SdeIndex, text: &str) -> OverviewParseResult {
+    let mut rows: Vec<Vec<&str>> = text
+        .lines()
+        .map(|line| line.split('\t').map(str::trim).collect::<Vec<_>>())
+        .filter(|columns| columns.iter().any(|cell| !cell.is_empty()))
+        .collect();
+
+    let columns = match rows.first().and_then(|first| header_columns(first)) {
+        Some(columns) => {
+            rows.remove(0);
+            columns
+        }
+        None => infer_columns(index, &rows),
+    };
+
+    let cell = |row: &[&str], column: Option<usize>| {
+        column
+            .and_then(|column| row.get(column))
+            .filter(|value| !value.is_empty() && **value != "-")
+            .map(|value| value.to_string())
+    };
+
+    let entries: Vec<OverviewEntry> = rows
+        .iter()
+        .map(|row| {
+            let type_name = cell(row, columns.type_name);
+            let classification = type_name
+                .as_deref()
+                .and_then(|name| index.classify(None, name));
+            let distance = cell(row, columns.distance);
+            let distance_m = distance.as_deref().and_then(parse_distance);
+            let kind = classification.map_or(ScanObjectKind::Other, object_kind);
+
+            OverviewEntry {
+                name: cell(row, columns.name).unwrap_or_default(),
+                type_id: classification.map(|entry| entry.type_id),
+                type_name,
+                group_id: classification.map(|entry| entry.group_id),
+                group_name: classification.map(|entry| entry.group_name.clone()),
+                category_id: classification.map(|entry| entry.category_id),
+                is_ship: kind == ScanObjectKind::Ship,
+                kind,
+                range: range_bucket(distance_m),
+                distance,
+                distance_m,
+                corporation_ticker: cell(row, columns.corporation).map(|t| strip_ticker(&t)),
+                alliance_ticker: cell(row, columns.alliance).map(|t| strip_ticker(&t)),
+            }
+        })
+        .collect();
+
+    OverviewParseResult {
+        total_rows: entries.len(),
+        ship_count: entries.iter().filter(|entry| entry.is_ship).count(),
+        by_corporation: ticker_counts(entries.iter().map(|e| e.corporation_ticker.as_deref())),
+        by_alliance: ticker_counts(entries.iter().map(|e| e.alliance_ticker.as_deref())),
+        columns,
+        entries,
+    }
+}
+
+/// Read the layout from a header row ("Distance", "Name", "Type",
+/// "Corporation", "Alliance"). Returns `None` when the row has no name or
+/// type header, i.e. it is data.
+fn header_columns(row: &[&str]) -> Option<OverviewColumns> {
+    let mut columns = OverviewColumns::default();
+    for (position, cell) in row.iter().enumerate() {
+        let slot = match cell.to_lowercase().as_str() {
+            "name" => &mut columns.name,
+            "type" => &mut columns.type_name,
+            "distance" => &mut columns.distance,
+            "corporation" | "corp" => &mut columns.corporation,
+            "alliance" => &mut columns.alliance,
+            _ => continue,
+        };
+        slot.get_or_insert(position);
+    }
+    (columns.name.is_some() || columns.type_name.is_some()).then_some(columns)
+}
+
+/// Infer the layout from cell contents: distances parse as distances, types
+/// are known to the index, "[CORP]" and "<ALLY>" tickers keep the client's
+/// brackets. Of what's left, the first text column is the name and short
+/// columns after it are bare corporation then alliance tickers.
+fn infer_columns(index: &SdeIndex, rows: &[Vec<&str>]) -> OverviewColumns {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let share = |column: usize, fits: &dyn Fn(&str) -> bool| {
+        let cells: Vec<&str> = rows
+            .iter()
+            .filter_map(|row| row.get(column).copied())
+            .filter(|cell| !cell.is_empty())
+            .collect();
+        if cells.is_empty() {
+            return 0.0;
+        }
+        cells.iter().filter(|cell| fits(cell)).count() as f64 / cells.len() as f64
+    };
+
+    let mut columns = OverviewColumns::default();
+    let mut unassigned = Vec::new();
+    for column in 0..width {
+        let slot = if share(column, &|cell| {
+            cell == "-" || parse_distance(cell).is_some()
+        }) >= MIN_COLUMN_SHARE
+        {
+            &mut columns.distance
+        } else if share(column, &|cell| index.classify(None, cell).is_some()) >= MIN_COLUMN_SHARE {
+            &mut columns.type_name
+        } else if share(column, &|cell| is_wrapped(cell, '[', ']')) >= MIN_COLUMN_SHARE {
+            &mut columns.corporation
+        } else if share(column, &|cell| is_wrapped(cell, '<', '>')) >= MIN_COLUMN_SHARE {
+            &mut columns.alliance
+        } else {
+            unassigned.push(column);
+            continue;
+        };
+        slot.get_or_insert(column);
+    }
+
+    let mut unassigned = unassigned.into_iter();
+    columns.name = unassigned.next();
+    for column in unassigned {
+        let is_ticker = share(column, &|cell| cell.chars().count() <= MAX_TICKER_LEN);
+        if is_ticker < MIN_COLUMN_SHARE {
+            continue;
+        }
+        if columns.corporation.is_none() {
+            columns.corporation = Some(column);
+        } else if columns.alliance.is_none() {
+            columns.alliance = Some(column);
+        }
+    }
+    columns
+}
+
+fn is_wrapped(cell: &str, open: char, close: char) -> bool {
+    cell.len() > 2 && cell.starts_with(open) && cell.ends_with(close)
+}
+
+fn strip_ticker(raw: &str) -> String {
+    raw.trim_matches(|char: char| matches!(char, '[' | ']' | '<' | '>') || char.is_whitespace())
+        .to_string()
+}
+
+fn ticker_counts<'a>(tickers: impl Iterator<Item = Option<&'a str>>) -> Vec<DscanCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for ticker in tickers.flatten().filter(|ticker| !ticker.is_empty()) {
+        *counts.entry(ticker).or_default() += 1;
+    }
+    let mut counts: Vec<DscanCount> = counts
+        .into_iter()
+        .map(|(name, count)| DscanCount {
+            id: None,
+            name: name.to_string(),
+            count,
+        })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DscanRange, ScanTypeIndexEntry};
+    use std::collections::BTreeMap;
+
+    fn index() -> SdeIndex {
+        let entry = |type_id, type_name: &str, group_id, category_id| ScanTypeIndexEntry {
+            type_id,
+            type_name: type_name.to_string(),
+            type_names: BTreeMap::new(),
+            group_id,
+            group_name: String::new(),
+            group_names: BTreeMap::new(),
+            category_id,
+            category_name: String::new(),
+        };
+        SdeIndex::from_entries(vec![
+            entry(587, "Rifter", 25, 6),
+            entry(22456, "Sabre", 541, 6),
+            entry(35832, "Astrahus", 1657, 65),
+        ])
+    }
+
+    #[test]
+    fn header_row_defines_the_layout() {
+        let text = "Name\tType\tCorporation\tAlliance\tDistance\n\
+                    Some Pilot\tRifter\t[ABC]\t<XYZ>\t12 km\n\
+                    Other Pilot\tSabre\t[DEF]\t\t2,5 AU";
+        let result = parse_overview_text(&index(), text);
+        assert_eq!(result.columns.name, Some(0));
+        assert_eq!(result.columns.distance, Some(4));
+        assert_eq!(result.total_rows, 2);
+        assert_eq!(result.ship_count, 2);
+
+        let row = &result.entries[0];
+        assert_eq!(row.name, "Some Pilot");
+        assert_eq!(row.type_id, Some(587));
+        assert_eq!(row.corporation_ticker.as_deref(), Some("ABC"));
+        assert_eq!(row.alliance_ticker.as_deref(), Some("XYZ"));
+        assert_eq!(row.range, DscanRange::OnGrid);
+        assert_eq!(result.entries[1].alliance_ticker, None);
+        assert_eq!(result.entries[1].range, DscanRange::InSystem);
+    }
+
+    #[test]
+    fn layout_is_inferred_without_a_header() {
+        let text = "2 345 m\tSome Pilot\tRifter\t[ABC]\t<XYZ>\n\
+                    14 km\tOther Pilot\tSabre\t[ABC]\t<XYZ>\n\
+                    -\tHome Base\tAstrahus\t[ABC]\t";
+        let result = parse_overview_text(&index(), text);
+        let columns = &result.columns;
+        assert_eq!(
+            (
+                columns.distance,
+                columns.name,
+                columns.type_name,
+                columns.corporation,
+                columns.alliance
+            ),
+            (Some(0), Some(1), Some(2), Some(3), Some(4))
+        );
+        assert_eq!(result.ship_count, 2);
+        assert_eq!(result.entries[2].kind, ScanObjectKind::Structure);
+        assert_eq!(result.entries[2].distance, None);
+        assert_eq!(result.by_corporation[0].name, "ABC");
+        assert_eq!(result.by_corporation[0].count, 3);
+        assert_eq!(result.by_alliance[0].count, 2);
+    }
+
+    #[test]
+    fn bare_tickers_follow_the_name_column() {
+        let text = "Some Pilot\tRifter\tABC\tXYZ\nOther Pilot\tSabre\tDEF\tXYZ";
+        let result = parse_overview_text(&index(), text);
+        assert_eq!(result.columns.name, Some(0));
+        assert_eq!(result.columns.type_name, Some(1));
+        assert_eq!(result.columns.corporation, Some(2));
+        assert_eq!(result.columns.alliance, Some(3));
+        assert_eq!(result.entries[1].corporation_ticker.as_deref(), Some("DEF"));
+    }
+
+    #[test]
+    fn unknown_types_are_kept_unclassified() {
+        let text = "Name\tType\nSomething\tMystery Hull";
+        let result = parse_overview_text(&index(), text);
+        let row = &result.entries[0];
+        assert_eq!(row.type_name.as_deref(), Some("Mystery Hull"));
+        assert_eq!(row.type_id, None);
+        assert_eq!(row.kind, ScanObjectKind::Other);
+        assert!(!row.is_ship);
+    }
+}
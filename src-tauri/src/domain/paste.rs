@@ -0,0 +1,211 @@
+//! Pure paste classification and normalization: decides which pipeline a
+//! clipboard paste belongs to (local, d-scan, probe scanner, overview,
+//! fleet composition or killmail) and cleans pilot names up before they
+//! reach the lookup.
+
+use std::collections::HashSet;
+
+use crate::domain::dscan::parse_distance;
+use crate::domain::probe::is_signature_id;
+use crate::models::PasteKind;
+
+/// EVE character names are 3 to 37 characters long.
+const MIN_NAME_LEN: usize = 3;
+const MAX_NAME_LEN: usize = 37;
+/// A first name, an optional middle name and a family name.
+const MAX_NAME_SPACES: usize = 2;
+
+const FLEET_POSITIONS: &[&str] = &[
+    "fleet commander",
+    "wing commander",
+    "squad commander",
+    "squad member",
+];
+
+/// Strip zero-width and byte-order-mark characters the client and chat
+/// channels like to leave behind, and unify line endings.
+pub fn normalize_paste(text: &str) -> String {
+    text.chars()
+        .filter(|char| !matches!(char, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}'))
+        .collect::<String>()
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+}
+
+/// Detect what a (normalized) paste is. Killmails are recognized by their
+/// section headers; everything else by a majority vote over its lines.
+pub fn detect_paste_kind(text: &str) -> PasteKind {
+    if is_killmail(text) {
+        return PasteKind::Killmail;
+    }
+
+    let mut votes: Vec<(PasteKind, usize)> = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let kind = line_kind(line);
+        match votes.iter_mut().find(|(voted, _)| *voted == kind) {
+            Some((_, count)) => *count += 1,
+            None => votes.push((kind, 1)),
+        }
+    }
+
+    // Ties go to the kind seen first.
+    votes
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map_or(PasteKind::Unknown, |(kind, _)| kind)
+}
+
+fn line_kind(line: &str) -> PasteKind {
+    let columns: Vec<&str> = line.split('\t').map(str::trim).collect();
+    if columns.len() == 1 {
+        return if is_valid_character_name(columns[0]) {
+            PasteKind::Local
+        } else {
+            PasteKind::Unknown
+        };
+    }
+
+    if is_signature_id(columns[0]) {
+        return PasteKind::Probe;
+    }
+    if columns.len() >= 5 && FLEET_POSITIONS.contains(&columns[4].to_lowercase().as_str()) {
+        return PasteKind::Fleet;
+    }
+    let leading_type_id = columns[0].is_empty() || columns[0].parse::<i64>().is_ok();
+    let trailing_distance = columns
+        .last()
+        .is_some_and(|last| *last == "-" || parse_distance(last).is_some());
+    if (3..=4).contains(&columns.len()) && leading_type_id && trailing_distance {
+        return PasteKind::Dscan;
+    }
+    PasteKind::Overview
+}
+
+fn is_killmail(text: &str) -> bool {
+    let has_line = |prefix: &str| text.lines().any(|line| line.trim().starts_with(prefix));
+    has_line("Victim:") && has_line("Involved parties:")
+}
+
+/// EVE character-name rules: 3-37 ASCII letters, digits, spaces, hyphens
+/// and apostrophes; at most two single spaces; no space, hyphen or
+/// apostrophe at either end.
+pub fn is_valid_character_name(name: &str) -> bool {
+    let is_edge = |char: char| matches!(char, ' ' | '-' | '\'');
+    (MIN_NAME_LEN..=MAX_NAME_LEN).contains(&name.len())
+        && name
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || is_edge(char))
+        && !name.starts_with(is_edge)
+        && !name.ends_with(is_edge)
+        && !name.contains("  ")
+        && name.matches(' ').count() <= MAX_NAME_SPACES
+}
+
+/// Valid pilot names from a local member list, deduplicated
+/// case-insensitively in paste order.
+pub fn local_pilot_names(text: &str) -> Vec<String> {
+    unique_names(text.lines().map(str::trim))
+}
+
+/// The victim and every involved party of a killmail.
+pub fn killmail_pilot_names(text: &str) -> Vec<String> {
+    unique_names(text.lines().filter_map(|line| {
+        let line = line.trim();
+        let name = line
+            .strip_prefix("Victim:")
+            .or_else(|| line.strip_prefix("Name:"))?;
+        // "Name: Some Pilot (laid the final blow)"
+        Some(name.split(" (").next().unwrap_or(name).trim())
+    }))
+}
+
+fn unique_names<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    names
+        .filter(|name| is_valid_character_name(name))
+        .filter(|name| seen.insert(name.to_lowercase()))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_strips_zero_width_characters() {
+        let text = "\u{FEFF}Some\u{200B} Pilot\r\nOther\u{2060} Pilot\r";
+        assert_eq!(normalize_paste(text), "Some Pilot\nOther Pilot\n");
+    }
+
+    #[test]
+    fn detects_every_paste_kind() {
+        let cases = [
+            ("Some Pilot\nOther Pilot\nThird Guy", PasteKind::Local),
+            (
+                "587\tSome Pilot's Rifter\tRifter\t2,3 km\n\tMoon 1\tMoon\t-",
+                PasteKind::Dscan,
+            ),
+            (
+                "ABC-123\tCosmic Signature\tData Site\tAmplifier\t100,0%\t4,36 AU",
+                PasteKind::Probe,
+            ),
+            (
+                "12 km\tSome Pilot\tRifter\t[ABC]\t<XYZ>\n2 AU\tOther\tSabre\t[ABC]\t",
+                PasteKind::Overview,
+            ),
+            (
+                "Boss\tJita\tSabre\tInterdictor\tFleet Commander\t5 - 5 - 5\t\n\
+                 Alpha\tJita\tMuninn\tHeavy Assault Cruiser\tSquad Member\t0 - 0 - 5\tWing 1 / Squad 1",
+                PasteKind::Fleet,
+            ),
+            (
+                "2024.01.01 12:00:00\n\nVictim: Some Pilot\nDestroyed: Rifter\n\n\
+                 Involved parties:\n\nName: Other Pilot (laid the final blow)",
+                PasteKind::Killmail,
+            ),
+            ("!!!\n???", PasteKind::Unknown),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(detect_paste_kind(text), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn character_name_rules() {
+        for valid in ["Bob", "Some Pilot", "Mr O'Neil", "Jean-Luc Picard", "A B C"] {
+            assert!(is_valid_character_name(valid), "{valid}");
+        }
+        for invalid in [
+            "Al",
+            " Some Pilot",
+            "Some Pilot-",
+            "'Quote",
+            "Two  Spaces",
+            "Too Many Spaces Here",
+            "Ünicode Pilot",
+            "Name_With_Underscore",
+            "An Extremely Long Character Name Exceeding",
+        ] {
+            assert!(!is_valid_character_name(invalid), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn local_names_are_deduplicated_and_validated() {
+        let names = local_pilot_names("Some Pilot\nsome pilot\n  Other Pilot \nx\n\nOther Pilot");
+        assert_eq!(names, vec!["Some Pilot", "Other Pilot"]);
+    }
+
+    #[test]
+    fn killmail_names_include_victim_and_attackers() {
+        let text = "Victim: Some Pilot\nCorp: Some Corp\nInvolved parties:\n\n\
+                    Name: Other Pilot (laid the final blow)\nShip: Sabre\n\n\
+                    Name: Third Pilot\nShip: Rifter";
+        assert_eq!(
+            killmail_pilot_names(text),
+            vec!["Some Pilot", "Other Pilot", "Third Pilot"]
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Pure probe-scanner parsing: turns a paste of the probe scanner window
+//! into typed signatures, and diffs two pastes of the same system.
+//!
+//! A row is tab separated: ID ("ABC-123"), scan group ("Cosmic
+//! Signature"), group ("Data Site"), type / site name, signal strength
+//! ("100,0%") and distance ("4,36 AU"). Group and name stay empty until the
+//! signature is scanned down far enough.
+
+use std::collections::HashMap;
+
+use crate::domain::dscan::{parse_distance, parse_localized_number};
+use crate::models::{ProbeDiff, ProbeParseResult, ProbeSignature, SignatureKind};
+
+/// Signal strength at which a signature counts as fully scanned.
+const RESOLVED_SIGNAL: f64 = 100.0;
+
+/// Parse raw probe-scanner text. Rows without a valid signature ID are
+/// skipped. Pure function.
+pub fn parse_probe_text(text: &str) -> ProbeParseResult {
+    let mut signatures = Vec::new();
+
+    for raw_line in text.lines() {
+        let columns: Vec<&str> = raw_line.split('\t').map(str::trim).collect();
+        let Some(id) = columns.first().filter(|id| is_signature_id(id)) else {
+            continue;
+        };
+
+        let column = |index: usize| {
+            columns
+                .get(index)
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        };
+        let group = column(2);
+        let distance = column(5);
+
+        signatures.push(ProbeSignature {
+            id: id.to_string(),
+            scan_group: column(1).unwrap_or_default(),
+            kind: group
+                .as_deref()
+                .map_or(SignatureKind::Unknown, signature_kind),
+            group,
+            site_name: column(3),
+            signal_strength: column(4).as_deref().and_then(parse_signal_strength),
+            distance_m: distance.as_deref().and_then(parse_distance),
+            distance,
+        });
+    }
+
+    ProbeParseResult {
+        total_rows: signatures.len(),
+        resolved_count: signatures.iter().filter(|sig| is_resolved(sig)).count(),
+        signatures,
+    }
+}
+
+/// Compare two pastes of the same system: signatures that appeared, ones
+/// that are gone, and ones that became resolved (fully scanned or newly
+/// identified) since the previous paste.
+pub fn diff_probe_scans(previous: &ProbeParseResult, current: &ProbeParseResult) -> ProbeDiff {
+    let before: HashMap<&str, &ProbeSignature> = previous
+        .signatures
+        .iter()
+        .map(|sig| (sig.id.as_str(), sig))
+        .collect();
+    let after: HashMap<&str, &ProbeSignature> = current
+        .signatures
+        .iter()
+        .map(|sig| (sig.id.as_str(), sig))
+        .collect();
+
+    let new = current
+        .signatures
+        .iter()
+        .filter(|sig| !before.contains_key(sig.id.as_str()))
+        .cloned()
+        .collect();
+    let gone = previous
+        .signatures
+        .iter()
+        .filter(|sig| !after.contains_key(sig.id.as_str()))
+        .cloned()
+        .collect();
+    let resolved = current
+        .signatures
+        .iter()
+        .filter(|sig| {
+            before
+                .get(sig.id.as_str())
+                .is_some_and(|old| is_resolved(sig) && !is_resolved(old))
+        })
+        .cloned()
+        .collect();
+
+    ProbeDiff {
+        new,
+        gone,
+        resolved,
+    }
+}
+
+/// Three uppercase letters, a dash, three digits: "ABC-123".
+pub fn is_signature_id(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 7
+        && bytes[..3].iter().all(u8::is_ascii_uppercase)
+        && bytes[3] == b'-'
+        && bytes[4..].iter().all(u8::is_ascii_digit)
+}
+
+fn is_resolved(signature: &ProbeSignature) -> bool {
+    signature
+        .signal_strength
+        .is_some_and(|signal| signal >= RESOLVED_SIGNAL)
+        || signature.site_name.is_some()
+}
+
+/// "100,0%", "12.5 %" -> percent. The value never exceeds 100, so a lone
+/// separator is always the decimal mark.
+fn parse_signal_strength(raw: &str) -> Option<f64> {
+    let number = raw.trim().trim_end_matches('%');
+    parse_localized_number(number, true)
+}
+
+fn signature_kind(group: &str) -> SignatureKind {
+    let group = group.to_lowercase();
+    let kinds = [
+        ("combat", SignatureKind::Combat),
+        ("data", SignatureKind::Data),
+        ("relic", SignatureKind::Relic),
+        ("gas", SignatureKind::Gas),
+        ("ore", SignatureKind::Ore),
+        ("wormhole", SignatureKind::Wormhole),
+    ];
+    kinds
+        .into_iter()
+        .find(|(keyword, _)| group.contains(keyword))
+        .map_or(SignatureKind::Unknown, |(_, kind)| kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASTE: &str =
+        "ABC-123\tCosmic Signature\tData Site\tUnsecured Perimeter Amplifier\t100,0%\t4,36 AU\n\
+         XYZ-789\tCosmic Signature\t\t\t12,5%\t12.1 AU\n\
+         QRS-456\tCosmic Signature\tWormhole\tUnstable Wormhole\t100.0%\t2.0 AU\n\
+         Not a signature row\n";
+
+    #[test]
+    fn parse_probe_reads_typed_signatures() {
+        let result = parse_probe_text(PASTE);
+        assert_eq!(result.total_rows, 3);
+        assert_eq!(result.resolved_count, 2);
+
+        let data = &result.signatures[0];
+        assert_eq!(data.id, "ABC-123");
+        assert_eq!(data.scan_group, "Cosmic Signature");
+        assert_eq!(data.kind, SignatureKind::Data);
+        assert_eq!(
+            data.site_name.as_deref(),
+            Some("Unsecured Perimeter Amplifier")
+        );
+        assert_eq!(data.signal_strength, Some(100.0));
+        assert!(data.distance_m.is_some());
+
+        let unknown = &result.signatures[1];
+        assert_eq!(unknown.kind, SignatureKind::Unknown);
+        assert_eq!(unknown.group, None);
+        assert_eq!(unknown.site_name, None);
+        assert_eq!(unknown.signal_strength, Some(12.5));
+
+        assert_eq!(result.signatures[2].kind, SignatureKind::Wormhole);
+    }
+
+    #[test]
+    fn signature_ids_must_match_the_eve_format() {
+        assert!(is_signature_id("ABC-123"));
+        assert!(!is_signature_id("abc-123"));
+        assert!(!is_signature_id("ABC123"));
+        assert!(!is_signature_id("ABCD-123"));
+        assert!(!is_signature_id("ABC-12X"));
+    }
+
+    #[test]
+    fn diff_reports_new_gone_and_resolved() {
+        let previous = parse_probe_text(
+            "ABC-123\tCosmic Signature\t\t\t20,0%\t4 AU\n\
+             DEF-456\tCosmic Signature\tGas Site\tBarren Perimeter Reservoir\t100%\t1 AU",
+        );
+        let current = parse_probe_text(
+            "ABC-123\tCosmic Signature\tRelic Site\tForgotten Frontier\t100,0%\t4 AU\n\
+             GHI-789\tCosmic Signature\t\t\t5,0%\t9 AU",
+        );
+        let diff = diff_probe_scans(&previous, &current);
+
+        let ids = |sigs: &[ProbeSignature]| -> Vec<String> {
+            sigs.iter().map(|sig| sig.id.clone()).collect()
+        };
+        assert_eq!(ids(&diff.new), vec!["GHI-789"]);
+        assert_eq!(ids(&diff.gone), vec!["DEF-456"]);
+        assert_eq!(ids(&diff.resolved), vec!["ABC-123"]);
+        assert_eq!(diff.resolved[0].kind, SignatureKind::Relic);
+    }
+
+    #[test]
+    fn already_resolved_signatures_are_not_reported_again() {
+        let scan = parse_probe_text(PASTE);
+        let diff = diff_probe_scans(&scan, &scan);
+        assert!(diff.new.is_empty());
+        assert!(diff.gone.is_empty());
+        assert!(diff.resolved.is_empty());
+    }
+}
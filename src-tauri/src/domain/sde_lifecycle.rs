@@ -1,30 +1,96 @@
 //! SDE index lifecycle state machine with effects-as-data.
 //!
-//! `crate::sde::ensure_sde_index` drives this machine: it seeds the phase
-//! from the on-disk cache, feeds `CheckRequested`, executes each returned
-//! effect (network/disk work), and feeds the outcome back in as the next
-//! event until no effects remain.
+//! `crate::sde::ensure_sde_index` and `crate::sde::import_sde_archive` drive
+//! this machine: they seed the phase from the on-disk cache, feed
+//! `CheckRequested` or `ImportRequested`, execute each returned effect
+//! (network/disk work), and feed the outcome back in as the next event
+//! until no effects remain.
 //!
 //! Transition table (all other (phase, event) pairs are stale/spurious and
 //! leave the phase unchanged with no effects):
 //!
-//! | Phase              | Event               | Next phase        | Effects            |
-//! |--------------------|---------------------|-------------------|--------------------|
-//! | Missing            | CheckRequested      | Checking(None)    | FetchRemoteBuild   |
-//! | Ready(b)           | CheckRequested      | Checking(Some(b)) | FetchRemoteBuild   |
-//! | Failed(_)          | CheckRequested      | Checking(None)    | FetchRemoteBuild   |
-//! | Updating(t)        | CheckRequested      | Updating(t)       | (denied, none)     |
-//! | Checking(Some(b))  | RemoteBuild(b)      | Ready(b)          | (up to date, none) |
-//! | Checking(c)        | RemoteBuild(l ≠ c)  | Updating(l)       | StartUpdate(l)     |
-//! | Checking(Some(b))  | UpToDate            | Ready(b)          | (keep cache, none) |
-//! | Checking(None)     | UpToDate            | Failed(msg)       | (none)             |
-//! | Updating(_)        | UpdateFinished(b)   | Ready(b)          | InvalidateIndex    |
-//! | Updating(_)        | UpdateFailed(e)     | Failed(e)         | (none)             |
+//! | Phase                 | Event                 | Next phase             | Effects                    |
+//! |-----------------------|-----------------------|------------------------|----------------------------|
+//! | Missing               | CheckRequested        | Checking(None, 0)      | FetchRemoteBuild(0)        |
+//! | Ready(b)              | CheckRequested        | Checking(Some(b), 0)   | FetchRemoteBuild(0)        |
+//! | Failed(_)             | CheckRequested        | Checking(None, 0)      | FetchRemoteBuild(0)        |
+//! | Updating/Importing    | CheckRequested        | (unchanged)            | (denied, none)             |
+//! | Ready(b)              | CheckDue(< i)         | Ready(b)               | (checked recently, none)   |
+//! | (otherwise)           | CheckDue              | (as CheckRequested)    |                            |
+//! | Ready(b)              | RebuildRequested(r)   | Rebuild(b, 0, r)       | FetchRemoteBuild(0)        |
+//! | Missing/Failed        | ImportRequested(p)    | Importing(None)        | ImportArchive(p)           |
+//! | Ready(b)              | ImportRequested(p)    | Importing(Some(b))     | ImportArchive(p)           |
+//! | Checking(c, s)        | SourceUnavailable     | Checking(c, s+1)       | FetchRemoteBuild(s+1)      |
+//! | Checking(Some(b), _)  | RemoteBuild(b)        | Ready(b)               | (up to date, none)         |
+//! | Checking(c, s)        | RemoteBuild(l ≠ c)    | Updating(l, s, 0, c)   | StartUpdate(l, s)          |
+//! | Rebuild(b, _, r)      | RemoteBuild(r)        | Ready(b)               | (rejected before, none)    |
+//! | Rebuild(b, s, r)      | RemoteBuild(l ≠ r)    | Updating(l, s, 0, b)   | StartUpdate(l, s)          |
+//! | Checking(Some(b), _)  | UpToDate              | Ready(b)               | (keep cache, none)         |
+//! | Checking(None, _)     | UpToDate              | Failed(msg)            | (none)                     |
+//! | Updating(t, s, n, p)  | DownloadFailed(_)     | Updating(t, s, n+1, p) | Wait(d), StartUpdate(t, s) |
+//! | Updating(t, s, n, p)  | DownloadFailed(_)     | Updating(t, s+1, 0, p) | StartUpdate(t, s+1)        |
+//! | Updating(.., Some(b)) | DownloadsExhausted(_) | Ready(b)               | (keep cache, none)         |
+//! | Updating(.., None)    | DownloadsExhausted(e) | Failed(e)              | (none)                     |
+//! | Updating/Importing    | UpdateFinished(b)     | Ready(b)               | InvalidateIndex            |
+//! | Updating/Importing    | UpdateFailed(e)       | Failed(e)              | (none)                     |
+//! | (.., Some(b))         | ValidationFailed(_)   | Ready(b)               | (keep cache, none)         |
+//! | (.., None)            | ValidationFailed(e)   | Failed(e)              | (none)                     |
+//!
+//! `Rebuild(b, s, r)` is `Checking(Some(b), s)` for a stale cache: it
+//! behaves the same (`SourceUnavailable`, `UpToDate`) except that the
+//! remote build is downloaded even when it matches, so the rebuilt index is
+//! labelled with the build actually fetched and an offline rebuild keeps
+//! serving the stale cache. `r` is the build the driver last saw fail
+//! validation for this rebuild; it isn't downloaded again.
+//!
+//! `s` indexes the driver's ordered list of SDE sources (the official URL
+//! and any mirrors): each unreachable one hands over to the next, and the
+//! driver answers a `FetchRemoteBuild` past the end of the list with
+//! `UpToDate`. The update then downloads from the source that answered.
+//! `n` counts failed download attempts; a download is retried until
+//! `MAX_DOWNLOAD_ATTEMPTS` have failed, waiting `retry_delay(n + 1)` first,
+//! and then moves on to the next source, so rebuilds (which start at the
+//! first) walk the whole list too. The driver answers a `StartUpdate` past
+//! the end with `DownloadsExhausted`.
+//! `p` is the build the cache held before the update: a new index that
+//! fails validation is never written, so the lifecycle returns to it.
+//! `CheckDue` comes from startup and the background scheduler, and only
+//! checks when the last successful check is at least the interval `i` old.
+
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Error produced when the remote build can't be determined and there is no
 /// cached index to fall back on (same message the old code returned).
 pub const NO_BUILD_ERROR: &str = "Unable to determine latest SDE build and no cached index exists";
 
+/// Download attempts per update, including the first.
+pub const MAX_DOWNLOAD_ATTEMPTS: u32 = 4;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Exponential backoff before download retry `attempt` (1-based): 2s, 4s,
+/// 8s, ... capped at 30s.
+pub fn retry_delay(attempt: u32) -> Duration {
+    let factor = 1u32 << attempt.saturating_sub(1).min(16);
+    RETRY_BASE_DELAY.saturating_mul(factor).min(RETRY_MAX_DELAY)
+}
+
+const CHECK_BACKOFF_BASE: Duration = Duration::from_secs(5 * 60);
+const CHECK_BACKOFF_MAX: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Wait before the next background check after `failures` consecutive
+/// failed ones: 5 min, 10 min, ... capped at 6 h, spread over ±20% by
+/// `jitter` (uniform in [0, 1)) so clients that failed together don't
+/// retry together.
+pub fn check_backoff(failures: u32, jitter: f64) -> Duration {
+    let factor = 1u32 << failures.saturating_sub(1).min(16);
+    let delay = CHECK_BACKOFF_BASE
+        .saturating_mul(factor)
+        .min(CHECK_BACKOFF_MAX);
+    delay.mul_f64(0.8 + 0.4 * jitter.clamp(0.0, 1.0))
+}
+
 /// Build numbers are `i64` to match the cache file / `SdeStatus` fields.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SdePhase {
@@ -32,10 +98,27 @@ pub enum SdePhase {
     Missing,
     /// A cached index for `build` exists and is usable.
     Ready { build: i64 },
-    /// Determining the latest remote build; `current` is the cached build.
-    Checking { current: Option<i64> },
-    /// Downloading/building the index for `target`.
-    Updating { target: i64 },
+    /// Asking source `source` for the latest remote build; `current` is
+    /// the cached build. A `rebuild` downloads the remote build even when
+    /// it matches `current`, to replace a stale cache, unless it is the
+    /// `rejected` build whose rebuild already failed validation.
+    Checking {
+        current: Option<i64>,
+        source: usize,
+        rebuild: bool,
+        rejected: Option<i64>,
+    },
+    /// Downloading/building the index for `target` from `source`;
+    /// `failed_attempts` downloads have failed so far. `previous` is the
+    /// cached build to fall back on.
+    Updating {
+        target: i64,
+        source: usize,
+        failed_attempts: u32,
+        previous: Option<i64>,
+    },
+    /// Building the index from a user-supplied archive.
+    Importing { previous: Option<i64> },
     /// The last check or update failed.
     Failed { error: String },
 }
@@ -44,22 +127,50 @@ pub enum SdePhase {
 pub enum SdeEvent {
     /// `ensure_sde_index` was invoked.
     CheckRequested,
+    /// A check is due at startup or on the background schedule, unless
+    /// the last successful one (if known) was less than `interval` ago.
+    CheckDue {
+        since_last_check: Option<Duration>,
+        interval: Duration,
+    },
+    /// The cached index is stale (older builder, or built with a different
+    /// category coverage): rebuild it from the current remote archive
+    /// without waiting for a new build. `rejected` is a build the same
+    /// rebuild already failed validation for.
+    RebuildRequested { rejected: Option<i64> },
+    /// Build the index from a local archive instead of downloading one.
+    ImportRequested(PathBuf),
     /// The remote build number was determined.
     RemoteBuild(i64),
-    /// The remote build could not be determined; keep whatever we have.
+    /// The current source couldn't tell the remote build; try the next.
+    SourceUnavailable,
+    /// No source could tell the remote build; keep whatever we have.
     UpToDate,
-    /// Download + index build for this build number completed.
+    /// The archive download broke off or failed verification; a partial
+    /// download is kept for the retry to resume.
+    DownloadFailed(String),
+    /// The download failed on every source; carries the last error.
+    DownloadsExhausted(String),
+    /// Download (or import) + index build for this build number completed.
     UpdateFinished(i64),
-    /// Download + index build failed.
+    /// Download (or import) + index build failed for good.
     UpdateFailed(String),
+    /// The new index was built but failed its sanity checks, so the
+    /// working cache was left as it was.
+    ValidationFailed(String),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SdeEffect {
-    /// HEAD the SDE URL to learn the latest build number.
-    FetchRemoteBuild,
-    /// Download the archive and rebuild the on-disk index cache.
-    StartUpdate(i64),
+    /// HEAD the given source to learn the latest build number.
+    FetchRemoteBuild(usize),
+    /// Download the archive from `source` and rebuild the on-disk index
+    /// cache.
+    StartUpdate { target: i64, source: usize },
+    /// Verify a local archive and rebuild the on-disk index cache from it.
+    ImportArchive(PathBuf),
+    /// Back off before the next effect.
+    Wait(Duration),
     /// Drop the in-memory index so the next read reloads the new cache.
     InvalidateIndex,
 }
@@ -68,73 +179,218 @@ pub fn step(phase: SdePhase, event: SdeEvent) -> (SdePhase, Vec<SdeEffect>) {
     match (phase, event) {
         // A check while an update is in flight is denied (no-op) — the
         // driver's mutex already serializes callers, this encodes it.
-        (SdePhase::Updating { target }, SdeEvent::CheckRequested) => {
-            (SdePhase::Updating { target }, vec![])
+        (
+            phase @ (SdePhase::Updating { .. } | SdePhase::Importing { .. }),
+            SdeEvent::CheckRequested | SdeEvent::CheckDue { .. },
+        ) => (phase, vec![]),
+        (
+            phase @ SdePhase::Ready { .. },
+            SdeEvent::CheckDue {
+                since_last_check: Some(since_last_check),
+                interval,
+            },
+        ) if since_last_check < interval => (phase, vec![]),
+        (SdePhase::Missing, SdeEvent::CheckRequested | SdeEvent::CheckDue { .. }) => check(None),
+        (SdePhase::Ready { build }, SdeEvent::CheckRequested | SdeEvent::CheckDue { .. }) => {
+            check(Some(build))
         }
-        (SdePhase::Missing, SdeEvent::CheckRequested) => (
-            SdePhase::Checking { current: None },
-            vec![SdeEffect::FetchRemoteBuild],
-        ),
-        (SdePhase::Ready { build }, SdeEvent::CheckRequested) => (
+        (SdePhase::Ready { build }, SdeEvent::RebuildRequested { rejected }) => (
             SdePhase::Checking {
                 current: Some(build),
+                source: 0,
+                rebuild: true,
+                rejected,
             },
-            vec![SdeEffect::FetchRemoteBuild],
+            vec![SdeEffect::FetchRemoteBuild(0)],
         ),
         // Failure recovery: a new check starts from scratch.
-        (SdePhase::Failed { .. }, SdeEvent::CheckRequested) => (
-            SdePhase::Checking { current: None },
-            vec![SdeEffect::FetchRemoteBuild],
+        (SdePhase::Failed { .. }, SdeEvent::CheckRequested | SdeEvent::CheckDue { .. }) => {
+            check(None)
+        }
+        (SdePhase::Missing | SdePhase::Failed { .. }, SdeEvent::ImportRequested(path)) => (
+            SdePhase::Importing { previous: None },
+            vec![SdeEffect::ImportArchive(path)],
+        ),
+        (SdePhase::Ready { build }, SdeEvent::ImportRequested(path)) => (
+            SdePhase::Importing {
+                previous: Some(build),
+            },
+            vec![SdeEffect::ImportArchive(path)],
+        ),
+        (
+            SdePhase::Checking {
+                current,
+                source,
+                rebuild,
+                rejected,
+            },
+            SdeEvent::SourceUnavailable,
+        ) => (
+            SdePhase::Checking {
+                current,
+                source: source + 1,
+                rebuild,
+                rejected,
+            },
+            vec![SdeEffect::FetchRemoteBuild(source + 1)],
         ),
-        (SdePhase::Checking { current }, SdeEvent::RemoteBuild(latest)) => {
-            if current == Some(latest) {
-                (SdePhase::Ready { build: latest }, vec![])
-            } else {
-                (
-                    SdePhase::Updating { target: latest },
-                    vec![SdeEffect::StartUpdate(latest)],
-                )
+        (
+            SdePhase::Checking {
+                current,
+                source,
+                rebuild,
+                rejected,
+            },
+            SdeEvent::RemoteBuild(latest),
+        ) => match current {
+            Some(build) if build == latest && !rebuild => (SdePhase::Ready { build }, vec![]),
+            // Rebuilding from the same archive would only be rejected again:
+            // the stale cache serves until a new build appears.
+            Some(build) if rebuild && rejected == Some(latest) => {
+                (SdePhase::Ready { build }, vec![])
             }
-        }
+            _ => start_update(latest, source, current),
+        },
         (
             SdePhase::Checking {
                 current: Some(build),
+                ..
             },
             SdeEvent::UpToDate,
         ) => (SdePhase::Ready { build }, vec![]),
-        (SdePhase::Checking { current: None }, SdeEvent::UpToDate) => (
+        (SdePhase::Checking { current: None, .. }, SdeEvent::UpToDate) => (
             SdePhase::Failed {
                 error: NO_BUILD_ERROR.to_string(),
             },
             vec![],
         ),
-        (SdePhase::Updating { .. }, SdeEvent::UpdateFinished(build)) => {
-            (SdePhase::Ready { build }, vec![SdeEffect::InvalidateIndex])
+        (
+            SdePhase::Updating {
+                target,
+                source,
+                failed_attempts,
+                previous,
+            },
+            SdeEvent::DownloadFailed(_),
+        ) => {
+            let failed_attempts = failed_attempts + 1;
+            if failed_attempts >= MAX_DOWNLOAD_ATTEMPTS {
+                return start_update(target, source + 1, previous);
+            }
+            (
+                SdePhase::Updating {
+                    target,
+                    source,
+                    failed_attempts,
+                    previous,
+                },
+                vec![
+                    SdeEffect::Wait(retry_delay(failed_attempts)),
+                    SdeEffect::StartUpdate { target, source },
+                ],
+            )
         }
-        (SdePhase::Updating { .. }, SdeEvent::UpdateFailed(error)) => {
+        // The driver reports the error; the cache is still there to serve.
+        (SdePhase::Updating { previous, .. }, SdeEvent::DownloadsExhausted(error)) => {
+            match previous {
+                Some(build) => (SdePhase::Ready { build }, vec![]),
+                None => (SdePhase::Failed { error }, vec![]),
+            }
+        }
+        (
+            SdePhase::Updating { .. } | SdePhase::Importing { .. },
+            SdeEvent::UpdateFinished(build),
+        ) => (SdePhase::Ready { build }, vec![SdeEffect::InvalidateIndex]),
+        (SdePhase::Updating { .. } | SdePhase::Importing { .. }, SdeEvent::UpdateFailed(error)) => {
             (SdePhase::Failed { error }, vec![])
         }
+        // The rejected index never replaced the cache, so the previous
+        // build is still loaded and nothing needs invalidating.
+        (
+            SdePhase::Updating { previous, .. } | SdePhase::Importing { previous },
+            SdeEvent::ValidationFailed(error),
+        ) => match previous {
+            Some(build) => (SdePhase::Ready { build }, vec![]),
+            None => (SdePhase::Failed { error }, vec![]),
+        },
         // Stale/spurious events never change the phase.
         (phase, _) => (phase, vec![]),
     }
 }
 
+/// Checks always start at the first source.
+fn check(current: Option<i64>) -> (SdePhase, Vec<SdeEffect>) {
+    (
+        SdePhase::Checking {
+            current,
+            source: 0,
+            rebuild: false,
+            rejected: None,
+        },
+        vec![SdeEffect::FetchRemoteBuild(0)],
+    )
+}
+
+fn start_update(target: i64, source: usize, previous: Option<i64>) -> (SdePhase, Vec<SdeEffect>) {
+    (
+        SdePhase::Updating {
+            target,
+            source,
+            failed_attempts: 0,
+            previous,
+        },
+        vec![SdeEffect::StartUpdate { target, source }],
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn checking(current: Option<i64>, source: usize) -> SdePhase {
+        SdePhase::Checking {
+            current,
+            source,
+            rebuild: false,
+            rejected: None,
+        }
+    }
+
+    fn updating(target: i64, failed_attempts: u32) -> SdePhase {
+        SdePhase::Updating {
+            target,
+            source: 0,
+            failed_attempts,
+            previous: None,
+        }
+    }
+
+    /// A fresh update that replaces the cached `previous` build.
+    fn updating_from(target: i64, previous: i64) -> SdePhase {
+        SdePhase::Updating {
+            target,
+            source: 0,
+            failed_attempts: 0,
+            previous: Some(previous),
+        }
+    }
+
+    fn start(target: i64) -> SdeEffect {
+        SdeEffect::StartUpdate { target, source: 0 }
+    }
+
     #[test]
     fn check_from_missing_fetches_remote_build() {
         let (phase, effects) = step(SdePhase::Missing, SdeEvent::CheckRequested);
-        assert_eq!(phase, SdePhase::Checking { current: None });
-        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild]);
+        assert_eq!(phase, checking(None, 0));
+        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(0)]);
     }
 
     #[test]
     fn check_from_ready_carries_current_build() {
         let (phase, effects) = step(SdePhase::Ready { build: 100 }, SdeEvent::CheckRequested);
-        assert_eq!(phase, SdePhase::Checking { current: Some(100) });
-        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild]);
+        assert_eq!(phase, checking(Some(100), 0));
+        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(0)]);
     }
 
     #[test]
@@ -143,20 +399,150 @@ mod tests {
             error: "boom".into(),
         };
         let (phase, effects) = step(failed, SdeEvent::CheckRequested);
-        assert_eq!(phase, SdePhase::Checking { current: None });
-        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild]);
+        assert_eq!(phase, checking(None, 0));
+        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(0)]);
+    }
+
+    fn due(since_last_check_hours: Option<u64>) -> SdeEvent {
+        SdeEvent::CheckDue {
+            since_last_check: since_last_check_hours
+                .map(|hours| Duration::from_secs(hours * 60 * 60)),
+            interval: Duration::from_secs(12 * 60 * 60),
+        }
+    }
+
+    #[test]
+    fn due_check_skips_a_recently_checked_index() {
+        let (phase, effects) = step(SdePhase::Ready { build: 100 }, due(Some(3)));
+        assert_eq!(phase, SdePhase::Ready { build: 100 });
+        assert!(effects.is_empty());
+
+        // Interval passed, or never checked: check as if requested.
+        for since in [Some(12), Some(30), None] {
+            let (phase, effects) = step(SdePhase::Ready { build: 100 }, due(since));
+            assert_eq!(phase, checking(Some(100), 0));
+            assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(0)]);
+        }
+    }
+
+    #[test]
+    fn due_check_without_an_index_always_checks() {
+        for phase in [
+            SdePhase::Missing,
+            SdePhase::Failed {
+                error: "offline".into(),
+            },
+        ] {
+            let (phase, effects) = step(phase, due(Some(1)));
+            assert_eq!(phase, checking(None, 0));
+            assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(0)]);
+        }
+
+        let (phase, effects) = step(updating(200, 0), due(None));
+        assert_eq!(phase, updating(200, 0));
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn check_backoff_grows_with_jitter_up_to_a_cap() {
+        assert_eq!(check_backoff(1, 0.5), CHECK_BACKOFF_BASE);
+        assert_eq!(check_backoff(3, 0.5), CHECK_BACKOFF_BASE * 4);
+        assert_eq!(check_backoff(40, 0.5), CHECK_BACKOFF_MAX);
+
+        // ±20% around the base delay.
+        assert_eq!(check_backoff(1, 0.0), CHECK_BACKOFF_BASE.mul_f64(0.8));
+        assert!(check_backoff(1, 0.999) < CHECK_BACKOFF_BASE.mul_f64(1.2));
+        assert!(check_backoff(1, 0.25) < check_backoff(1, 0.75));
     }
 
     #[test]
     fn check_while_updating_is_denied() {
-        let (phase, effects) = step(SdePhase::Updating { target: 200 }, SdeEvent::CheckRequested);
-        assert_eq!(phase, SdePhase::Updating { target: 200 });
+        let (phase, effects) = step(updating(200, 0), SdeEvent::CheckRequested);
+        assert_eq!(phase, updating(200, 0));
+        assert!(effects.is_empty());
+
+        let (phase, effects) = step(
+            SdePhase::Importing { previous: None },
+            SdeEvent::CheckRequested,
+        );
+        assert_eq!(phase, SdePhase::Importing { previous: None });
+        assert!(effects.is_empty());
+    }
+
+    fn rebuilding(source: usize) -> SdePhase {
+        SdePhase::Checking {
+            current: Some(100),
+            source,
+            rebuild: true,
+            rejected: None,
+        }
+    }
+
+    fn rebuild() -> SdeEvent {
+        SdeEvent::RebuildRequested { rejected: None }
+    }
+
+    #[test]
+    fn rebuild_downloads_the_remote_build_even_when_it_matches() {
+        let (phase, effects) = step(SdePhase::Ready { build: 100 }, rebuild());
+        assert_eq!(phase, rebuilding(0));
+        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(0)]);
+
+        let (phase, effects) = step(phase, SdeEvent::RemoteBuild(100));
+        assert_eq!(phase, updating_from(100, 100));
+        assert_eq!(effects, vec![start(100)]);
+
+        // The rebuild is labelled with whatever build the source has now.
+        let (phase, effects) = step(rebuilding(0), SdeEvent::RemoteBuild(200));
+        assert_eq!(phase, updating_from(200, 100));
+        assert_eq!(effects, vec![start(200)]);
+    }
+
+    #[test]
+    fn rejected_rebuild_waits_for_a_new_remote_build() {
+        let (phase, effects) = step(SdePhase::Ready { build: 100 }, rebuild());
+        let (phase, _) = step(phase, SdeEvent::RemoteBuild(200));
+        let (phase, _) = step(phase, SdeEvent::ValidationFailed("too few types".into()));
+        assert_eq!(phase, SdePhase::Ready { build: 100 });
+        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(0)]);
+
+        // The next rebuild of the still-stale cache checks, but doesn't
+        // download build 200 again.
+        let rejected = SdeEvent::RebuildRequested {
+            rejected: Some(200),
+        };
+        let (phase, effects) = step(phase, rejected.clone());
+        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(0)]);
+        let (phase, effects) = step(phase, SdeEvent::RemoteBuild(200));
+        assert_eq!(phase, SdePhase::Ready { build: 100 });
         assert!(effects.is_empty());
+
+        // A newer build gets its chance.
+        let (phase, _) = step(phase, rejected);
+        let (phase, effects) = step(phase, SdeEvent::RemoteBuild(300));
+        assert_eq!(phase, updating_from(300, 100));
+        assert_eq!(effects, vec![start(300)]);
+    }
+
+    #[test]
+    fn offline_rebuild_keeps_serving_the_stale_cache() {
+        let (phase, effects) = step(rebuilding(0), SdeEvent::SourceUnavailable);
+        assert_eq!(phase, rebuilding(1));
+        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(1)]);
+        let (phase, _) = step(phase, SdeEvent::UpToDate);
+        assert_eq!(phase, SdePhase::Ready { build: 100 });
+
+        // A source that answers but can't deliver the archive.
+        let (phase, _) = step(
+            updating_from(100, 100),
+            SdeEvent::DownloadsExhausted("offline".into()),
+        );
+        assert_eq!(phase, SdePhase::Ready { build: 100 });
     }
 
     #[test]
     fn matching_remote_build_is_up_to_date() {
-        let checking = SdePhase::Checking { current: Some(100) };
+        let checking = checking(Some(100), 0);
         let (phase, effects) = step(checking, SdeEvent::RemoteBuild(100));
         assert_eq!(phase, SdePhase::Ready { build: 100 });
         assert!(effects.is_empty());
@@ -164,26 +550,120 @@ mod tests {
 
     #[test]
     fn newer_remote_build_starts_update() {
-        let checking = SdePhase::Checking { current: Some(100) };
+        let checking = checking(Some(100), 0);
         let (phase, effects) = step(checking, SdeEvent::RemoteBuild(200));
-        assert_eq!(phase, SdePhase::Updating { target: 200 });
-        assert_eq!(effects, vec![SdeEffect::StartUpdate(200)]);
+        assert_eq!(phase, updating_from(200, 100));
+        assert_eq!(effects, vec![start(200)]);
     }
 
     #[test]
     fn remote_build_with_no_cache_starts_update() {
+        let (phase, effects) = step(checking(None, 0), SdeEvent::RemoteBuild(200));
+        assert_eq!(phase, updating(200, 0));
+        assert_eq!(effects, vec![start(200)]);
+    }
+
+    #[test]
+    fn unavailable_source_falls_back_to_the_next() {
+        let (phase, effects) = step(checking(Some(100), 0), SdeEvent::SourceUnavailable);
+        assert_eq!(phase, checking(Some(100), 1));
+        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(1)]);
+
+        // The update downloads from the source that answered.
+        let (phase, effects) = step(phase, SdeEvent::RemoteBuild(200));
+        assert_eq!(
+            phase,
+            SdePhase::Updating {
+                target: 200,
+                source: 1,
+                failed_attempts: 0,
+                previous: Some(100),
+            }
+        );
+        assert_eq!(
+            effects,
+            vec![SdeEffect::StartUpdate {
+                target: 200,
+                source: 1
+            }]
+        );
+        let (_, effects) = step(phase, SdeEvent::DownloadFailed("timeout".into()));
+        assert_eq!(
+            effects[1],
+            SdeEffect::StartUpdate {
+                target: 200,
+                source: 1
+            }
+        );
+    }
+
+    #[test]
+    fn import_builds_from_a_local_archive() {
+        let path = PathBuf::from("/tmp/sde.zip");
+        for (phase, previous) in [
+            (SdePhase::Missing, None),
+            (SdePhase::Ready { build: 100 }, Some(100)),
+            (
+                SdePhase::Failed {
+                    error: NO_BUILD_ERROR.to_string(),
+                },
+                None,
+            ),
+        ] {
+            let (phase, effects) = step(phase, SdeEvent::ImportRequested(path.clone()));
+            assert_eq!(phase, SdePhase::Importing { previous });
+            assert_eq!(effects, vec![SdeEffect::ImportArchive(path.clone())]);
+        }
+
         let (phase, effects) = step(
-            SdePhase::Checking { current: None },
-            SdeEvent::RemoteBuild(200),
+            SdePhase::Importing { previous: None },
+            SdeEvent::UpdateFinished(300),
+        );
+        assert_eq!(phase, SdePhase::Ready { build: 300 });
+        assert_eq!(effects, vec![SdeEffect::InvalidateIndex]);
+
+        let (phase, _) = step(
+            SdePhase::Importing { previous: None },
+            SdeEvent::UpdateFailed("bad zip".into()),
+        );
+        assert_eq!(
+            phase,
+            SdePhase::Failed {
+                error: "bad zip".into()
+            }
+        );
+    }
+
+    #[test]
+    fn rejected_index_returns_to_the_previous_build() {
+        let invalid = || SdeEvent::ValidationFailed("too few solar systems".into());
+        for phase in [
+            updating_from(200, 100),
+            SdePhase::Importing {
+                previous: Some(100),
+            },
+        ] {
+            let (phase, effects) = step(phase, invalid());
+            assert_eq!(phase, SdePhase::Ready { build: 100 });
+            // The old index was never replaced, so nothing is invalidated.
+            assert!(effects.is_empty());
+        }
+
+        // With nothing to go back to, the update simply fails.
+        let (phase, effects) = step(updating(200, 0), invalid());
+        assert_eq!(
+            phase,
+            SdePhase::Failed {
+                error: "too few solar systems".into()
+            }
         );
-        assert_eq!(phase, SdePhase::Updating { target: 200 });
-        assert_eq!(effects, vec![SdeEffect::StartUpdate(200)]);
+        assert!(effects.is_empty());
     }
 
     #[test]
     fn up_to_date_with_cache_stays_ready_on_unknown_remote() {
         // Remote build undeterminable but a cache exists: keep serving it.
-        let checking = SdePhase::Checking { current: Some(100) };
+        let checking = checking(Some(100), 0);
         let (phase, effects) = step(checking, SdeEvent::UpToDate);
         assert_eq!(phase, SdePhase::Ready { build: 100 });
         assert!(effects.is_empty());
@@ -191,7 +671,7 @@ mod tests {
 
     #[test]
     fn up_to_date_without_cache_fails() {
-        let (phase, effects) = step(SdePhase::Checking { current: None }, SdeEvent::UpToDate);
+        let (phase, effects) = step(checking(None, 0), SdeEvent::UpToDate);
         assert_eq!(
             phase,
             SdePhase::Failed {
@@ -202,11 +682,84 @@ mod tests {
     }
 
     #[test]
-    fn update_finished_becomes_ready_and_invalidates_index() {
+    fn failed_download_backs_off_and_retries() {
+        let (phase, effects) = step(
+            updating(200, 0),
+            SdeEvent::DownloadFailed("connection reset".into()),
+        );
+        assert_eq!(phase, updating(200, 1));
+        assert_eq!(
+            effects,
+            vec![SdeEffect::Wait(Duration::from_secs(2)), start(200)]
+        );
+
+        let (phase, effects) = step(phase, SdeEvent::DownloadFailed("timeout".into()));
+        assert_eq!(phase, updating(200, 2));
+        assert_eq!(effects[0], SdeEffect::Wait(Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn download_moves_to_the_next_source_after_max_attempts() {
         let (phase, effects) = step(
-            SdePhase::Updating { target: 200 },
-            SdeEvent::UpdateFinished(200),
+            updating(200, MAX_DOWNLOAD_ATTEMPTS - 1),
+            SdeEvent::DownloadFailed("timeout".into()),
+        );
+        assert_eq!(
+            phase,
+            SdePhase::Updating {
+                target: 200,
+                source: 1,
+                failed_attempts: 0,
+                previous: None,
+            }
+        );
+        assert_eq!(
+            effects,
+            vec![SdeEffect::StartUpdate {
+                target: 200,
+                source: 1
+            }]
         );
+
+        // Past the last source the driver reports every download failed.
+        let (phase, effects) = step(phase, SdeEvent::DownloadsExhausted("timeout".into()));
+        assert_eq!(
+            phase,
+            SdePhase::Failed {
+                error: "timeout".into()
+            }
+        );
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn rebuild_walks_the_sources_too() {
+        let (phase, _) = step(SdePhase::Ready { build: 100 }, rebuild());
+        let (mut phase, _) = step(phase, SdeEvent::RemoteBuild(100));
+        let mut effects = vec![];
+        for _ in 0..MAX_DOWNLOAD_ATTEMPTS {
+            (phase, effects) = step(phase, SdeEvent::DownloadFailed("offline".into()));
+        }
+        assert_eq!(
+            effects,
+            vec![SdeEffect::StartUpdate {
+                target: 100,
+                source: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_a_cap() {
+        assert_eq!(retry_delay(1), Duration::from_secs(2));
+        assert_eq!(retry_delay(3), Duration::from_secs(8));
+        assert_eq!(retry_delay(5), RETRY_MAX_DELAY);
+        assert_eq!(retry_delay(u32::MAX), RETRY_MAX_DELAY);
+    }
+
+    #[test]
+    fn update_finished_becomes_ready_and_invalidates_index() {
+        let (phase, effects) = step(updating(200, 0), SdeEvent::UpdateFinished(200));
         assert_eq!(phase, SdePhase::Ready { build: 200 });
         assert_eq!(effects, vec![SdeEffect::InvalidateIndex]);
     }
@@ -214,7 +767,7 @@ mod tests {
     #[test]
     fn update_failed_records_the_error() {
         let (phase, effects) = step(
-            SdePhase::Updating { target: 200 },
+            updating(200, 0),
             SdeEvent::UpdateFailed("download died".into()),
         );
         assert_eq!(
@@ -232,21 +785,38 @@ mod tests {
         let cases = [
             (SdePhase::Missing, SdeEvent::RemoteBuild(1)),
             (SdePhase::Missing, SdeEvent::UpdateFinished(1)),
+            (SdePhase::Missing, rebuild()),
+            (
+                SdePhase::Ready { build: 5 },
+                SdeEvent::DownloadFailed("x".into()),
+            ),
+            (updating(9, 0), rebuild()),
             (SdePhase::Ready { build: 5 }, SdeEvent::UpToDate),
+            (
+                SdePhase::Ready { build: 5 },
+                SdeEvent::ValidationFailed("x".into()),
+            ),
             (
                 SdePhase::Ready { build: 5 },
                 SdeEvent::UpdateFailed("x".into()),
             ),
+            (checking(None, 0), SdeEvent::UpdateFinished(9)),
+            (checking(None, 0), SdeEvent::CheckRequested),
+            (updating(9, 0), SdeEvent::RemoteBuild(9)),
+            (updating(9, 0), SdeEvent::UpToDate),
+            (
+                updating(9, 0),
+                SdeEvent::ImportRequested(PathBuf::from("sde.zip")),
+            ),
+            (SdePhase::Ready { build: 5 }, SdeEvent::SourceUnavailable),
             (
-                SdePhase::Checking { current: None },
-                SdeEvent::UpdateFinished(9),
+                SdePhase::Importing { previous: None },
+                SdeEvent::DownloadFailed("x".into()),
             ),
             (
-                SdePhase::Checking { current: None },
-                SdeEvent::CheckRequested,
+                SdePhase::Importing { previous: None },
+                SdeEvent::DownloadsExhausted("x".into()),
             ),
-            (SdePhase::Updating { target: 9 }, SdeEvent::RemoteBuild(9)),
-            (SdePhase::Updating { target: 9 }, SdeEvent::UpToDate),
             (
                 SdePhase::Failed { error: "e".into() },
                 SdeEvent::RemoteBuild(9),
@@ -263,7 +833,7 @@ mod tests {
     fn full_update_cycle_walks_the_happy_path() {
         let (phase, _) = step(SdePhase::Ready { build: 100 }, SdeEvent::CheckRequested);
         let (phase, effects) = step(phase, SdeEvent::RemoteBuild(200));
-        assert_eq!(effects, vec![SdeEffect::StartUpdate(200)]);
+        assert_eq!(effects, vec![start(200)]);
         let (phase, effects) = step(phase, SdeEvent::UpdateFinished(200));
         assert_eq!(phase, SdePhase::Ready { build: 200 });
         assert_eq!(effects, vec![SdeEffect::InvalidateIndex]);
@@ -277,8 +847,8 @@ mod tests {
         assert!(matches!(phase, SdePhase::Failed { .. }));
 
         let (phase, effects) = step(phase, SdeEvent::CheckRequested);
-        assert_eq!(phase, SdePhase::Checking { current: None });
-        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild]);
+        assert_eq!(phase, checking(None, 0));
+        assert_eq!(effects, vec![SdeEffect::FetchRemoteBuild(0)]);
         let (phase, _) = step(phase, SdeEvent::RemoteBuild(200));
         let (phase, _) = step(phase, SdeEvent::UpdateFinished(200));
         assert_eq!(phase, SdePhase::Ready { build: 200 });
@@ -1,7 +1,10 @@
 //! Threat scoring and pilot-flag detection from zKillboard stats.
 
-use crate::models::{PilotFlags, ZkillStats};
+use crate::domain::dscan::SdeIndex;
+use crate::models::{HullRole, HullSize, PilotFlags, ShipStats, TechLevel, ZkillStats};
 
+/// Groups flagged by ID for ships the SDE index can't classify (not built
+/// yet, or a type newer than the index).
 mod ship_groups {
     pub const FORCE_RECON: i64 = 833;
     pub const COMBAT_RECON: i64 = 906;
@@ -40,26 +43,35 @@ const CAPITAL_GROUPS: &[i64] = &[
 
 const SUPER_GROUPS: &[i64] = &[ship_groups::SUPERCARRIER, ship_groups::TITAN];
 
-pub fn detect_pilot_flags(zkill: &Option<ZkillStats>) -> PilotFlags {
+/// Flags from the hull roles of the ships a pilot gets kills in, as the
+/// SDE index classifies them, falling back to known group IDs for ships
+/// it has no profile for.
+pub fn detect_pilot_flags(zkill: &Option<ZkillStats>, index: &SdeIndex) -> PilotFlags {
     let mut flags = PilotFlags::default();
 
     let Some(stats) = zkill else {
         return flags;
     };
 
-    let ships = &stats.top_ships;
-
-    let has_ship_in_group = |groups: &[i64], min_kills: i64| {
-        ships
-            .iter()
-            .any(|s| groups.contains(&s.group_id) && s.kills >= min_kills)
-    };
-
-    flags.is_recon = has_ship_in_group(RECON_GROUPS, 1);
-    flags.is_blops = has_ship_in_group(&[ship_groups::BLACK_OPS], 1);
-    flags.is_cyno = has_ship_in_group(COVERT_CYNO_GROUPS, 1);
-    flags.is_capital = has_ship_in_group(CAPITAL_GROUPS, 1);
-    flags.is_super = has_ship_in_group(SUPER_GROUPS, 1);
+    for ship in stats.top_ships.iter().filter(|ship| ship.kills >= 1) {
+        let Some(hull) = index.hull_profile(ship.ship_type_id) else {
+            flag_by_group(&mut flags, ship);
+            continue;
+        };
+        let has = |role: HullRole| hull.roles.contains(&role);
+
+        flags.is_cyno |= has(HullRole::Covert);
+        flags.is_blops |= has(HullRole::Covert) && hull.size == HullSize::Battleship;
+        flags.is_recon |= has(HullRole::Ewar)
+            && hull.tech_level == TechLevel::Tech2
+            && hull.size == HullSize::Cruiser;
+        flags.is_capital |= has(HullRole::Capital);
+        flags.is_super |= has(HullRole::Super);
+        flags.is_tackle |= has(HullRole::Tackle);
+        flags.is_interdiction |= has(HullRole::Interdiction);
+        flags.is_logistics |= has(HullRole::Logistics);
+        flags.is_ewar |= has(HullRole::Ewar);
+    }
 
     if stats.ships_destroyed > 10 {
         let solo_ratio = stats.solo_kills as f64 / stats.ships_destroyed as f64;
@@ -69,6 +81,15 @@ pub fn detect_pilot_flags(zkill: &Option<ZkillStats>) -> PilotFlags {
     flags
 }
 
+fn flag_by_group(flags: &mut PilotFlags, ship: &ShipStats) {
+    let group_id = ship.group_id;
+    flags.is_recon |= RECON_GROUPS.contains(&group_id);
+    flags.is_blops |= group_id == ship_groups::BLACK_OPS;
+    flags.is_cyno |= COVERT_CYNO_GROUPS.contains(&group_id);
+    flags.is_capital |= CAPITAL_GROUPS.contains(&group_id);
+    flags.is_super |= SUPER_GROUPS.contains(&group_id);
+}
+
 pub fn calculate_threat_level(zkill: &Option<ZkillStats>) -> String {
     match zkill {
         None => "Unknown".to_string(),
@@ -126,7 +147,7 @@ pub fn threat_rank(level: &str) -> u8 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::ShipStats;
+    use crate::models::{HullProfile, ScanTypeIndexEntry};
 
     #[test]
     fn threat_rank_orders_every_level_calculate_can_produce() {
@@ -139,17 +160,103 @@ mod tests {
         assert!(threat_rank("") > threat_rank("MINIMAL"));
     }
 
-    fn ship(group_id: i64, kills: i64) -> ShipStats {
+    // (type ID, group ID, roles, tech level, size) as the SDE builder
+    // derives them for a Falcon, Rook, Redeemer, Crow, Erebus and
+    // Revelation.
+    const HULLS: [(i64, i64, &[HullRole], TechLevel, HullSize); 6] = [
+        (
+            11957,
+            833,
+            &[HullRole::Ewar, HullRole::Covert],
+            TechLevel::Tech2,
+            HullSize::Cruiser,
+        ),
+        (
+            11959,
+            906,
+            &[HullRole::Ewar],
+            TechLevel::Tech2,
+            HullSize::Cruiser,
+        ),
+        (
+            22428,
+            898,
+            &[HullRole::Dps, HullRole::Covert],
+            TechLevel::Tech2,
+            HullSize::Battleship,
+        ),
+        (
+            11176,
+            831,
+            &[HullRole::Tackle],
+            TechLevel::Tech2,
+            HullSize::Frigate,
+        ),
+        (
+            671,
+            30,
+            &[HullRole::Dps, HullRole::Capital, HullRole::Super],
+            TechLevel::Tech1,
+            HullSize::Capital,
+        ),
+        (
+            19720,
+            485,
+            &[HullRole::Dps, HullRole::Capital],
+            TechLevel::Tech1,
+            HullSize::Capital,
+        ),
+    ];
+
+    fn index() -> SdeIndex {
+        let entries = HULLS
+            .iter()
+            .map(|(type_id, group_id, ..)| ScanTypeIndexEntry {
+                type_id: *type_id,
+                type_name: "Ship".to_string(),
+                type_names: Default::default(),
+                group_id: *group_id,
+                group_name: "Group".to_string(),
+                group_names: Default::default(),
+                category_id: 6,
+                category_name: "Ship".to_string(),
+            })
+            .collect();
+        let profiles = HULLS
+            .iter()
+            .map(|(type_id, _, roles, tech_level, size)| {
+                (
+                    *type_id,
+                    HullProfile {
+                        roles: roles.to_vec(),
+                        tech_level: *tech_level,
+                        size: *size,
+                    },
+                )
+            })
+            .collect();
+        SdeIndex::from_entries(entries).with_hull_profiles(profiles)
+    }
+
+    fn ship(ship_type_id: i64, kills: i64) -> ShipStats {
+        let (_, group_id, ..) = HULLS
+            .iter()
+            .find(|(type_id, ..)| *type_id == ship_type_id)
+            .unwrap();
         ShipStats {
-            ship_type_id: 1,
+            ship_type_id,
             ship_name: "Ship".to_string(),
-            group_id,
+            group_id: *group_id,
             group_name: "Group".to_string(),
             kills,
             losses: 0,
         }
     }
 
+    fn flags_for(ships: Vec<ShipStats>) -> PilotFlags {
+        detect_pilot_flags(&stats_with_ships(ships), &index())
+    }
+
     fn stats_with_ships(ships: Vec<ShipStats>) -> Option<ZkillStats> {
         Some(ZkillStats {
             top_ships: ships,
@@ -159,48 +266,72 @@ mod tests {
 
     #[test]
     fn no_stats_yields_default_flags() {
-        assert_eq!(detect_pilot_flags(&None), PilotFlags::default());
+        assert_eq!(detect_pilot_flags(&None, &index()), PilotFlags::default());
         assert_eq!(
-            detect_pilot_flags(&Some(ZkillStats::default())),
+            detect_pilot_flags(&Some(ZkillStats::default()), &index()),
             PilotFlags::default()
         );
     }
 
     #[test]
-    fn recon_groups_set_recon_and_cyno() {
-        // Force recon (833) is both a recon group and a covert-cyno group.
-        let flags = detect_pilot_flags(&stats_with_ships(vec![ship(833, 1)]));
+    fn recon_hulls_set_recon_and_ewar() {
+        // A force recon is both e-war and covert-cyno capable.
+        let flags = flags_for(vec![ship(11957, 1)]);
         assert!(flags.is_recon);
+        assert!(flags.is_ewar);
         assert!(flags.is_cyno);
         assert!(!flags.is_blops);
         assert!(!flags.is_capital);
 
-        // Combat recon (906) is recon but not covert-cyno.
-        let flags = detect_pilot_flags(&stats_with_ships(vec![ship(906, 1)]));
+        // A combat recon is recon but not covert-cyno.
+        let flags = flags_for(vec![ship(11959, 1)]);
         assert!(flags.is_recon);
         assert!(!flags.is_cyno);
     }
 
     #[test]
     fn zero_kill_ships_do_not_set_flags() {
-        let flags = detect_pilot_flags(&stats_with_ships(vec![ship(833, 0)]));
+        let flags = flags_for(vec![ship(11957, 0)]);
+        assert_eq!(flags, PilotFlags::default());
+    }
+
+    #[test]
+    fn ships_without_a_profile_fall_back_to_group_ids() {
+        let index = SdeIndex::default();
+        let flags = detect_pilot_flags(&stats_with_ships(vec![ship(11957, 5)]), &index);
+        assert!(flags.is_recon && flags.is_cyno);
+        assert!(!flags.is_blops && !flags.is_capital);
+
+        let flags = detect_pilot_flags(&stats_with_ships(vec![ship(671, 1)]), &index);
+        assert!(flags.is_capital && flags.is_super);
+
+        // A Crow's group carries no flags either way.
+        let flags = detect_pilot_flags(&stats_with_ships(vec![ship(11176, 9)]), &index);
         assert_eq!(flags, PilotFlags::default());
     }
 
     #[test]
     fn blops_sets_blops_and_cyno() {
-        let flags = detect_pilot_flags(&stats_with_ships(vec![ship(898, 1)]));
+        let flags = flags_for(vec![ship(22428, 1)]);
         assert!(flags.is_blops);
         assert!(flags.is_cyno);
+        assert!(!flags.is_recon);
+    }
+
+    #[test]
+    fn tackle_role_sets_tackle() {
+        let flags = flags_for(vec![ship(11176, 3)]);
+        assert!(flags.is_tackle);
+        assert!(!flags.is_interdiction && !flags.is_ewar);
     }
 
     #[test]
     fn supers_are_also_capitals() {
-        let flags = detect_pilot_flags(&stats_with_ships(vec![ship(30, 1)])); // Titan
+        let flags = flags_for(vec![ship(671, 1)]); // Titan
         assert!(flags.is_super);
         assert!(flags.is_capital);
 
-        let flags = detect_pilot_flags(&stats_with_ships(vec![ship(485, 1)])); // Dreadnought
+        let flags = flags_for(vec![ship(19720, 1)]); // Dreadnought
         assert!(flags.is_capital);
         assert!(!flags.is_super);
     }
@@ -215,12 +346,13 @@ mod tests {
             })
         };
 
+        let index = index();
         // > 30% solo of > 10 kills
-        assert!(detect_pilot_flags(&solo_stats(100, 40)).is_solo);
+        assert!(detect_pilot_flags(&solo_stats(100, 40), &index).is_solo);
         // Ratio below threshold
-        assert!(!detect_pilot_flags(&solo_stats(100, 30)).is_solo);
+        assert!(!detect_pilot_flags(&solo_stats(100, 30), &index).is_solo);
         // Not enough total kills, even at 100% solo
-        assert!(!detect_pilot_flags(&solo_stats(10, 10)).is_solo);
+        assert!(!detect_pilot_flags(&solo_stats(10, 10), &index).is_solo);
     }
 
     #[test]
@@ -0,0 +1,452 @@
+//! Ranked type name search over the SDE index, for autocomplete. The
+//! structures are built once per index, so a keystroke costs a binary
+//! search and a few posting lists rather than a pass over every name.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{ScanTypeIndexEntry, TypeMatchKind};
+
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+pub const MAX_SEARCH_LIMIT: usize = 100;
+
+/// Shorter queries only match name and word prefixes: a one- or two-letter
+/// substring or typo matches too much of the index to be useful.
+const MIN_INFIX_QUERY_CHARS: usize = 3;
+/// Leading characters of each word indexed for typo matching.
+const FUZZY_HEAD_CHARS: usize = 4;
+
+/// The first `FUZZY_HEAD_CHARS` of a word, padded with NULs.
+type Head = [char; FUZZY_HEAD_CHARS];
+
+/// Restricts results to the given categories and groups; an empty list
+/// doesn't restrict.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeSearchFilter {
+    pub category_ids: Vec<i64>,
+    pub group_ids: Vec<i64>,
+}
+
+impl TypeSearchFilter {
+    fn allows(&self, searched: &SearchType) -> bool {
+        (self.category_ids.is_empty() || self.category_ids.contains(&searched.category_id))
+            && (self.group_ids.is_empty() || self.group_ids.contains(&searched.group_id))
+    }
+}
+
+/// A ranked match: the type and the name it matched by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeMatch<'a> {
+    pub type_id: i64,
+    pub matched_name: &'a str,
+    pub kind: TypeMatchKind,
+}
+
+/// (kind, typos, name index).
+type Candidate = (TypeMatchKind, usize, u32);
+
+#[derive(Debug)]
+struct SearchType {
+    type_id: i64,
+    group_id: i64,
+    category_id: i64,
+}
+
+#[derive(Debug)]
+struct SearchName {
+    /// `search_key` of `name`.
+    key: String,
+    /// Characters in `key`.
+    len: usize,
+    name: String,
+    type_slot: u32,
+    english: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct TypeSearchIndex {
+    types: Vec<SearchType>,
+    /// English and localized names, each distinct key once per type.
+    names: Vec<SearchName>,
+    /// (name, byte offset of a word start in its key), sorted by the key
+    /// from that offset: prefix matches of any word are one range.
+    word_starts: Vec<(u32, u32)>,
+    /// Ascending name indices per three-character run of a key.
+    trigrams: HashMap<[char; 3], Vec<u32>>,
+    /// (name, word offset) per word head and per one-character deletion of
+    /// it. A query head sharing a variant is within about two edits of the
+    /// word.
+    fuzzy_heads: HashMap<Head, Vec<(u32, u32)>>,
+}
+
+impl TypeSearchIndex {
+    pub fn new<'a>(entries: impl IntoIterator<Item = &'a ScanTypeIndexEntry>) -> Self {
+        let mut index = TypeSearchIndex::default();
+        for entry in entries {
+            let type_slot = index.types.len() as u32;
+            index.types.push(SearchType {
+                type_id: entry.type_id,
+                group_id: entry.group_id,
+                category_id: entry.category_id,
+            });
+            let localized = entry.type_names.values().map(|name| (name, false));
+            let mut keys = HashSet::new();
+            for (name, english) in std::iter::once((&entry.type_name, true)).chain(localized) {
+                let key = search_key(name);
+                if !key.is_empty() && keys.insert(key.clone()) {
+                    index.names.push(SearchName {
+                        len: key.chars().count(),
+                        key,
+                        name: name.clone(),
+                        type_slot,
+                        english,
+                    });
+                }
+            }
+        }
+
+        for (name_index, name) in index.names.iter().enumerate() {
+            let name_index = name_index as u32;
+            for offset in word_offsets(&name.key) {
+                let word = (name_index, offset as u32);
+                index.word_starts.push(word);
+                for variant in deletion_variants(head(name.key[offset..].chars())) {
+                    push_posting(index.fuzzy_heads.entry(variant).or_default(), word);
+                }
+            }
+            let chars: Vec<char> = name.key.chars().collect();
+            for run in chars.windows(3) {
+                let posting = index.trigrams.entry([run[0], run[1], run[2]]).or_default();
+                push_posting(posting, name_index);
+            }
+        }
+        let names = &index.names;
+        index
+            .word_starts
+            .sort_unstable_by(|a, b| word_suffix(names, *a).cmp(word_suffix(names, *b)));
+        index
+    }
+
+    /// The best `limit` types for `query`: exact, then name prefix, word
+    /// prefix, substring and typo matches, English and then shorter names
+    /// first within each.
+    pub fn search(
+        &self,
+        query: &str,
+        filter: &TypeSearchFilter,
+        limit: usize,
+    ) -> Vec<TypeMatch<'_>> {
+        let query = search_key(query);
+        if query.is_empty() || limit == 0 {
+            return Vec::new();
+        }
+        let query_chars: Vec<char> = query.chars().collect();
+
+        // Best candidate per type slot.
+        let mut best: HashMap<u32, Candidate> = HashMap::new();
+        let offer = |best: &mut HashMap<u32, Candidate>, name_index: u32, kind, typos| {
+            let type_slot = self.names[name_index as usize].type_slot;
+            if !filter.allows(&self.types[type_slot as usize]) {
+                return;
+            }
+            let candidate = (kind, typos, name_index);
+            best.entry(type_slot)
+                .and_modify(|current| {
+                    if self.rank(candidate) < self.rank(*current) {
+                        *current = candidate;
+                    }
+                })
+                .or_insert(candidate);
+        };
+
+        let first = self
+            .word_starts
+            .partition_point(|start| word_suffix(&self.names, *start) < query.as_str());
+        for &(name_index, offset) in self.word_starts[first..]
+            .iter()
+            .take_while(|start| word_suffix(&self.names, **start).starts_with(&query))
+        {
+            let kind = match offset {
+                0 if self.names[name_index as usize].key == query => TypeMatchKind::Exact,
+                0 => TypeMatchKind::Prefix,
+                _ => TypeMatchKind::WordPrefix,
+            };
+            offer(&mut best, name_index, kind, 0);
+        }
+
+        // Later kinds can't outrank a full page of earlier ones.
+        if query_chars.len() >= MIN_INFIX_QUERY_CHARS && best.len() < limit {
+            // Every run of the query must occur in a match, so the rarest
+            // run's names are the only candidates.
+            let rarest = query_chars
+                .windows(3)
+                .map(|run| self.trigrams.get(&[run[0], run[1], run[2]]))
+                .min_by_key(|posting| posting.map_or(0, Vec::len))
+                .flatten();
+            for &name_index in rarest.into_iter().flatten() {
+                if self.names[name_index as usize].key.contains(&query) {
+                    offer(&mut best, name_index, TypeMatchKind::Substring, 0);
+                }
+            }
+        }
+
+        if query_chars.len() >= MIN_INFIX_QUERY_CHARS && best.len() < limit {
+            let max_typos = (query_chars.len() / 4).clamp(1, 2);
+            let mut candidates: Vec<(u32, u32)> =
+                deletion_variants(head(query_chars.iter().copied()))
+                    .iter()
+                    .filter_map(|variant| self.fuzzy_heads.get(variant))
+                    .flatten()
+                    .copied()
+                    .collect();
+            candidates.sort_unstable();
+            candidates.dedup();
+            for word in candidates {
+                let typos =
+                    prefix_edit_distance(&query_chars, word_suffix(&self.names, word), max_typos);
+                if typos <= max_typos {
+                    offer(&mut best, word.0, TypeMatchKind::Fuzzy, typos);
+                }
+            }
+        }
+
+        let mut ranked: Vec<_> = best.into_values().collect();
+        ranked.sort_unstable_by(|a, b| self.rank(*a).cmp(&self.rank(*b)));
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(kind, _, name_index)| {
+                let name = &self.names[name_index as usize];
+                TypeMatch {
+                    type_id: self.types[name.type_slot as usize].type_id,
+                    matched_name: &name.name,
+                    kind,
+                }
+            })
+            .collect()
+    }
+
+    /// Sort key of a candidate: match kind, typos, then English and shorter
+    /// names first, with the type ID as the final tie-break.
+    fn rank(
+        &self,
+        (kind, typos, name_index): Candidate,
+    ) -> (TypeMatchKind, usize, bool, usize, &str, i64) {
+        let name = &self.names[name_index as usize];
+        (
+            kind,
+            typos,
+            !name.english,
+            name.len,
+            &name.key,
+            self.types[name.type_slot as usize].type_id,
+        )
+    }
+}
+
+/// Lowercase, with runs of whitespace collapsed to one space.
+fn search_key(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Byte offsets where words of a key start: after a space or a hyphen,
+/// so "Hel" finds "Sansha's Hel" and "hel" finds "Hel-Thera".
+fn word_offsets(key: &str) -> impl Iterator<Item = usize> + '_ {
+    std::iter::once(0).chain(
+        key.char_indices()
+            .filter(|(_, char)| *char == ' ' || *char == '-')
+            .map(|(offset, char)| offset + char.len_utf8())
+            .filter(move |offset| *offset < key.len()),
+    )
+}
+
+fn word_suffix(names: &[SearchName], (name_index, offset): (u32, u32)) -> &str {
+    &names[name_index as usize].key[offset as usize..]
+}
+
+/// Postings are built in ascending order, so a repeat is always the last.
+fn push_posting<T: PartialEq>(posting: &mut Vec<T>, item: T) {
+    if posting.last() != Some(&item) {
+        posting.push(item);
+    }
+}
+
+fn head(chars: impl Iterator<Item = char>) -> Head {
+    let mut head = ['\0'; FUZZY_HEAD_CHARS];
+    head.iter_mut()
+        .zip(chars)
+        .for_each(|(slot, char)| *slot = char);
+    head
+}
+
+/// A head and each of its one-character deletions.
+fn deletion_variants(word: Head) -> Vec<Head> {
+    let len = word.iter().take_while(|char| **char != '\0').count();
+    let mut variants = vec![word];
+    if len > 1 {
+        variants.extend((0..len).map(|skip| {
+            let mut variant = word;
+            variant.copy_within(skip + 1.., skip);
+            variant[FUZZY_HEAD_CHARS - 1] = '\0';
+            variant
+        }));
+    }
+    variants
+}
+
+/// Edits (insertions, deletions, substitutions and adjacent swaps) to turn
+/// `query` into some prefix of `text`; anything above `max` reads as
+/// `max + 1`.
+fn prefix_edit_distance(query: &[char], text: &str, max: usize) -> usize {
+    let text: Vec<char> = text.chars().take(query.len() + max).collect();
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=text.len()).collect();
+    for (row, query_char) in query.iter().enumerate() {
+        let mut current = vec![row + 1; text.len() + 1];
+        for (column, text_char) in text.iter().enumerate() {
+            let substitution = previous[column] + usize::from(query_char != text_char);
+            current[column + 1] = substitution
+                .min(previous[column + 1] + 1)
+                .min(current[column] + 1);
+            if row > 0
+                && column > 0
+                && *query_char == text[column - 1]
+                && query[row - 1] == *text_char
+            {
+                current[column + 1] = current[column + 1].min(before[column - 1] + 1);
+            }
+        }
+        // Later rows only grow from here (a swap reaches back one more row).
+        let floor = |row: &[usize]| row.iter().copied().min().unwrap_or_default();
+        if floor(&current) > max && floor(&previous) >= max {
+            return max + 1;
+        }
+        before = std::mem::replace(&mut previous, current);
+    }
+    previous.into_iter().min().unwrap_or_default().min(max + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn entry(type_id: i64, name: &str, group_id: i64, category_id: i64) -> ScanTypeIndexEntry {
+        ScanTypeIndexEntry {
+            type_id,
+            type_name: name.to_string(),
+            type_names: BTreeMap::from([("en".to_string(), name.to_string())]),
+            group_id,
+            group_name: "Group".to_string(),
+            group_names: BTreeMap::new(),
+            category_id,
+            category_name: "Category".to_string(),
+        }
+    }
+
+    fn index() -> TypeSearchIndex {
+        let mut raven = entry(638, "Raven", 27, 6);
+        raven
+            .type_names
+            .insert("de".to_string(), "Rabe".to_string());
+        let entries = [
+            entry(22456, "Sabre", 541, 6),
+            entry(621, "Caracal", 26, 6),
+            entry(17634, "Caracal Navy Issue", 358, 6),
+            raven,
+            entry(17636, "Raven Navy Issue", 27, 6),
+            entry(526, "Stasis Webifier I", 65, 7),
+            entry(33816, "Garmur", 25, 6),
+        ];
+        TypeSearchIndex::new(&entries)
+    }
+
+    fn search(query: &str, filter: &TypeSearchFilter) -> Vec<(i64, TypeMatchKind)> {
+        index()
+            .search(query, filter, DEFAULT_SEARCH_LIMIT)
+            .into_iter()
+            .map(|found| (found.type_id, found.kind))
+            .collect()
+    }
+
+    #[test]
+    fn matches_rank_exact_prefix_word_prefix_then_substring() {
+        let any = TypeSearchFilter::default();
+        assert_eq!(search("sab", &any), [(22456, TypeMatchKind::Prefix)]);
+        assert_eq!(
+            search("Raven", &any),
+            [(638, TypeMatchKind::Exact), (17636, TypeMatchKind::Prefix)]
+        );
+        // Shorter names first within a kind.
+        assert_eq!(
+            search("navy", &any),
+            [
+                (17636, TypeMatchKind::WordPrefix),
+                (17634, TypeMatchKind::WordPrefix),
+            ]
+        );
+        assert_eq!(
+            search("aven", &any),
+            [
+                (638, TypeMatchKind::Substring),
+                (17636, TypeMatchKind::Substring),
+            ]
+        );
+        // Too short for a substring match.
+        assert!(search("ab", &any).is_empty());
+    }
+
+    #[test]
+    fn typos_match_after_everything_else() {
+        let any = TypeSearchFilter::default();
+        assert_eq!(search("sbare", &any), [(22456, TypeMatchKind::Fuzzy)]);
+        assert_eq!(
+            search("carcal", &any),
+            [(621, TypeMatchKind::Fuzzy), (17634, TypeMatchKind::Fuzzy),]
+        );
+        assert_eq!(search("webifeir", &any), [(526, TypeMatchKind::Fuzzy)]);
+        assert!(search("zzzzz", &any).is_empty());
+    }
+
+    #[test]
+    fn localized_names_are_searched() {
+        let index = index();
+        let found = index.search("rabe", &TypeSearchFilter::default(), 5);
+        assert_eq!(found[0].type_id, 638);
+        assert_eq!(found[0].matched_name, "Rabe");
+        assert_eq!(found[0].kind, TypeMatchKind::Exact);
+    }
+
+    #[test]
+    fn filters_and_limit_apply() {
+        let ships_only = TypeSearchFilter {
+            category_ids: vec![6],
+            group_ids: Vec::new(),
+        };
+        assert!(search("stasis", &ships_only).is_empty());
+
+        let battleships = TypeSearchFilter {
+            category_ids: Vec::new(),
+            group_ids: vec![27],
+        };
+        assert_eq!(
+            search("ra", &battleships),
+            [(638, TypeMatchKind::Prefix), (17636, TypeMatchKind::Prefix)]
+        );
+
+        let index = index();
+        let found = index.search("ca", &TypeSearchFilter::default(), 1);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].type_id, 621);
+    }
+
+    #[test]
+    fn prefix_edit_distance_counts_swaps_as_one() {
+        let query: Vec<char> = "sbare".chars().collect();
+        assert_eq!(prefix_edit_distance(&query, "sabre", 2), 1);
+        assert_eq!(prefix_edit_distance(&query, "sbarenthing", 2), 0);
+        assert_eq!(prefix_edit_distance(&query, "xxxxx", 2), 3);
+    }
+}
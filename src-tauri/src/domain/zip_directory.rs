@@ -0,0 +1,266 @@
+//! Pure ZIP central-directory handling for fetching only some members of a
+//! remote archive: locate the directory in the archive's tail, list its
+//! members, work out the byte span of each wanted one, and reassemble the
+//! fetched spans into a standalone archive.
+//!
+//! ZIP64 archives are rejected; callers fall back to a full download.
+
+use std::ops::Range;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const CENTRAL_HEADER_LEN: usize = 46;
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const EOCD_LEN: usize = 22;
+/// Offset of the local-header offset field in a central-directory record.
+const LOCAL_OFFSET_FIELD: usize = 42;
+
+/// The end-of-central-directory record plus the longest archive comment:
+/// fetching this many trailing bytes always finds the record.
+pub const MAX_TAIL_LEN: u64 = (EOCD_LEN + u16::MAX as usize) as u64;
+
+/// Where the central directory sits in the archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectoryLocation {
+    pub offset: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipMember {
+    pub name: String,
+    /// Where the member's local header starts.
+    pub local_offset: u64,
+    /// The raw central-directory record, copied into a reassembled archive.
+    record: Vec<u8>,
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+/// Find the end-of-central-directory record in the archive's last bytes.
+pub fn locate_central_directory(tail: &[u8]) -> Result<DirectoryLocation, String> {
+    let eocd = (0..=tail.len().saturating_sub(EOCD_LEN))
+        .rev()
+        .find(|&at| {
+            at + EOCD_LEN <= tail.len()
+                && u32_at(tail, at) == EOCD_SIGNATURE
+                && at + EOCD_LEN + usize::from(u16_at(tail, at + 20)) == tail.len()
+        })
+        .ok_or_else(|| "No ZIP end-of-central-directory record".to_string())?;
+
+    let entries = u16_at(tail, eocd + 10);
+    let size = u32_at(tail, eocd + 12);
+    let offset = u32_at(tail, eocd + 16);
+    if entries == u16::MAX || size == u32::MAX || offset == u32::MAX {
+        return Err("ZIP64 archives aren't supported".to_string());
+    }
+
+    Ok(DirectoryLocation {
+        offset: u64::from(offset),
+        size: u64::from(size),
+    })
+}
+
+/// List the members recorded in a central directory.
+pub fn parse_central_directory(directory: &[u8]) -> Result<Vec<ZipMember>, String> {
+    let mut members = Vec::new();
+    let mut at = 0;
+
+    while at < directory.len() {
+        if directory.len() - at < CENTRAL_HEADER_LEN
+            || u32_at(directory, at) != CENTRAL_HEADER_SIGNATURE
+        {
+            return Err("Malformed ZIP central directory".to_string());
+        }
+        let name_len = usize::from(u16_at(directory, at + 28));
+        let extra_len = usize::from(u16_at(directory, at + 30));
+        let comment_len = usize::from(u16_at(directory, at + 32));
+        let end = at + CENTRAL_HEADER_LEN + name_len + extra_len + comment_len;
+        if end > directory.len() {
+            return Err("Malformed ZIP central directory".to_string());
+        }
+
+        let compressed_size = u32_at(directory, at + 20);
+        let size = u32_at(directory, at + 24);
+        let local_offset = u32_at(directory, at + LOCAL_OFFSET_FIELD);
+        if [compressed_size, size, local_offset].contains(&u32::MAX) {
+            return Err("ZIP64 archives aren't supported".to_string());
+        }
+
+        let name_start = at + CENTRAL_HEADER_LEN;
+        members.push(ZipMember {
+            name: String::from_utf8_lossy(&directory[name_start..name_start + name_len])
+                .into_owned(),
+            local_offset: u64::from(local_offset),
+            record: directory[at..end].to_vec(),
+        });
+        at = end;
+    }
+
+    Ok(members)
+}
+
+/// The byte range of each wanted member: from its local header up to the
+/// next member (or the central directory), which covers its data and any
+/// trailing data descriptor. Pairs are (index into `members`, range).
+pub fn member_spans(
+    members: &[ZipMember],
+    directory_offset: u64,
+    wanted: &[&str],
+) -> Result<Vec<(usize, Range<u64>)>, String> {
+    let mut offsets: Vec<u64> = members.iter().map(|member| member.local_offset).collect();
+    offsets.push(directory_offset);
+    offsets.sort_unstable();
+    offsets.dedup();
+
+    wanted
+        .iter()
+        .map(|name| {
+            let index = members
+                .iter()
+                .position(|member| member.name == *name)
+                .ok_or_else(|| format!("Archive has no {}", name))?;
+            let start = members[index].local_offset;
+            let end = offsets
+                .iter()
+                .copied()
+                .find(|offset| *offset > start)
+                .ok_or_else(|| format!("{} starts past the central directory", name))?;
+            Ok((index, start..end))
+        })
+        .collect()
+}
+
+/// Build a standalone archive from fetched member spans, rewriting each
+/// central-directory record to the member's new offset.
+pub fn assemble_archive(parts: &[(&ZipMember, Vec<u8>)]) -> Result<Vec<u8>, String> {
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+
+    for (member, span) in parts {
+        if span.len() < 4 || u32_at(span, 0) != LOCAL_HEADER_SIGNATURE {
+            return Err(format!(
+                "Fetched bytes for {} aren't a ZIP member",
+                member.name
+            ));
+        }
+        let offset = u32::try_from(archive.len())
+            .map_err(|_| "Reassembled archive is too large".to_string())?;
+        let mut record = member.record.clone();
+        record[LOCAL_OFFSET_FIELD..LOCAL_OFFSET_FIELD + 4].copy_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(&record);
+        archive.extend_from_slice(span);
+    }
+
+    let entries = u16::try_from(parts.len()).map_err(|_| "Too many members".to_string())?;
+    let directory_offset =
+        u32::try_from(archive.len()).map_err(|_| "Reassembled archive is too large".to_string())?;
+    let directory_size = directory.len() as u32;
+    archive.extend_from_slice(&directory);
+
+    archive.extend_from_slice(&EOCD_SIGNATURE.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]); // this disk, directory disk
+    archive.extend_from_slice(&entries.to_le_bytes());
+    archive.extend_from_slice(&entries.to_le_bytes());
+    archive.extend_from_slice(&directory_size.to_le_bytes());
+    archive.extend_from_slice(&directory_offset.to_le_bytes());
+    archive.extend_from_slice(&[0; 2]); // comment length
+    Ok(archive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read, Write};
+
+    fn archive(comment: &str) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let stored = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for (name, contents, options) in [
+            ("_sde.jsonl", "{\"buildNumber\": 1}", stored),
+            ("blueprints.jsonl", &"{}\n".repeat(500), Default::default()),
+            (
+                "types.jsonl",
+                &"{\"_key\": 587}\n".repeat(200),
+                Default::default(),
+            ),
+        ] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.set_comment(comment);
+        writer.finish().unwrap().into_inner()
+    }
+
+    fn read_member(bytes: Vec<u8>, name: &str) -> String {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut contents = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        contents
+    }
+
+    #[test]
+    fn wanted_members_reassemble_into_a_readable_archive() {
+        let bytes = archive("SDE build 1");
+        let location = locate_central_directory(&bytes).unwrap();
+        let directory = &bytes[location.offset as usize..][..location.size as usize];
+        let members = parse_central_directory(directory).unwrap();
+        assert_eq!(members.len(), 3);
+
+        let spans =
+            member_spans(&members, location.offset, &["types.jsonl", "_sde.jsonl"]).unwrap();
+        let parts: Vec<(&ZipMember, Vec<u8>)> = spans
+            .into_iter()
+            .map(|(index, span)| {
+                let span = span.start as usize..span.end as usize;
+                (&members[index], bytes[span].to_vec())
+            })
+            .collect();
+        let assembled = assemble_archive(&parts).unwrap();
+
+        assert!(assembled.len() < bytes.len());
+        assert_eq!(
+            read_member(assembled.clone(), "types.jsonl"),
+            "{\"_key\": 587}\n".repeat(200)
+        );
+        assert_eq!(
+            read_member(assembled.clone(), "_sde.jsonl"),
+            "{\"buildNumber\": 1}"
+        );
+        let archive = zip::ZipArchive::new(Cursor::new(assembled)).unwrap();
+        assert!(archive.index_for_name("blueprints.jsonl").is_none());
+    }
+
+    #[test]
+    fn directory_is_found_in_a_partial_tail() {
+        let bytes = archive("");
+        let full = locate_central_directory(&bytes).unwrap();
+        // Only the last bytes of the archive were fetched.
+        let tail = &bytes[bytes.len() - 40..];
+        assert_eq!(locate_central_directory(tail).unwrap(), full);
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert!(locate_central_directory(b"not a zip at all").is_err());
+        assert!(parse_central_directory(&[0; 50]).is_err());
+
+        let bytes = archive("");
+        let location = locate_central_directory(&bytes).unwrap();
+        let directory = &bytes[location.offset as usize..][..location.size as usize];
+        let members = parse_central_directory(directory).unwrap();
+        assert!(member_spans(&members, location.offset, &["mapStargates.jsonl"]).is_err());
+        assert!(assemble_archive(&[(&members[0], vec![0; 10])]).is_err());
+    }
+}
@@ -411,24 +411,56 @@ pub async fn remove_network_access(
     Ok(())
 }
 
+/// Share a scan. A `solar_system_id` is validated against the SDE map and
+/// replaces the free-text `solar_system` with the system's canonical name.
+/// A d-scan shared without either gets the system its celestials point to.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn share_scan(
     state: State<'_, Mutex<IntelState>>,
     clients: State<'_, TelescopeClient>,
+    app_dir: State<'_, std::path::PathBuf>,
+    sde_service: State<'_, crate::sde::SdeService>,
     network_id: i64,
-    scan_type: String,
+    scan_type: ScanType,
     raw_text: String,
     solar_system: Option<String>,
+    solar_system_id: Option<i64>,
 ) -> Result<NetworkScan, String> {
+    let (solar_system, solar_system_id) = match (solar_system_id, solar_system) {
+        (Some(system_id), _) => {
+            let map = sde_service
+                .map(app_dir.inner().as_path())
+                .await?
+                .ok_or_else(|| "SDE index is not ready yet".to_string())?;
+            let system = map
+                .system(system_id)
+                .ok_or_else(|| format!("Unknown solar system ID {}", system_id))?;
+            (Some(system.name.clone()), Some(system_id))
+        }
+        (None, None) if scan_type == ScanType::Dscan => {
+            match crate::sde::detect_dscan_system(app_dir.inner(), &sde_service, &raw_text).await {
+                Ok(Some(detected)) => (Some(detected.name), Some(detected.system_id)),
+                Ok(None) => (None, None),
+                Err(err) => {
+                    warn!("[Intel] System detection failed: {}", err);
+                    (None, None)
+                }
+            }
+        }
+        (None, solar_system) => (solar_system, None),
+    };
+
     let (base_url, client) = api_context(&state, &clients).await?;
 
     telescope_api::share_scan(
         &client,
         &base_url,
         network_id,
-        &scan_type,
+        scan_type.as_str(),
         &raw_text,
         solar_system.as_deref(),
+        solar_system_id,
     )
     .await
 }
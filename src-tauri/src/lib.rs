@@ -1,6 +1,7 @@
 mod api;
 mod commands;
 mod deep_link;
+mod doctrines;
 mod domain;
 mod intel_commands;
 mod intel_state;
@@ -9,6 +10,8 @@ mod sde;
 mod telescope_api;
 
 pub use models::*;
+#[doc(hidden)]
+pub use sde::{build_index_for_bench, decode_index_for_bench, encode_index_for_bench};
 
 use intel_state::IntelState;
 use tauri::Manager;
@@ -64,6 +67,7 @@ pub fn run() {
             app.manage(deep_link::PendingShare::default());
             app.manage(telescope_api::TelescopeClient::default());
             app.manage(sde::SdeService::default());
+            commands::start_sde_scheduler(app.handle().clone());
 
             {
                 use tauri_plugin_deep_link::DeepLinkExt;
@@ -94,9 +98,29 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::lookup_pilots,
+            commands::lookup_fleet,
+            commands::ingest_paste,
             commands::ensure_sde_index,
             commands::get_sde_status,
+            commands::get_sde_coverage,
+            commands::set_sde_coverage,
+            commands::import_sde_archive,
+            commands::get_sde_sources,
+            commands::set_sde_sources,
+            commands::get_sde_schedule,
+            commands::set_sde_check_interval,
             commands::parse_dscan,
+            commands::diff_dscan,
+            commands::analyze_dscan_fleet,
+            commands::parse_overview,
+            commands::get_type_details,
+            commands::search_types,
+            commands::resolve_system,
+            commands::plan_route,
+            commands::jump_range,
+            commands::capital_threats,
+            commands::parse_probe_scan,
+            commands::diff_probe_scans,
             commands::clear_cache,
             commands::check_for_update,
             commands::is_overlay_open,
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -51,6 +53,9 @@ pub struct SystemStats {
     pub system_id: i64,
     pub system_name: String,
     pub kills: i64,
+    /// Gate jumps from the lookup's origin system, when one was given.
+    #[serde(default)]
+    pub jumps: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
@@ -61,6 +66,14 @@ pub struct PilotFlags {
     pub is_capital: bool,
     pub is_super: bool,
     pub is_solo: bool,
+    #[serde(default)]
+    pub is_tackle: bool,
+    #[serde(default)]
+    pub is_interdiction: bool,
+    #[serde(default)]
+    pub is_logistics: bool,
+    #[serde(default)]
+    pub is_ewar: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -166,10 +179,60 @@ pub struct NetworkScan {
     pub scan_type: String,
     pub raw_text: String,
     pub solar_system: Option<String>,
+    #[serde(default)]
+    pub solar_system_id: Option<i64>,
     pub created_at: String,
     pub submitted_by: Option<ScanSubmitter>,
 }
 
+/// Kinds of paste that can be shared to a network.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanType {
+    Local,
+    Dscan,
+    Probe,
+}
+
+impl ScanType {
+    /// The wire name the telescope API stores in `NetworkScan.scan_type`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScanType::Local => "local",
+            ScanType::Dscan => "dscan",
+            ScanType::Probe => "probe",
+        }
+    }
+}
+
+/// What a clipboard paste was detected as.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteKind {
+    Local,
+    Dscan,
+    Probe,
+    Overview,
+    Fleet,
+    Killmail,
+    Unknown,
+}
+
+/// Result of `ingest_paste`: the detected kind and that pipeline's output.
+/// Serialized as `{ "kind": ..., "result": ... }`; local and killmail
+/// pastes go through the pilot lookup.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", content = "result", rename_all = "snake_case")]
+pub enum IngestedPaste {
+    Local(Vec<PilotIntel>),
+    Dscan(DscanParseResult),
+    Probe(ProbeParseResult),
+    Overview(OverviewParseResult),
+    Fleet(FleetLookupResult),
+    Killmail(Vec<PilotIntel>),
+    Unknown,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PaginatedScans {
     pub data: Vec<NetworkScan>,
@@ -194,34 +257,607 @@ pub struct SdeStatus {
     pub ready: bool,
     pub updating: bool,
     pub last_error: Option<String>,
+    /// Snapshot of the update in flight, if any.
+    #[serde(default)]
+    pub progress: Option<SdeProgress>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SdeUpdateStage {
+    #[default]
+    Checking,
+    Downloading,
+    Verifying,
+    Building,
+    Ready,
+    Failed,
+}
+
+/// Payload of the "sde-progress" event emitted while the SDE lifecycle
+/// runs.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+pub struct SdeProgress {
+    pub stage: SdeUpdateStage,
+    /// Build being downloaded or imported, once known.
+    pub target_build: Option<i64>,
+    pub bytes_downloaded: u64,
+    /// `None` until the server reports the archive length.
+    pub bytes_total: Option<u64>,
+    /// Types, systems and stargates indexed so far.
+    pub rows_indexed: usize,
+    /// Set on the final `Failed` update.
+    pub error: Option<String>,
+}
+
+/// Which SDE categories and groups the type index covers. A group is
+/// indexed when its category is listed or the group itself is.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SdeCoverage {
+    pub category_ids: Vec<i64>,
+    #[serde(default)]
+    pub group_ids: Vec<i64>,
+}
+
+/// How often the background scheduler checks for a new SDE build.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SdeSchedule {
+    pub check_interval_hours: u32,
+    /// RFC 3339 time of the last check a source answered, `None` before
+    /// the first.
+    #[serde(default)]
+    pub last_checked_at: Option<String>,
+    /// The latest build a source reported at that check.
+    #[serde(default)]
+    pub latest_build_number: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ScanTypeIndexEntry {
     pub type_id: i64,
+    /// English name, the canonical lookup key.
     pub type_name: String,
+    /// Names keyed by SDE locale code ("en", "de", "fr", "ja", "ru", "zh", ...).
+    #[serde(default)]
+    pub type_names: BTreeMap<String, String>,
     pub group_id: i64,
     pub group_name: String,
+    #[serde(default)]
+    pub group_names: BTreeMap<String, String>,
     pub category_id: i64,
     pub category_name: String,
 }
 
+/// Fitting slots and weapon hardpoints of a ship hull.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShipSlots {
+    pub high: u8,
+    pub mid: u8,
+    pub low: u8,
+    pub rig: u8,
+    pub turret: u8,
+    pub launcher: u8,
+}
+
+/// Tackle and e-war modules a hull bonus can apply to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HullBonusKind {
+    WarpScrambler,
+    WarpDisruptor,
+    /// Warp disruption field generators and interdiction spheres.
+    Interdiction,
+    StasisWebifier,
+    Ecm,
+    SensorDampener,
+    WeaponDisruptor,
+    TargetPainter,
+    /// Energy neutralizers and nosferatus.
+    CapacitorWarfare,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HullBonus {
+    pub kinds: Vec<HullBonusKind>,
+    /// As shown in game, e.g. "10% bonus to Stasis Webifier range".
+    pub text: String,
+    /// Per level of the hull skill; role bonuses are flat.
+    pub per_level: bool,
+}
+
+/// SDE dogma attributes of a ship hull, unfitted and unskilled.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ShipAttributes {
+    pub slots: ShipSlots,
+    /// Metres.
+    pub signature_radius: f64,
+    /// Base sub-warp velocity in m/s.
+    pub max_velocity: f64,
+    /// AU/s.
+    pub warp_speed: f64,
+    /// Can fit a Covert Ops Cloaking Device (and so warp cloaked).
+    pub covert_ops_cloak: bool,
+    pub jump_drive: bool,
+    /// Only the bonuses to tackle and e-war modules.
+    pub bonuses: Vec<HullBonus>,
+}
+
+/// What a hull is for, derived from its SDE group, market groups and
+/// dogma. A hull can have several roles.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum HullRole {
+    Tackle,
+    Interdiction,
+    Logistics,
+    Ewar,
+    Dps,
+    Hauler,
+    Mining,
+    /// Can fit a covert ops cloak (and so a covert cyno).
+    Covert,
+    Capital,
+    /// Supercarriers and titans; always also `Capital`.
+    Super,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TechLevel {
+    #[serde(rename = "t1")]
+    Tech1,
+    #[serde(rename = "t2")]
+    Tech2,
+    #[serde(rename = "t3")]
+    Tech3,
+    /// Faction, storyline, officer and deadspace hulls.
+    #[serde(rename = "faction")]
+    Faction,
+}
+
+/// Hull size class, from the same SDE names as the roles.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HullSize {
+    Frigate,
+    Destroyer,
+    Cruiser,
+    Battlecruiser,
+    Battleship,
+    /// Capitals, supercapitals and freighters.
+    Capital,
+    /// Industrials, shuttles, capsules and anything unrecognized.
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HullProfile {
+    /// Sorted, without duplicates.
+    pub roles: Vec<HullRole>,
+    pub tech_level: TechLevel,
+    pub size: HullSize,
+}
+
+/// An indexed type and, for ships, its hull attributes and profile.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TypeDetails {
+    #[serde(flatten)]
+    pub entry: ScanTypeIndexEntry,
+    pub ship: Option<ShipAttributes>,
+    pub hull: Option<HullProfile>,
+}
+
+/// How a type search result matched its query, best first.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum TypeMatchKind {
+    Exact,
+    Prefix,
+    /// The query starts a later word of the name ("navy" for "Raven Navy
+    /// Issue").
+    WordPrefix,
+    Substring,
+    /// Within a typo or two of a name or word prefix.
+    Fuzzy,
+}
+
+/// A type search result. `matched_name` is the (possibly localized) name
+/// the query matched; `type_name` is always English.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TypeSearchHit {
+    pub type_id: i64,
+    pub type_name: String,
+    pub group_id: i64,
+    pub group_name: String,
+    pub category_id: i64,
+    pub category_name: String,
+    pub matched_name: String,
+    pub match_kind: TypeMatchKind,
+}
+
+/// A solar system from the SDE map data, with its constellation and
+/// region resolved.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SolarSystemEntry {
+    pub system_id: i64,
+    pub name: String,
+    pub constellation_id: i64,
+    pub constellation_name: String,
+    pub region_id: i64,
+    pub region_name: String,
+    /// Unrounded; the client displays it rounded to one decimal.
+    pub security_status: f64,
+    /// Wormhole class (1-6 for J-space, 7-9 for known space, ...),
+    /// inherited from the constellation or region when the system has none.
+    pub wormhole_class: Option<i64>,
+    /// Universe coordinates in metres. Added in index format 4.
+    #[serde(default)]
+    pub position: Option<[f64; 3]>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutePreference {
+    #[default]
+    Shortest,
+    /// Stay in high-sec where possible.
+    Safer,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Route {
+    /// Origin first, destination last.
+    pub systems: Vec<SolarSystemEntry>,
+    pub jumps: usize,
+}
+
+/// Jump-drive ship classes, by base jump range.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JumpShipClass {
+    /// Carriers, dreadnoughts, force auxiliaries.
+    Capital,
+    /// Supercarriers and titans.
+    Supercapital,
+    BlackOps,
+    JumpFreighter,
+    Rorqual,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JumpRangeSystem {
+    pub system: SolarSystemEntry,
+    pub distance_ly: f64,
+}
+
+/// A capital pilot whose usual systems are within jump range of staging.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CapitalThreat {
+    pub character_id: i64,
+    pub character_name: String,
+    pub is_super: bool,
+    /// Top systems in range, nearest first.
+    pub systems_in_range: Vec<JumpRangeSystem>,
+}
+
+/// Coarse distance bucket of a d-scan row, derived from its parsed distance.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DscanRange {
+    /// Close enough to share a grid with the scanner.
+    OnGrid,
+    /// Off grid but within 1 AU: a short warp, catchable by a bubble.
+    WarpBubble,
+    /// Anywhere else in d-scan range.
+    InSystem,
+    /// No distance column ("-" or unparseable).
+    Unknown,
+}
+
+/// What a d-scan row is, derived from its SDE category and group.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanObjectKind {
+    Ship,
+    Structure,
+    Deployable,
+    Drone,
+    Fighter,
+    Celestial,
+    Probe,
+    Wreck,
+    Other,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DscanEntry {
     pub type_id: Option<i64>,
     pub name: String,
+    /// Type name as pasted (in the client's language).
     pub type_name: String,
+    /// Classified type name in the requested display language.
+    pub display_type_name: Option<String>,
+    /// Raw distance column as pasted, e.g. "2,3 km".
     pub distance: Option<String>,
+    /// `distance` parsed into metres.
+    pub distance_m: Option<f64>,
+    pub range: DscanRange,
+    pub group_id: Option<i64>,
     pub group_name: Option<String>,
+    pub category_id: Option<i64>,
     pub category_name: Option<String>,
+    pub kind: ScanObjectKind,
     pub is_ship: bool,
+    /// Empty for anything but a classified ship.
+    #[serde(default)]
+    pub hull_roles: Vec<HullRole>,
+    #[serde(default)]
+    pub tech_level: Option<TechLevel>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct DscanRangeCounts {
+    pub on_grid: usize,
+    pub warp_bubble: usize,
+    pub in_system: usize,
+    pub unknown: usize,
+}
+
+/// One line of a d-scan composition summary: a type, group or category
+/// and how many rows it accounts for. `id` is None for unclassified types.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DscanCount {
+    pub id: Option<i64>,
+    pub name: String,
+    pub count: usize,
+}
+
+/// Ship rows per hull role; a hull with several roles counts in each.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct HullRoleTotals {
+    pub tackle: usize,
+    pub interdiction: usize,
+    pub logistics: usize,
+    pub ewar: usize,
+    pub dps: usize,
+    pub hauler: usize,
+    pub mining: usize,
+    pub covert: usize,
+    pub capital: usize,
+    #[serde(rename = "super")]
+    pub super_capital: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct TechLevelTotals {
+    pub t1: usize,
+    pub t2: usize,
+    pub t3: usize,
+    pub faction: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ShipClassTotals {
+    pub frigates: usize,
+    pub destroyers: usize,
+    pub cruisers: usize,
+    pub battlecruisers: usize,
+    pub battleships: usize,
+    pub capitals: usize,
+    pub industrials: usize,
+    pub other: usize,
+}
+
+/// Aggregated d-scan composition, each list sorted by count descending
+/// then name.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DscanSummary {
+    pub by_type: Vec<DscanCount>,
+    pub by_group: Vec<DscanCount>,
+    pub by_category: Vec<DscanCount>,
+    pub ship_classes: ShipClassTotals,
+    #[serde(default)]
+    pub hull_roles: HullRoleTotals,
+    #[serde(default)]
+    pub tech_levels: TechLevelTotals,
+}
+
+/// A type or group whose row count differs between two scans.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DscanCountChange {
+    pub id: Option<i64>,
+    pub name: String,
+    pub before: usize,
+    pub after: usize,
+    pub delta: i64,
+}
+
+/// An object present in both scans whose distance bucket changed.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DscanMove {
+    pub type_id: Option<i64>,
+    pub type_name: String,
+    pub name: String,
+    pub from: DscanRange,
+    pub to: DscanRange,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DscanDiff {
+    /// Types whose count went up ("+3 Sabre"), largest increase first.
+    pub arrivals: Vec<DscanCountChange>,
+    /// Types whose count went down ("-1 Loki"), largest decrease first.
+    pub departures: Vec<DscanCountChange>,
+    /// Per-group count changes, largest absolute change first.
+    pub group_changes: Vec<DscanCountChange>,
+    pub moved: Vec<DscanMove>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DscanParseResult {
     pub total_rows: usize,
     pub ship_count: usize,
+    /// Ship rows per distance bucket.
+    pub ships_by_range: DscanRangeCounts,
+    pub summary: DscanSummary,
     pub entries: Vec<DscanEntry>,
+    /// The scanner's system, inferred from celestials when the map is
+    /// available.
+    #[serde(default)]
+    pub detected_system: Option<DetectedSystem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DetectedSystem {
+    pub system_id: i64,
+    pub name: String,
+    /// 0.0..=1.0: the system's share of the evidence.
+    pub confidence: f64,
+    /// Rows that named a known system.
+    pub evidence: usize,
+}
+
+/// Column positions detected in an overview paste; `None` when the column
+/// was not pasted.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct OverviewColumns {
+    pub name: Option<usize>,
+    pub type_name: Option<usize>,
+    pub distance: Option<usize>,
+    pub corporation: Option<usize>,
+    pub alliance: Option<usize>,
+}
+
+/// One overview row. Tickers are as pasted, without the client's brackets.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OverviewEntry {
+    pub name: String,
+    pub type_name: Option<String>,
+    pub type_id: Option<i64>,
+    pub group_id: Option<i64>,
+    pub group_name: Option<String>,
+    pub category_id: Option<i64>,
+    pub kind: ScanObjectKind,
+    pub is_ship: bool,
+    pub distance: Option<String>,
+    pub distance_m: Option<f64>,
+    pub range: DscanRange,
+    pub corporation_ticker: Option<String>,
+    pub alliance_ticker: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OverviewParseResult {
+    pub total_rows: usize,
+    pub ship_count: usize,
+    pub columns: OverviewColumns,
+    /// Rows per ticker, most common first.
+    pub by_corporation: Vec<DscanCount>,
+    pub by_alliance: Vec<DscanCount>,
+    pub entries: Vec<OverviewEntry>,
+}
+
+/// Site type of a probe-scanner signature, from its group column.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureKind {
+    Combat,
+    Data,
+    Relic,
+    Gas,
+    Ore,
+    Wormhole,
+    /// Not scanned far enough to show a group, or an unrecognized one.
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProbeSignature {
+    /// Signature ID, e.g. "ABC-123".
+    pub id: String,
+    pub scan_group: String,
+    pub group: Option<String>,
+    pub kind: SignatureKind,
+    pub site_name: Option<String>,
+    /// Percent, 0.0..=100.0.
+    pub signal_strength: Option<f64>,
+    pub distance: Option<String>,
+    pub distance_m: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProbeParseResult {
+    pub total_rows: usize,
+    pub resolved_count: usize,
+    pub signatures: Vec<ProbeSignature>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProbeDiff {
+    pub new: Vec<ProbeSignature>,
+    pub gone: Vec<ProbeSignature>,
+    /// Present in both pastes, and fully scanned only in the newer one.
+    pub resolved: Vec<ProbeSignature>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DoctrineMatch {
+    pub name: String,
+    /// 0.0..=1.0: component fulfilment weighted by how much of the fleet
+    /// the doctrine explains.
+    pub confidence: f64,
+    pub matched_ships: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FleetAnalysis {
+    pub likely_doctrine: Option<DoctrineMatch>,
+    /// Every doctrine with at least one matching ship, best first.
+    pub matches: Vec<DoctrineMatch>,
+    pub ship_count: usize,
+    pub logistics_count: usize,
+    pub tackle_count: usize,
+    pub capital_count: usize,
+    pub has_capitals: bool,
+}
+
+/// One row of a fleet-composition paste. `type_id`/`group_id` are filled
+/// from the SDE index; `group_name` falls back to the pasted ship class.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FleetMember {
+    pub pilot: String,
+    pub system: String,
+    pub ship_type_name: String,
+    pub type_id: Option<i64>,
+    pub group_id: Option<i64>,
+    pub group_name: Option<String>,
+    pub position: String,
+    pub wing: Option<String>,
+    pub squad: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FleetWing {
+    pub name: String,
+    pub member_count: usize,
+    /// Ship counts by hull, most common first.
+    pub ships: Vec<DscanCount>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FleetComposition {
+    pub members: Vec<FleetMember>,
+    pub wings: Vec<FleetWing>,
+    /// Unique pilot names in paste order.
+    pub pilot_names: Vec<String>,
+    /// The system the fleet is considered to be in.
+    pub reference_system: Option<String>,
+    pub out_of_position: Vec<FleetMember>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FleetLookupResult {
+    pub composition: FleetComposition,
+    pub pilots: Vec<PilotIntel>,
 }
 
 #[cfg(test)]
@@ -375,6 +1011,17 @@ mod tests {
         assert_eq!(result.data[0].solar_system.as_deref(), Some("Jita"));
     }
 
+    #[test]
+    fn scan_type_wire_names_match_serde() {
+        for scan_type in [ScanType::Local, ScanType::Dscan, ScanType::Probe] {
+            assert_eq!(
+                serde_json::to_value(scan_type).unwrap(),
+                json!(scan_type.as_str())
+            );
+        }
+        assert!(serde_json::from_value::<ScanType>(json!("overview")).is_err());
+    }
+
     #[test]
     fn entity_info_type_renames_correctly() {
         let data = json!({
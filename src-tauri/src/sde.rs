@@ -1,35 +1,382 @@
-//! SDE index I/O service: download, build, load and cache the type index.
-//! The pure pieces live in `crate::domain`: d-scan parsing/classification in
-//! `domain::dscan`, and the check/update lifecycle in `domain::sde_lifecycle`
+//! SDE index I/O service: download, build, load and cache the type and
+//! solar-system indexes. The pure pieces live in `crate::domain`: d-scan
+//! parsing/classification in `domain::dscan`, system search in
+//! `domain::map`, and the check/update lifecycle in `domain::sde_lifecycle`
 //! (driven here by `ensure_sde_index`).
 
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, PoisonError};
+use std::time::Duration;
 
-use chrono::Utc;
-use log::warn;
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use tokio::io::AsyncWriteExt;
 use zip::ZipArchive;
 
-use crate::domain::dscan::SdeIndex;
-use crate::domain::sde_lifecycle::{step, SdeEffect, SdeEvent, SdePhase};
-use crate::models::{ScanTypeIndexEntry, SdeStatus};
+use crate::domain::dogma::{ship_attributes, BonusLine, SHIP_ATTRIBUTE_IDS};
+use crate::domain::dscan::{SdeIndex, SHIP_CATEGORY_ID};
+use crate::domain::hull_taxonomy::{hull_profile, HullFacts};
+use crate::domain::locate::MIN_AUTOFILL_CONFIDENCE;
+use crate::domain::map::MapIndex;
+use crate::domain::sde_lifecycle::{check_backoff, step, SdeEffect, SdeEvent, SdePhase};
+use crate::domain::zip_directory::{
+    assemble_archive, locate_central_directory, member_spans, parse_central_directory, ZipMember,
+    MAX_TAIL_LEN,
+};
+use crate::models::{
+    DetectedSystem, HullBonus, HullBonusKind, HullProfile, HullRole, HullSize, ScanTypeIndexEntry,
+    SdeCoverage, SdeProgress, SdeSchedule, SdeStatus, SdeUpdateStage, ShipAttributes, ShipSlots,
+    SolarSystemEntry, TechLevel,
+};
 
 const SDE_URL: &str =
     "https://developers.eveonline.com/static-data/eve-online-static-data-latest-jsonl.zip";
-const INDEX_FILE: &str = "sde_type_index.json";
-const INCLUDED_CATEGORY_IDS: [i64; 6] = [2, 3, 6, 18, 22, 65];
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
+const INDEX_FILE: &str = "sde_type_index.bin";
+/// Where caches lived before the binary format; migrated on first load.
+const LEGACY_INDEX_FILE: &str = "sde_type_index.json";
+/// The index a rebuild replaced, restored if the new file turns out
+/// unreadable.
+const PREVIOUS_INDEX_FILE: &str = "sde_type_index.previous.bin";
+const COVERAGE_FILE: &str = "sde_coverage.json";
+const SOURCES_FILE: &str = "sde_sources.json";
+const LAST_ERROR_FILE: &str = "sde_last_error.json";
+const REJECTED_BUILD_FILE: &str = "sde_rejected_build.json";
+/// `sde-members-<build>.zip`: the index's members of the archive the cache
+/// was built from, kept so a coverage change rebuilds without the network.
+const KEPT_MEMBERS_PREFIX: &str = "sde-members-";
+const SCHEDULE_FILE: &str = "sde_schedule.json";
+
+/// The archive members the index is built from; the rest of the SDE is
+/// never fetched when the server supports range requests.
+const SDE_MEMBERS: [&str; 11] = [
+    "_sde.jsonl",
+    "categories.jsonl",
+    "groups.jsonl",
+    "marketGroups.jsonl",
+    "types.jsonl",
+    "typeDogma.jsonl",
+    "typeBonus.jsonl",
+    "mapRegions.jsonl",
+    "mapConstellations.jsonl",
+    "mapSolarSystems.jsonl",
+    "mapStargates.jsonl",
+];
+
+/// Celestial, Station, Ship, Drone, Deployable, Starbase, Sovereignty
+/// Structures, Orbitals, Structure, Fighter.
+const DEFAULT_CATEGORY_IDS: [i64; 10] = [2, 3, 6, 18, 22, 23, 40, 46, 65, 87];
+/// Scanner Probe, Survey Probe, Interdiction Probe: scan-relevant groups
+/// from the otherwise uninteresting Charge category.
+const DEFAULT_GROUP_IDS: [i64; 3] = [479, 492, 548];
+/// What caches built before coverage was configurable contain.
+const LEGACY_CATEGORY_IDS: [i64; 6] = [2, 3, 6, 18, 22, 65];
+
+/// Bump whenever the builder starts extracting data an older cache lacks;
+/// a cache written by an older format is rebuilt on the next check.
+const INDEX_FORMAT_VERSION: u32 = 7;
+
+/// Fewer types than this in a covered category means `types.jsonl` was
+/// truncated or its schema changed; every real build has several times more.
+const MIN_TYPES_PER_CATEGORY: [(i64, usize); 4] = [(2, 100), (6, 300), (18, 100), (65, 20)];
+/// Types every build has: Moon, Rifter, Capsule, Hobgoblin I, Astrahus.
+const SENTINEL_TYPE_IDS: [(i64, i64); 5] = [(2, 14), (6, 587), (6, 670), (18, 2456), (65, 35832)];
+const MIN_SOLAR_SYSTEMS: usize = 5000;
+const MIN_STARGATES: usize = 2000;
+const JITA_SYSTEM_ID: i64 = 30000142;
+
+const DEFAULT_CHECK_INTERVAL_HOURS: u32 = 12;
+/// Check interval bounds: at most hourly, at least weekly.
+const CHECK_INTERVAL_HOURS: std::ops::RangeInclusive<u32> = 1..=7 * 24;
+
+/// Deserialize only reads legacy JSON caches; new ones are written in the
+/// binary format below.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
 struct SdeIndexCache {
+    /// Caches written before versioning deserialize as 0.
+    #[serde(default)]
+    format_version: u32,
     build_number: i64,
     generated_at: String,
+    /// Coverage the entries were built with.
+    #[serde(default = "legacy_coverage")]
+    coverage: SdeCoverage,
     entries: Vec<ScanTypeIndexEntry>,
+    /// Added in format 2.
+    #[serde(default)]
+    systems: Vec<SolarSystemEntry>,
+    /// Stargate connections as (system, system), each listed once. Added
+    /// in format 3.
+    #[serde(default)]
+    stargates: Vec<(i64, i64)>,
+    /// Hull attributes by ship type ID, sorted. Added in format 5.
+    #[serde(default)]
+    ship_attributes: Vec<(i64, ShipAttributes)>,
+    /// Hull roles, tech level and size by ship type ID, sorted. Added in
+    /// format 6; sizes since format 7.
+    #[serde(default)]
+    hull_profiles: Vec<(i64, HullProfile)>,
+}
+
+impl SdeIndexCache {
+    /// Usable, but built by an older builder or with a different coverage
+    /// than configured: rebuild when possible.
+    fn is_stale(&self, coverage: &SdeCoverage) -> bool {
+        self.format_version < INDEX_FORMAT_VERSION || self.coverage != *coverage
+    }
+
+    /// Sanity-check a freshly built index before it replaces the working
+    /// one: type counts per covered category, known sentinel types (with
+    /// hull attributes for ships), and a plausible map.
+    fn validate(&self) -> Result<(), String> {
+        let covered = |category_id: i64| self.coverage.category_ids.contains(&category_id);
+
+        let mut counts: HashMap<i64, usize> = HashMap::new();
+        for entry in &self.entries {
+            *counts.entry(entry.category_id).or_default() += 1;
+        }
+        for (category_id, minimum) in MIN_TYPES_PER_CATEGORY {
+            let count = counts.get(&category_id).copied().unwrap_or_default();
+            if covered(category_id) && count < minimum {
+                return Err(format!(
+                    "SDE index has only {} types in category {} (expected at least {})",
+                    count, category_id, minimum
+                ));
+            }
+        }
+
+        let type_ids: HashSet<i64> = self.entries.iter().map(|entry| entry.type_id).collect();
+        if let Some((_, type_id)) = SENTINEL_TYPE_IDS
+            .iter()
+            .find(|(category_id, type_id)| covered(*category_id) && !type_ids.contains(type_id))
+        {
+            return Err(format!("SDE index is missing type {}", type_id));
+        }
+        let ship_ids: HashSet<i64> = self
+            .ship_attributes
+            .iter()
+            .map(|(type_id, _)| *type_id)
+            .collect();
+        if let Some((_, type_id)) = SENTINEL_TYPE_IDS.iter().find(|(category_id, type_id)| {
+            *category_id == SHIP_CATEGORY_ID && covered(*category_id) && !ship_ids.contains(type_id)
+        }) {
+            return Err(format!("SDE index has no attributes for ship {}", type_id));
+        }
+
+        if self.systems.len() < MIN_SOLAR_SYSTEMS {
+            return Err(format!(
+                "SDE index has only {} solar systems",
+                self.systems.len()
+            ));
+        }
+        if !self
+            .systems
+            .iter()
+            .any(|system| system.system_id == JITA_SYSTEM_ID)
+        {
+            return Err(format!("SDE index is missing system {}", JITA_SYSTEM_ID));
+        }
+        if self.stargates.len() < MIN_STARGATES {
+            return Err(format!(
+                "SDE index has only {} stargate connections",
+                self.stargates.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn default_coverage() -> SdeCoverage {
+    SdeCoverage {
+        category_ids: DEFAULT_CATEGORY_IDS.to_vec(),
+        group_ids: DEFAULT_GROUP_IDS.to_vec(),
+    }
+}
+
+fn legacy_coverage() -> SdeCoverage {
+    SdeCoverage {
+        category_ids: LEGACY_CATEGORY_IDS.to_vec(),
+        group_ids: Vec::new(),
+    }
+}
+
+/// Sorted and deduplicated, so equal configurations compare equal.
+fn normalize_coverage(mut coverage: SdeCoverage) -> SdeCoverage {
+    coverage.category_ids.sort_unstable();
+    coverage.category_ids.dedup();
+    coverage.group_ids.sort_unstable();
+    coverage.group_ids.dedup();
+    coverage
+}
+
+fn coverage_path(app_dir: &Path) -> PathBuf {
+    app_dir.join(COVERAGE_FILE)
+}
+
+/// The configured coverage, or the default when none was saved.
+pub fn load_coverage(app_dir: &Path) -> Result<SdeCoverage, String> {
+    let path = coverage_path(app_dir);
+    if !path.exists() {
+        return Ok(default_coverage());
+    }
+
+    let json = std::fs::read(path).map_err(|err| err.to_string())?;
+    let coverage = serde_json::from_slice(&json).map_err(|err| err.to_string())?;
+    Ok(normalize_coverage(coverage))
+}
+
+/// Persist a new coverage. The next `ensure_sde_index` sees the cache's
+/// coverage differ and rebuilds.
+pub fn save_coverage(app_dir: &Path, coverage: SdeCoverage) -> Result<SdeCoverage, String> {
+    let coverage = normalize_coverage(coverage);
+    let json = serde_json::to_vec_pretty(&coverage).map_err(|err| err.to_string())?;
+    std::fs::write(coverage_path(app_dir), json).map_err(|err| err.to_string())?;
+    Ok(coverage)
+}
+
+fn sources_path(app_dir: &Path) -> PathBuf {
+    app_dir.join(SOURCES_FILE)
+}
+
+/// The SDE archive URLs to try, in fallback order: the official URL unless
+/// mirrors were configured.
+pub fn load_sde_sources(app_dir: &Path) -> Result<Vec<String>, String> {
+    let path = sources_path(app_dir);
+    if !path.exists() {
+        return Ok(vec![SDE_URL.to_string()]);
+    }
+
+    let json = std::fs::read(path).map_err(|err| err.to_string())?;
+    let sources: Vec<String> = serde_json::from_slice(&json).map_err(|err| err.to_string())?;
+    normalize_sources(sources)
+}
+
+/// Persist a new source order. An empty list restores the official URL.
+pub fn save_sde_sources(app_dir: &Path, sources: Vec<String>) -> Result<Vec<String>, String> {
+    let sources = normalize_sources(sources)?;
+    let json = serde_json::to_vec_pretty(&sources).map_err(|err| err.to_string())?;
+    std::fs::write(sources_path(app_dir), json).map_err(|err| err.to_string())?;
+    Ok(sources)
+}
+
+/// Trimmed, http(s) only, first occurrence kept.
+fn normalize_sources(sources: Vec<String>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for source in sources {
+        let source = source.trim();
+        let url = url::Url::parse(source).map_err(|err| format!("{}: {}", source, err))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "{}: only http and https sources are supported",
+                source
+            ));
+        }
+        if !normalized.iter().any(|existing| existing == source) {
+            normalized.push(source.to_string());
+        }
+    }
+
+    if normalized.is_empty() {
+        normalized.push(SDE_URL.to_string());
+    }
+    Ok(normalized)
+}
+
+fn schedule_path(app_dir: &Path) -> PathBuf {
+    app_dir.join(SCHEDULE_FILE)
+}
+
+/// The configured check interval and when a source last answered.
+fn load_sde_schedule(app_dir: &Path) -> Result<SdeSchedule, String> {
+    let path = schedule_path(app_dir);
+    if !path.exists() {
+        return Ok(SdeSchedule {
+            check_interval_hours: DEFAULT_CHECK_INTERVAL_HOURS,
+            last_checked_at: None,
+            latest_build_number: None,
+        });
+    }
+
+    let json = std::fs::read(path).map_err(|err| err.to_string())?;
+    serde_json::from_slice(&json).map_err(|err| err.to_string())
+}
+
+/// The schedule, or the default one when the file can't be read; the next
+/// save replaces a damaged file.
+pub fn load_sde_schedule_or_default(app_dir: &Path) -> SdeSchedule {
+    load_sde_schedule(app_dir).unwrap_or_else(|err| {
+        warn!("Failed to read SDE schedule: {}", err);
+        SdeSchedule {
+            check_interval_hours: DEFAULT_CHECK_INTERVAL_HOURS,
+            last_checked_at: None,
+            latest_build_number: None,
+        }
+    })
+}
+
+fn save_sde_schedule(app_dir: &Path, schedule: &SdeSchedule) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(schedule).map_err(|err| err.to_string())?;
+    write_atomically(&schedule_path(app_dir), &json).map_err(|err| err.to_string())
+}
+
+/// Write `bytes` beside `path` and rename them over it once synced, so a
+/// crash leaves the old file or the new one, never a torn one.
+fn write_atomically(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    use std::io::Write as _;
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    let mut file = File::create(&temp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    std::fs::rename(&temp_path, path)
+}
+
+/// Persist a new check interval, clamped to hourly..weekly, and wake the
+/// scheduler so it applies right away.
+pub fn save_sde_check_interval(
+    app_dir: &Path,
+    service: &SdeService,
+    hours: u32,
+) -> Result<SdeSchedule, String> {
+    let mut schedule = load_sde_schedule_or_default(app_dir);
+    schedule.check_interval_hours =
+        hours.clamp(*CHECK_INTERVAL_HOURS.start(), *CHECK_INTERVAL_HOURS.end());
+    save_sde_schedule(app_dir, &schedule)?;
+    service.schedule_changed.notify_one();
+    Ok(schedule)
+}
+
+fn record_sde_check(app_dir: &Path, latest_build_number: i64) -> Result<(), String> {
+    let mut schedule = load_sde_schedule_or_default(app_dir);
+    schedule.last_checked_at = Some(Utc::now().to_rfc3339());
+    schedule.latest_build_number = Some(latest_build_number);
+    save_sde_schedule(app_dir, &schedule)
+}
+
+/// `None` when never checked, or the recorded time is unreadable or in
+/// the future.
+fn since_last_check(schedule: &SdeSchedule) -> Option<Duration> {
+    let checked_at = DateTime::parse_from_rfc3339(schedule.last_checked_at.as_deref()?).ok()?;
+    (Utc::now() - checked_at.with_timezone(&Utc)).to_std().ok()
+}
+
+fn check_interval(schedule: &SdeSchedule) -> Duration {
+    Duration::from_secs(u64::from(schedule.check_interval_hours) * 60 * 60)
+}
+
+/// How long until the next scheduled check is due; zero when it already is.
+fn next_check_delay(schedule: &SdeSchedule) -> Duration {
+    since_last_check(schedule).map_or(Duration::ZERO, |since| {
+        check_interval(schedule).saturating_sub(since)
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -39,6 +386,8 @@ struct SdeIndexCache {
 #[derive(Default)]
 pub struct SdeService {
     index: tokio::sync::RwLock<Option<Arc<SdeIndex>>>,
+    /// Loaded together with `index` from the same cache file.
+    map: tokio::sync::RwLock<Option<Arc<MapIndex>>>,
     /// Update guard: held across the whole check + build in
     /// `ensure_sde_index` so two concurrent calls can't both download the
     /// archive and write the same `sde-{build}.zip.download` temp file.
@@ -46,6 +395,11 @@ pub struct SdeService {
     /// RwLock as usual. The lifecycle phase itself is call-local: the
     /// on-disk cache is the source of truth between calls.
     update_guard: tokio::sync::Mutex<()>,
+    /// Progress of the update in flight; `None` when idle. A plain mutex so
+    /// `get_sde_status` never waits on `update_guard`.
+    progress: Arc<std::sync::Mutex<Option<SdeProgress>>>,
+    /// Wakes the background scheduler when the check interval changes.
+    schedule_changed: tokio::sync::Notify,
 }
 
 impl SdeService {
@@ -55,43 +409,383 @@ impl SdeService {
         if let Some(index) = self.index.read().await.clone() {
             return Ok(Some(index));
         }
+        Ok(self.load(app_dir).await?.map(|(index, _)| index))
+    }
+
+    /// Return the in-memory solar-system index, loading it from disk on
+    /// first use. Returns Ok(None) when no index has been built yet.
+    pub async fn map(&self, app_dir: &Path) -> Result<Option<Arc<MapIndex>>, String> {
+        if let Some(map) = self.map.read().await.clone() {
+            return Ok(Some(map));
+        }
+        Ok(self.load(app_dir).await?.map(|(_, map)| map))
+    }
 
+    /// Parse the cache file once and keep both indexes built from it.
+    async fn load(&self, app_dir: &Path) -> Result<Option<(Arc<SdeIndex>, Arc<MapIndex>)>, String> {
         let dir = app_dir.to_path_buf();
-        let loaded = tokio::task::spawn_blocking(move || -> Result<Option<SdeIndex>, String> {
-            Ok(load_index_cache(&dir)?.map(|cache| SdeIndex::from_entries(cache.entries)))
+        let loaded = tokio::task::spawn_blocking(move || -> Result<_, String> {
+            Ok(load_index_cache(&dir)?.map(|cache| {
+                (
+                    SdeIndex::from_entries(cache.entries)
+                        .with_ship_attributes(cache.ship_attributes)
+                        .with_hull_profiles(cache.hull_profiles),
+                    MapIndex::from_systems(cache.systems, &cache.stargates),
+                )
+            }))
         })
         .await
         .map_err(|err| err.to_string())??;
 
-        let Some(index) = loaded else {
+        let Some((index, map)) = loaded else {
             return Ok(None);
         };
 
-        let index = Arc::new(index);
+        let (index, map) = (Arc::new(index), Arc::new(map));
         *self.index.write().await = Some(index.clone());
-        Ok(Some(index))
+        *self.map.write().await = Some(map.clone());
+        Ok(Some((index, map)))
     }
 
-    /// Drop the cached index so the next access reloads from disk
+    /// Drop the cached indexes so the next access reloads from disk
     /// (call after an SDE update rewrites the cache file).
     pub async fn invalidate(&self) {
         *self.index.write().await = None;
+        *self.map.write().await = None;
+    }
+
+    /// The update in flight, if any.
+    pub fn progress(&self) -> Option<SdeProgress> {
+        self.progress
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn reporter(&self, listener: ProgressListener) -> ProgressReporter {
+        ProgressReporter {
+            snapshot: self.progress.clone(),
+            listener,
+        }
+    }
+}
+
+/// Receives every progress update; the command layer emits them as
+/// "sde-progress" events.
+pub type ProgressListener = Arc<dyn Fn(&SdeProgress) + Send + Sync>;
+
+/// Report download progress at most once per this many bytes.
+const PROGRESS_BYTES_STEP: u64 = 1 << 20;
+
+/// Updates the service's in-flight snapshot and forwards each change to
+/// the listener. Cheap to clone into blocking tasks.
+#[derive(Clone)]
+struct ProgressReporter {
+    snapshot: Arc<std::sync::Mutex<Option<SdeProgress>>>,
+    listener: ProgressListener,
+}
+
+impl ProgressReporter {
+    fn update(&self, change: impl FnOnce(&mut SdeProgress)) {
+        let progress = {
+            let mut snapshot = self.snapshot.lock().unwrap_or_else(PoisonError::into_inner);
+            let progress = snapshot.get_or_insert_with(SdeProgress::default);
+            change(progress);
+            progress.clone()
+        };
+        (self.listener)(&progress);
+    }
+
+    fn stage(&self, stage: SdeUpdateStage) {
+        self.update(|progress| progress.stage = stage);
+    }
+
+    /// Report the outcome and clear the in-flight snapshot.
+    fn finish(&self, error: Option<String>) {
+        self.update(|progress| {
+            progress.stage = match error {
+                Some(_) => SdeUpdateStage::Failed,
+                None => SdeUpdateStage::Ready,
+            };
+            progress.error = error;
+        });
+        *self.snapshot.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+fn last_error_path(app_dir: &Path) -> PathBuf {
+    app_dir.join(LAST_ERROR_FILE)
+}
+
+/// Why the last check or update failed, kept until one succeeds.
+fn load_last_error(app_dir: &Path) -> Option<String> {
+    let json = std::fs::read(last_error_path(app_dir)).ok()?;
+    serde_json::from_slice(&json).ok()
+}
+
+fn save_last_error(app_dir: &Path, error: Option<&str>) -> Result<(), String> {
+    let path = last_error_path(app_dir);
+    match error {
+        Some(error) => {
+            let json = serde_json::to_vec(error).map_err(|err| err.to_string())?;
+            std::fs::write(path, json).map_err(|err| err.to_string())
+        }
+        None if path.exists() => std::fs::remove_file(path).map_err(|err| err.to_string()),
+        None => Ok(()),
     }
 }
 
+/// A remote build whose index failed validation, and the builder and
+/// coverage it failed for.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct RejectedBuild {
+    build_number: i64,
+    format_version: u32,
+    coverage: SdeCoverage,
+}
+
+fn rejected_build_path(app_dir: &Path) -> PathBuf {
+    app_dir.join(REJECTED_BUILD_FILE)
+}
+
+/// The build a rebuild with this builder and `coverage` was last rejected
+/// for; rebuilding a stale cache from it again would fail the same way.
+fn load_rejected_build(app_dir: &Path, coverage: &SdeCoverage) -> Option<i64> {
+    let json = std::fs::read(rejected_build_path(app_dir)).ok()?;
+    let rejected: RejectedBuild = serde_json::from_slice(&json).ok()?;
+    (rejected.format_version == INDEX_FORMAT_VERSION && rejected.coverage == *coverage)
+        .then_some(rejected.build_number)
+}
+
+fn save_rejected_build(
+    app_dir: &Path,
+    build_number: Option<i64>,
+    coverage: &SdeCoverage,
+) -> Result<(), String> {
+    let path = rejected_build_path(app_dir);
+    match build_number {
+        Some(build_number) => {
+            let rejected = RejectedBuild {
+                build_number,
+                format_version: INDEX_FORMAT_VERSION,
+                coverage: coverage.clone(),
+            };
+            let json = serde_json::to_vec(&rejected).map_err(|err| err.to_string())?;
+            std::fs::write(path, json).map_err(|err| err.to_string())
+        }
+        None if path.exists() => std::fs::remove_file(path).map_err(|err| err.to_string()),
+        None => Ok(()),
+    }
+}
+
+/// Persist the outcome for `get_sde_status` and send the final progress
+/// update. A rejected index is reported even though the previous one is
+/// still serving.
+fn finish_update(app_dir: &Path, progress: &ProgressReporter, result: Result<&SdeStatus, &String>) {
+    let error = match result {
+        Ok(status) => status.last_error.clone(),
+        Err(err) => Some(err.clone()),
+    };
+    if let Err(err) = save_last_error(app_dir, error.as_deref()) {
+        warn!("Failed to record SDE update outcome: {}", err);
+    }
+    progress.finish(error);
+}
+
+/// The system a d-scan paste was taken in, when the celestials on it name
+/// one confidently enough to fill in a shared scan. `None` while the index
+/// isn't built.
+pub async fn detect_dscan_system(
+    app_dir: &Path,
+    service: &SdeService,
+    text: &str,
+) -> Result<Option<DetectedSystem>, String> {
+    let (Some(index), Some(map)) = (service.index(app_dir).await?, service.map(app_dir).await?)
+    else {
+        return Ok(None);
+    };
+
+    let text = text.to_string();
+    tokio::task::spawn_blocking(move || {
+        let result = crate::domain::dscan::parse_dscan_text(&index, &text);
+        crate::domain::locate::detect_system(&map, &result.entries)
+            .filter(|detected| detected.confidence >= MIN_AUTOFILL_CONFIDENCE)
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
 // ---------------------------------------------------------------------------
 // Status / update
 // ---------------------------------------------------------------------------
 
-/// Drives the `domain::sde_lifecycle` machine: seed the phase from the
-/// on-disk cache, feed `CheckRequested`, execute each returned effect and
-/// feed its outcome back in until the machine settles.
-pub async fn ensure_sde_index(app_dir: &Path, service: &SdeService) -> Result<SdeStatus, String> {
+/// Check the SDE sources for a newer build and update the index from the
+/// first one that answers. Unless `force`d, skips the check when the last
+/// one is more recent than the check interval.
+pub async fn ensure_sde_index(
+    app_dir: &Path,
+    service: &SdeService,
+    listener: ProgressListener,
+    force: bool,
+) -> Result<SdeStatus, String> {
+    update_sde_index(app_dir, service, listener, force)
+        .await
+        .map(|(status, _)| status)
+}
+
+/// `ensure_sde_index`, also telling whether a new index was installed.
+async fn update_sde_index(
+    app_dir: &Path,
+    service: &SdeService,
+    listener: ProgressListener,
+    force: bool,
+) -> Result<(SdeStatus, bool), String> {
     // Hold the update guard across the whole drive so concurrent calls
     // can't both download/build the same archive.
     let _update_guard = service.update_guard.lock().await;
+    let progress = service.reporter(listener);
+
+    let result = async {
+        let mut current = load_index_cache_async(app_dir).await?;
+        let coverage = load_coverage(app_dir)?;
+
+        // A stale cache is rebuilt from the members kept from its own build
+        // first: a coverage change needs no download. Members an older
+        // builder didn't keep fail verification and fall through.
+        if let Some(build) = current
+            .as_ref()
+            .filter(|cache| cache.is_stale(&coverage))
+            .map(|cache| cache.build_number)
+        {
+            let kept = kept_members_path(app_dir, build);
+            if kept.exists() {
+                let event = SdeEvent::ImportRequested(kept);
+                match drive_lifecycle(app_dir, service, current, &coverage, event, &progress).await
+                {
+                    Ok((status, true)) => return Ok((status, true)),
+                    Ok(_) => warn!("Rebuilding from the kept SDE members was rejected"),
+                    Err(err) => warn!("Rebuilding from the kept SDE members failed: {}", err),
+                }
+                current = load_index_cache_async(app_dir).await?;
+            }
+        }
+
+        // A cache from an older builder or with outdated coverage is rebuilt
+        // from whatever build the sources have, due or not.
+        let first_event = if current
+            .as_ref()
+            .is_some_and(|cache| cache.is_stale(&coverage))
+        {
+            SdeEvent::RebuildRequested {
+                rejected: load_rejected_build(app_dir, &coverage),
+            }
+        } else if force {
+            SdeEvent::CheckRequested
+        } else {
+            let schedule = load_sde_schedule_or_default(app_dir);
+            SdeEvent::CheckDue {
+                since_last_check: since_last_check(&schedule),
+                interval: check_interval(&schedule),
+            }
+        };
+        drive_lifecycle(app_dir, service, current, &coverage, first_event, &progress).await
+    }
+    .await;
+
+    finish_update(
+        app_dir,
+        &progress,
+        result.as_ref().map(|(status, _)| status),
+    );
+    result
+}
+
+/// Run scheduled checks for the app's lifetime: one whenever the check
+/// interval has passed since a source last answered, and after a failure
+/// again with jittered backoff. `on_installed` gets the status after every
+/// check that installed a new build.
+pub async fn run_sde_scheduler(
+    app_dir: &Path,
+    service: &SdeService,
+    listener: ProgressListener,
+    on_installed: impl Fn(&SdeStatus),
+) {
+    let mut failures = 0;
+    loop {
+        let delay = match failures {
+            0 => next_check_delay(&load_sde_schedule_or_default(app_dir)),
+            _ => check_backoff(failures, jitter()),
+        };
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = service.schedule_changed.notified() => continue,
+        }
+
+        let result = update_sde_index(app_dir, service, listener.clone(), false).await;
+        // Still due afterwards means no source answered, or the update
+        // failed before the check could be recorded.
+        if next_check_delay(&load_sde_schedule_or_default(app_dir)).is_zero() {
+            failures += 1;
+        } else {
+            failures = 0;
+        }
+        match result {
+            Ok((status, true)) => on_installed(&status),
+            Ok(_) => {}
+            Err(err) => warn!("Scheduled SDE check failed: {}", err),
+        }
+    }
+}
+
+/// Uniform in [0, 1), from the std hasher's randomly seeded keys.
+fn jitter() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    let bits = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Build the index from a user-supplied SDE archive (the JSONL zip), for
+/// networks that can't reach any SDE source.
+pub async fn import_sde_archive(
+    app_dir: &Path,
+    service: &SdeService,
+    archive_path: &Path,
+    listener: ProgressListener,
+) -> Result<SdeStatus, String> {
+    let _update_guard = service.update_guard.lock().await;
+    let progress = service.reporter(listener);
+
+    let result = async {
+        let current = load_index_cache_async(app_dir).await?;
+        let coverage = load_coverage(app_dir)?;
+        let event = SdeEvent::ImportRequested(archive_path.to_path_buf());
+        drive_lifecycle(app_dir, service, current, &coverage, event, &progress).await
+    }
+    .await;
+
+    finish_update(
+        app_dir,
+        &progress,
+        result.as_ref().map(|(status, _)| status),
+    );
+    result.map(|(status, _)| status)
+}
 
-    let mut current = load_index_cache_async(app_dir).await?;
+/// Drives the `domain::sde_lifecycle` machine: seed the phase from the
+/// on-disk cache, feed `first_event`, execute each returned effect and
+/// feed its outcome back in until the machine settles. Also tells whether
+/// a new index was installed.
+async fn drive_lifecycle(
+    app_dir: &Path,
+    service: &SdeService,
+    mut current: Option<SdeIndexCache>,
+    coverage: &SdeCoverage,
+    first_event: SdeEvent,
+    progress: &ProgressReporter,
+) -> Result<(SdeStatus, bool), String> {
+    let sources = load_sde_sources(app_dir)?;
 
     // The phase is call-local, seeded from the cache file: disk is the
     // source of truth between calls (matches the previous stateless
@@ -104,29 +798,101 @@ pub async fn ensure_sde_index(app_dir: &Path, service: &SdeService) -> Result<Sd
     };
 
     let mut latest_build_number: Option<i64> = None;
+    let mut rejected: Option<String> = None;
+    let mut last_download_error: Option<String> = None;
+    // Every source failed the download, and the cache kept serving.
+    let mut download_failed: Option<String> = None;
+    let mut installed = false;
 
-    let (next, effects) = step(phase, SdeEvent::CheckRequested);
+    let (next, effects) = step(phase, first_event);
     phase = next;
     let mut pending: VecDeque<SdeEffect> = effects.into();
 
     while let Some(effect) = pending.pop_front() {
         let event = match effect {
-            SdeEffect::FetchRemoteBuild => match fetch_latest_build_number().await {
-                Ok(latest) => {
-                    latest_build_number = Some(latest);
-                    SdeEvent::RemoteBuild(latest)
+            SdeEffect::FetchRemoteBuild(source) => {
+                progress.stage(SdeUpdateStage::Checking);
+                match sources.get(source) {
+                    Some(url) => match fetch_latest_build_number(url).await {
+                        Ok(latest) => {
+                            latest_build_number = Some(latest);
+                            SdeEvent::RemoteBuild(latest)
+                        }
+                        Err(err) => {
+                            warn!("SDE source {} unavailable: {}", url, err);
+                            SdeEvent::SourceUnavailable
+                        }
+                    },
+                    // Every source tried: the machine decides whether the
+                    // cached index is enough or this is a hard failure.
+                    None => SdeEvent::UpToDate,
                 }
-                // Remote build undeterminable: the machine decides whether
-                // the cached index is enough or this is a hard failure.
-                Err(_) => SdeEvent::UpToDate,
-            },
-            SdeEffect::StartUpdate(target) => {
-                match build_index_from_remote(app_dir, target).await {
-                    Ok(()) => SdeEvent::UpdateFinished(target),
-                    Err(err) => SdeEvent::UpdateFailed(err),
+            }
+            // Every source failed the download.
+            SdeEffect::StartUpdate { source, .. } if source >= sources.len() => {
+                let error = last_download_error
+                    .take()
+                    .unwrap_or_else(|| "No SDE source is configured".to_string());
+                download_failed = Some(error.clone());
+                SdeEvent::DownloadsExhausted(error)
+            }
+            SdeEffect::StartUpdate { target, source } => {
+                let url = &sources[source];
+                progress.update(|progress| {
+                    *progress = SdeProgress {
+                        stage: SdeUpdateStage::Downloading,
+                        target_build: Some(target),
+                        ..SdeProgress::default()
+                    }
+                });
+                match build_index_from_remote(app_dir, url, target, coverage, progress).await {
+                    Ok(build) => SdeEvent::UpdateFinished(build),
+                    Err(UpdateFailure::Download(err)) => {
+                        warn!("SDE download from {} failed: {}", url, err);
+                        last_download_error = Some(err.clone());
+                        SdeEvent::DownloadFailed(err)
+                    }
+                    Err(UpdateFailure::Build(err)) => SdeEvent::UpdateFailed(err),
+                    Err(UpdateFailure::Invalid(err)) => {
+                        warn!("Rejected SDE build {}: {}", target, err);
+                        if let Err(err) = save_rejected_build(app_dir, Some(target), coverage) {
+                            warn!("Failed to record rejected SDE build: {}", err);
+                        }
+                        rejected = Some(err.clone());
+                        SdeEvent::ValidationFailed(err)
+                    }
+                }
+            }
+            SdeEffect::ImportArchive(archive_path) => {
+                let dir = app_dir.to_path_buf();
+                let coverage = coverage.clone();
+                let progress = progress.clone();
+                let imported = tokio::task::spawn_blocking(move || {
+                    build_index_from_archive(&dir, &archive_path, coverage, &progress)
+                })
+                .await
+                .map_err(|err| err.to_string())?;
+                match imported {
+                    Ok(build) => SdeEvent::UpdateFinished(build),
+                    Err(UpdateFailure::Invalid(err)) => {
+                        warn!("Rejected imported SDE archive: {}", err);
+                        rejected = Some(err.clone());
+                        SdeEvent::ValidationFailed(err)
+                    }
+                    Err(UpdateFailure::Download(err) | UpdateFailure::Build(err)) => {
+                        SdeEvent::UpdateFailed(err)
+                    }
                 }
             }
+            SdeEffect::Wait(delay) => {
+                tokio::time::sleep(delay).await;
+                continue;
+            }
             SdeEffect::InvalidateIndex => {
+                installed = true;
+                if let Err(err) = save_rejected_build(app_dir, None, coverage) {
+                    warn!("Failed to clear rejected SDE build: {}", err);
+                }
                 service.invalidate().await;
                 // Reload only after the build actually rewrote the cache file.
                 current = load_index_cache_async(app_dir).await?;
@@ -143,23 +909,51 @@ pub async fn ensure_sde_index(app_dir: &Path, service: &SdeService) -> Result<Sd
         return Err(error);
     }
 
-    Ok(make_status(current.as_ref(), latest_build_number))
+    // Only a source that answered, and an update (if one was needed) that
+    // finished or was rejected, count as a check: anything else stays due.
+    if let Some(latest) = latest_build_number.filter(|_| download_failed.is_none()) {
+        if let Err(err) = record_sde_check(app_dir, latest) {
+            warn!("Failed to record SDE check time: {}", err);
+        }
+    }
+
+    let status = make_status(
+        current.as_ref(),
+        latest_build_number,
+        None,
+        rejected.or(download_failed),
+    );
+    Ok((status, installed))
 }
 
-pub async fn get_sde_status(app_dir: &Path) -> Result<SdeStatus, String> {
+/// The cached build, the latest remote one as of the last check, and any
+/// update in flight or failure since the last successful check. Doesn't
+/// contact the sources; that's the scheduler's job.
+pub async fn get_sde_status(app_dir: &Path, service: &SdeService) -> Result<SdeStatus, String> {
     let current = load_index_cache_async(app_dir).await?;
-    let latest_build_number = fetch_latest_build_number().await.ok();
-
-    Ok(make_status(current.as_ref(), latest_build_number))
+    let schedule = load_sde_schedule_or_default(app_dir);
+
+    Ok(make_status(
+        current.as_ref(),
+        schedule.latest_build_number,
+        service.progress(),
+        load_last_error(app_dir),
+    ))
 }
 
-fn make_status(cache: Option<&SdeIndexCache>, latest_build_number: Option<i64>) -> SdeStatus {
+fn make_status(
+    cache: Option<&SdeIndexCache>,
+    latest_build_number: Option<i64>,
+    progress: Option<SdeProgress>,
+    last_error: Option<String>,
+) -> SdeStatus {
     SdeStatus {
         build_number: cache.map(|cache| cache.build_number),
         latest_build_number,
         ready: cache.is_some(),
-        updating: false,
-        last_error: None,
+        updating: progress.is_some(),
+        last_error,
+        progress,
     }
 }
 
@@ -167,10 +961,10 @@ fn index_path(app_dir: &Path) -> PathBuf {
     app_dir.join(INDEX_FILE)
 }
 
-async fn fetch_latest_build_number() -> Result<i64, String> {
+async fn fetch_latest_build_number(url: &str) -> Result<i64, String> {
     let client = crate::api::create_client()?;
     let response = client
-        .head(SDE_URL)
+        .head(url)
         .send()
         .await
         .map_err(|err| err.to_string())?;
@@ -188,31 +982,81 @@ async fn fetch_latest_build_number() -> Result<i64, String> {
         .ok_or_else(|| "Unable to determine latest SDE build number".to_string())
 }
 
-async fn build_index_from_remote(app_dir: &Path, expected_build: i64) -> Result<(), String> {
+/// Why an update attempt failed: download problems are retried by the
+/// lifecycle, anything after a verified download is final, and an index
+/// that fails validation leaves the previous one in place.
+enum UpdateFailure {
+    Download(String),
+    Build(String),
+    Invalid(String),
+}
+
+/// Download, verify and index the archive at `url`, returning the build
+/// it turned out to hold.
+async fn build_index_from_remote(
+    app_dir: &Path,
+    url: &str,
+    expected_build: i64,
+    coverage: &SdeCoverage,
+    progress: &ProgressReporter,
+) -> Result<i64, UpdateFailure> {
     let temp_path = app_dir.join(format!("sde-{}.zip.download", expected_build));
     let final_path = app_dir.join(format!("sde-{}.zip", expected_build));
 
-    if let Err(err) = download_sde_zip(&temp_path).await {
-        // Don't leave partial downloads behind in app-data.
+    remove_stale_downloads(app_dir, &temp_path).await;
+
+    // Only the members the index needs when the server allows it. A
+    // broken-off full download stays on disk for the retry to resume.
+    match fetch_sde_members(url, &temp_path, progress).await {
+        Ok(()) => {}
+        Err(PartialFetchError::Unsupported(reason)) => {
+            info!("Downloading the whole SDE archive: {}", reason);
+            download_sde_zip(url, &temp_path, progress)
+                .await
+                .map_err(UpdateFailure::Download)?;
+        }
+        Err(PartialFetchError::Failed(err)) => return Err(UpdateFailure::Download(err)),
+    }
+
+    // A file without a readable directory can only be fetched again from
+    // scratch. Member CRCs are checked as the build reads them; a corrupt
+    // one fails the build, which deletes the archive all the same.
+    progress.stage(SdeUpdateStage::Verifying);
+    let archive_path = temp_path.clone();
+    let verified = tokio::task::spawn_blocking(move || verify_sde_archive(&archive_path))
+        .await
+        .map_err(|err| UpdateFailure::Build(err.to_string()))?;
+    let _ = tokio::fs::remove_file(etag_path(&temp_path)).await;
+    if let Err(err) = verified {
         let _ = tokio::fs::remove_file(&temp_path).await;
-        return Err(err);
+        return Err(UpdateFailure::Download(format!(
+            "SDE archive failed verification: {}",
+            err
+        )));
     }
 
     if let Err(err) = tokio::fs::rename(&temp_path, &final_path).await {
         let _ = tokio::fs::remove_file(&temp_path).await;
-        return Err(err.to_string());
+        return Err(UpdateFailure::Build(err.to_string()));
     }
 
     // Decoding a multi-hundred-MB zip and parsing tens of thousands of JSONL
     // lines is blocking CPU/IO work — keep it off the async runtime.
     let dir = app_dir.to_path_buf();
     let zip_path = final_path.clone();
-    let build_result = tokio::task::spawn_blocking(move || -> Result<(), String> {
-        let cache = build_index_cache_from_zip(&zip_path, expected_build)?;
-        save_index_cache(&dir, &cache)
+    let coverage = coverage.clone();
+    let progress = progress.clone();
+    let build_result = tokio::task::spawn_blocking(move || {
+        let cache =
+            build_index_cache_from_zip(&zip_path, Some(expected_build), coverage, &progress)
+                .map_err(UpdateFailure::Build)?;
+        cache.validate().map_err(UpdateFailure::Invalid)?;
+        save_index_cache(&dir, &cache).map_err(UpdateFailure::Build)?;
+        keep_sde_members(&dir, &zip_path, cache.build_number);
+        Ok(cache.build_number)
     })
     .await
-    .map_err(|err| err.to_string())?;
+    .map_err(|err| UpdateFailure::Build(err.to_string()))?;
 
     if let Err(err) = tokio::fs::remove_file(&final_path).await {
         warn!("Failed to remove SDE archive {:?}: {}", final_path, err);
@@ -221,208 +1065,1767 @@ async fn build_index_from_remote(app_dir: &Path, expected_build: i64) -> Result<
     build_result
 }
 
-async fn download_sde_zip(temp_path: &Path) -> Result<(), String> {
-    let client = crate::api::create_client()?;
-    let mut response = client
-        .get(SDE_URL)
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
-
-    let mut file = tokio::fs::File::create(temp_path)
-        .await
-        .map_err(|err| err.to_string())?;
-
-    while let Some(chunk) = response.chunk().await.map_err(|err| err.to_string())? {
-        file.write_all(&chunk)
-            .await
-            .map_err(|err| err.to_string())?;
+/// Partial downloads of other builds can't be resumed into this one.
+async fn remove_stale_downloads(app_dir: &Path, keep: &Path) {
+    let Ok(mut entries) = tokio::fs::read_dir(app_dir).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let is_download = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| {
+                name.starts_with("sde-")
+                    && (name.ends_with(".zip.download") || name.ends_with(".zip.download.etag"))
+            });
+        if is_download && path != keep && path != etag_path(keep) {
+            if let Err(err) = tokio::fs::remove_file(&path).await {
+                warn!("Failed to remove stale SDE download {:?}: {}", path, err);
+            }
+        }
     }
-    file.flush().await.map_err(|err| err.to_string())
 }
 
-fn build_index_cache_from_zip(
-    zip_path: &Path,
-    fallback_build: i64,
-) -> Result<SdeIndexCache, String> {
-    let file = File::open(zip_path).map_err(|err| err.to_string())?;
-    let mut archive = ZipArchive::new(file).map_err(|err| err.to_string())?;
-
-    let build_number = read_sde_build_number(&mut archive)?.unwrap_or(fallback_build);
-    let categories = read_categories(&mut archive)?;
-    let groups = read_groups(&mut archive)?;
-    let entries = read_type_entries(&mut archive, &categories, &groups)?;
-
-    Ok(SdeIndexCache {
-        build_number,
-        generated_at: Utc::now().to_rfc3339(),
-        entries,
-    })
+enum PartialFetchError {
+    /// The server or the archive can't serve single members; download the
+    /// whole archive instead.
+    Unsupported(String),
+    /// A transfer failed; retried like any download.
+    Failed(String),
 }
 
-fn read_sde_build_number(archive: &mut ZipArchive<File>) -> Result<Option<i64>, String> {
-    let mut file = archive
-        .by_name("_sde.jsonl")
-        .map_err(|err| err.to_string())?;
-    let reader = BufReader::new(&mut file);
-
-    for line in reader.lines() {
-        let line = line.map_err(|err| err.to_string())?;
-        if line.trim().is_empty() {
-            continue;
-        }
-
-        let value: Value = serde_json::from_str(&line).map_err(|err| err.to_string())?;
-        if let Some(build) = value.get("buildNumber").and_then(Value::as_i64) {
-            return Ok(Some(build));
+/// Fetch only `SDE_MEMBERS` with Range requests, reading the central
+/// directory from the archive's tail, and write them to `temp_path` as a
+/// standalone archive.
+async fn fetch_sde_members(
+    url: &str,
+    temp_path: &Path,
+    progress: &ProgressReporter,
+) -> Result<(), PartialFetchError> {
+    use PartialFetchError::{Failed, Unsupported};
+
+    let client = crate::api::create_client().map_err(Failed)?;
+    let mut etag = None;
+
+    let (tail, archive_len) = fetch_range(
+        &client,
+        url,
+        format!("bytes=-{}", MAX_TAIL_LEN),
+        &mut etag,
+        None,
+    )
+    .await?;
+    let tail_start = archive_len.saturating_sub(tail.len() as u64);
+    let location = locate_central_directory(&tail).map_err(Unsupported)?;
+
+    let directory = if location.offset >= tail_start {
+        let start = (location.offset - tail_start) as usize;
+        tail.get(start..start + location.size as usize)
+            .ok_or_else(|| Failed("SDE archive directory is out of bounds".to_string()))?
+            .to_vec()
+    } else {
+        let end = location.offset + location.size;
+        let range = format!("bytes={}-{}", location.offset, end.saturating_sub(1));
+        fetch_range(&client, url, range, &mut etag, None).await?.0
+    };
+    let members = parse_central_directory(&directory).map_err(Unsupported)?;
+    let spans = member_spans(&members, location.offset, &SDE_MEMBERS).map_err(Unsupported)?;
+
+    let total: u64 = spans.iter().map(|(_, span)| span.end - span.start).sum();
+    progress.update(|progress| {
+        progress.bytes_downloaded = 0;
+        progress.bytes_total = Some(total);
+    });
+
+    let mut parts: Vec<(&ZipMember, Vec<u8>)> = Vec::with_capacity(spans.len());
+    for (index, span) in spans {
+        let range = format!("bytes={}-{}", span.start, span.end - 1);
+        let (bytes, _) = fetch_range(&client, url, range, &mut etag, Some(progress)).await?;
+        if bytes.len() as u64 != span.end - span.start {
+            return Err(Failed(format!(
+                "SDE member {} broke off",
+                members[index].name
+            )));
         }
+        parts.push((&members[index], bytes));
     }
 
-    Ok(None)
+    let archive = assemble_archive(&parts).map_err(Failed)?;
+    tokio::fs::write(temp_path, archive)
+        .await
+        .map_err(|err| Failed(err.to_string()))
 }
 
-fn read_categories(archive: &mut ZipArchive<File>) -> Result<HashMap<i64, String>, String> {
-    let mut file = archive
-        .by_name("categories.jsonl")
-        .map_err(|err| err.to_string())?;
-    let reader = BufReader::new(&mut file);
-    let included: HashSet<i64> = INCLUDED_CATEGORY_IDS.into_iter().collect();
-    let mut categories = HashMap::new();
+/// One Range request, returning the body and the archive's total length.
+/// An ETag that changes between requests means the archive was replaced
+/// mid-fetch. With `progress`, the body counts towards `bytes_downloaded`
+/// as it arrives.
+async fn fetch_range(
+    client: &reqwest::Client,
+    url: &str,
+    range: String,
+    etag: &mut Option<String>,
+    progress: Option<&ProgressReporter>,
+) -> Result<(Vec<u8>, u64), PartialFetchError> {
+    use reqwest::header::{CONTENT_RANGE, ETAG, RANGE};
+    use reqwest::StatusCode;
+    use PartialFetchError::{Failed, Unsupported};
 
-    for line in reader.lines() {
-        let line = line.map_err(|err| err.to_string())?;
-        if line.trim().is_empty() {
-            continue;
-        }
+    let mut response = client
+        .get(url)
+        .header(RANGE, range)
+        .send()
+        .await
+        .map_err(|err| Failed(err.to_string()))?;
+
+    let status = response.status();
+    if status != StatusCode::PARTIAL_CONTENT {
+        // Dropping the response abandons a full-archive body unread.
+        return Err(
+            if status.is_success() || status == StatusCode::RANGE_NOT_SATISFIABLE {
+                Unsupported("server doesn't support range requests".to_string())
+            } else {
+                Failed(format!("SDE server returned {}", status))
+            },
+        );
+    }
 
-        let value: Value = serde_json::from_str(&line).map_err(|err| err.to_string())?;
-        let key = value
-            .get("_key")
-            .and_then(Value::as_i64)
-            .unwrap_or_default();
-        if !included.contains(&key) {
-            continue;
-        }
+    let total = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range)
+        .and_then(|range| range.total)
+        .ok_or_else(|| Unsupported("server doesn't report the archive length".to_string()))?;
 
-        if let Some(name) = value
-            .get("name")
-            .and_then(|name| name.get("en"))
-            .and_then(Value::as_str)
-        {
-            categories.insert(key, name.to_string());
+    let current = response
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    match (etag.as_ref(), current) {
+        (Some(previous), Some(current)) if *previous != current => {
+            return Err(Failed(
+                "SDE archive changed during the download".to_string(),
+            ));
         }
+        (None, current) => *etag = current,
+        _ => {}
     }
 
-    Ok(categories)
+    let mut body = Vec::new();
+    let mut reported = 0;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|err| Failed(err.to_string()))?
+    {
+        body.extend_from_slice(&chunk);
+        let unreported = (body.len() - reported) as u64;
+        if let Some(progress) = progress.filter(|_| unreported >= PROGRESS_BYTES_STEP) {
+            reported = body.len();
+            progress.update(|progress| progress.bytes_downloaded += unreported);
+        }
+    }
+    let unreported = (body.len() - reported) as u64;
+    if let Some(progress) = progress.filter(|_| unreported > 0) {
+        progress.update(|progress| progress.bytes_downloaded += unreported);
+    }
+    Ok((body, total))
 }
 
-fn read_groups(archive: &mut ZipArchive<File>) -> Result<HashMap<i64, (i64, String)>, String> {
-    let mut file = archive
-        .by_name("groups.jsonl")
-        .map_err(|err| err.to_string())?;
-    let reader = BufReader::new(&mut file);
-    let included: HashSet<i64> = INCLUDED_CATEGORY_IDS.into_iter().collect();
-    let mut groups = HashMap::new();
-
-    for line in reader.lines() {
-        let line = line.map_err(|err| err.to_string())?;
-        if line.trim().is_empty() {
-            continue;
-        }
+/// Where the ETag of a partial download is kept, for resuming it.
+fn etag_path(temp_path: &Path) -> PathBuf {
+    temp_path.with_extension("download.etag")
+}
 
-        let value: Value = serde_json::from_str(&line).map_err(|err| err.to_string())?;
-        let key = value
-            .get("_key")
-            .and_then(Value::as_i64)
-            .unwrap_or_default();
-        let category_id = value
-            .get("categoryID")
-            .and_then(Value::as_i64)
-            .unwrap_or_default();
-        if !included.contains(&category_id) {
-            continue;
+/// Remove a partial download and its ETag, so the next attempt fetches the
+/// archive whole.
+async fn discard_partial_download(temp_path: &Path) {
+    for path in [temp_path.to_path_buf(), etag_path(temp_path)] {
+        match tokio::fs::remove_file(&path).await {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                warn!("Failed to remove partial SDE download {:?}: {}", path, err);
+            }
+            _ => {}
         }
+    }
+}
 
-        if let Some(name) = value
-            .get("name")
-            .and_then(|name| name.get("en"))
-            .and_then(Value::as_str)
+/// Download the archive into `temp_path`, resuming a partial file with an
+/// HTTP Range request conditional on the ETag it was started with (a
+/// changed archive is sent whole instead). Fails unless the server says
+/// how long the archive is and the file ends up exactly that long.
+async fn download_sde_zip(
+    url: &str,
+    temp_path: &Path,
+    progress: &ProgressReporter,
+) -> Result<(), String> {
+    use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE, RANGE};
+    use reqwest::StatusCode;
+
+    let resume_from = tokio::fs::metadata(temp_path)
+        .await
+        .map(|metadata| metadata.len())
+        .unwrap_or(0);
+    // Without a strong ETag there's no telling whether the partial file
+    // still matches the archive, so it's fetched again whole.
+    let resume_etag = match resume_from {
+        0 => None,
+        _ => tokio::fs::read_to_string(etag_path(temp_path)).await.ok(),
+    };
+
+    let client = crate::api::create_client()?;
+    let mut request = client.get(url);
+    if let Some(etag) = &resume_etag {
+        request = request
+            .header(RANGE, format!("bytes={}-", resume_from))
+            .header(IF_RANGE, etag.as_str());
+    }
+    let response = request.send().await.map_err(|err| err.to_string())?;
+
+    let content_range = response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range);
+
+    if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        // Nothing left to fetch when the partial file is already whole;
+        // otherwise it doesn't belong to this archive.
+        if content_range.and_then(|range| range.total) == Some(resume_from) {
+            return Ok(());
+        }
+        discard_partial_download(temp_path).await;
+        return Err("Partial SDE download doesn't match the archive".to_string());
+    }
+    let mut response = response.error_for_status().map_err(|err| err.to_string())?;
+
+    let (mut file, expected_len) = match content_range {
+        Some(range)
+            if response.status() == StatusCode::PARTIAL_CONTENT
+                && range.start == Some(resume_from) =>
         {
-            groups.insert(key, (category_id, name.to_string()));
+            // Without the total the resumed file can't be checked; start
+            // over so the next attempt isn't refused the same way.
+            if range.total.is_none() {
+                discard_partial_download(temp_path).await;
+                return Err("SDE server didn't say how long the archive is".to_string());
+            }
+            let file = tokio::fs::OpenOptions::new()
+                .append(true)
+                .open(temp_path)
+                .await
+                .map_err(|err| err.to_string())?;
+            (file, range.total)
+        }
+        _ if response.status() == StatusCode::PARTIAL_CONTENT => {
+            discard_partial_download(temp_path).await;
+            return Err("SDE server resumed the download at the wrong offset".to_string());
+        }
+        // A full response: the archive changed, the server ignored the
+        // range, or there was nothing to resume.
+        _ => {
+            let etag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|value| value.to_str().ok())
+                .filter(|etag| !etag.starts_with("W/"));
+            let saved = match etag {
+                Some(etag) => tokio::fs::write(etag_path(temp_path), etag).await,
+                None => match tokio::fs::remove_file(etag_path(temp_path)).await {
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                    result => result,
+                },
+            };
+            saved.map_err(|err| err.to_string())?;
+            let file = tokio::fs::File::create(temp_path)
+                .await
+                .map_err(|err| err.to_string())?;
+            (file, response.content_length())
+        }
+    };
+    let expected_len =
+        expected_len.ok_or_else(|| "SDE server didn't say how long the archive is".to_string())?;
+
+    let mut downloaded = file.metadata().await.map_err(|err| err.to_string())?.len();
+    let mut reported = downloaded;
+    progress.update(|progress| {
+        progress.bytes_downloaded = downloaded;
+        progress.bytes_total = Some(expected_len);
+    });
+
+    while let Some(chunk) = response.chunk().await.map_err(|err| err.to_string())? {
+        file.write_all(&chunk)
+            .await
+            .map_err(|err| err.to_string())?;
+        downloaded += chunk.len() as u64;
+        if downloaded - reported >= PROGRESS_BYTES_STEP {
+            reported = downloaded;
+            progress.update(|progress| progress.bytes_downloaded = downloaded);
         }
     }
+    file.flush().await.map_err(|err| err.to_string())?;
+    progress.update(|progress| progress.bytes_downloaded = downloaded);
 
-    Ok(groups)
+    let len = tokio::fs::metadata(temp_path)
+        .await
+        .map_err(|err| err.to_string())?
+        .len();
+    match len.cmp(&expected_len) {
+        std::cmp::Ordering::Less => Err(format!(
+            "SDE download broke off at {} of {} bytes",
+            len, expected_len
+        )),
+        std::cmp::Ordering::Greater => {
+            let _ = tokio::fs::remove_file(temp_path).await;
+            Err(format!(
+                "SDE download is {} bytes, expected {}",
+                len, expected_len
+            ))
+        }
+        std::cmp::Ordering::Equal => Ok(()),
+    }
 }
 
-fn read_type_entries(
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ContentRange {
+    /// `None` for the "bytes */total" form sent with a 416.
+    start: Option<u64>,
+    total: Option<u64>,
+}
+
+/// "bytes 100-199/200", "bytes 100-199/*" or "bytes */200".
+fn parse_content_range(value: &str) -> Option<ContentRange> {
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    let start = match range {
+        "*" => None,
+        range => Some(range.split_once('-')?.0.parse().ok()?),
+    };
+    Some(ContentRange { start, total })
+}
+
+/// Read the archive's central directory (a truncated file has none) and
+/// check it lists every member the index is built from. Nothing is
+/// decompressed here: the zip reader checks each member's CRC-32 as the
+/// build streams it.
+fn verify_sde_archive(zip_path: &Path) -> Result<(), String> {
+    let file = File::open(zip_path).map_err(|err| err.to_string())?;
+    let archive = ZipArchive::new(file).map_err(|err| err.to_string())?;
+    // The build number can also come from the file name.
+    let missing: Vec<&str> = SDE_MEMBERS
+        .into_iter()
+        .filter(|name| *name != "_sde.jsonl" && archive.index_for_name(name).is_none())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("SDE archive lacks {}", missing.join(", ")));
+    }
+    Ok(())
+}
+
+/// Verify and index a local archive, returning its build number (from
+/// `_sde.jsonl`, else the file name; an archive naming neither is
+/// rejected). The archive itself is left where the user keeps it.
+fn build_index_from_archive(
+    app_dir: &Path,
+    archive_path: &Path,
+    coverage: SdeCoverage,
+    progress: &ProgressReporter,
+) -> Result<i64, UpdateFailure> {
+    progress.stage(SdeUpdateStage::Verifying);
+    verify_sde_archive(archive_path).map_err(UpdateFailure::Build)?;
+    let fallback_build = archive_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(extract_build_from_url);
+    let cache = build_index_cache_from_zip(archive_path, fallback_build, coverage, progress)
+        .map_err(UpdateFailure::Build)?;
+    cache.validate().map_err(UpdateFailure::Invalid)?;
+    save_index_cache(app_dir, &cache).map_err(UpdateFailure::Build)?;
+    if archive_path != kept_members_path(app_dir, cache.build_number) {
+        keep_sde_members(app_dir, archive_path, cache.build_number);
+    }
+    Ok(cache.build_number)
+}
+
+fn kept_members_path(app_dir: &Path, build: i64) -> PathBuf {
+    app_dir.join(format!("{}{}.zip", KEPT_MEMBERS_PREFIX, build))
+}
+
+/// Copy the members the index is built from out of `archive_path`, still
+/// compressed, as the kept archive for `build`, replacing any other build's.
+/// Only logged on failure: the next coverage change then downloads again.
+fn keep_sde_members(app_dir: &Path, archive_path: &Path, build: i64) {
+    let path = kept_members_path(app_dir, build);
+    let temp_path = path.with_extension("zip.tmp");
+    let copied = (|| -> Result<(), String> {
+        let file = File::open(archive_path).map_err(|err| err.to_string())?;
+        let mut archive = ZipArchive::new(file).map_err(|err| err.to_string())?;
+        let output = File::create(&temp_path).map_err(|err| err.to_string())?;
+        let mut writer = zip::ZipWriter::new(output);
+        for name in SDE_MEMBERS {
+            if let Ok(member) = archive.by_name(name) {
+                writer
+                    .raw_copy_file(member)
+                    .map_err(|err| err.to_string())?;
+            }
+        }
+        writer.finish().map_err(|err| err.to_string())?;
+        std::fs::rename(&temp_path, &path).map_err(|err| err.to_string())
+    })();
+    if let Err(err) = copied {
+        let _ = std::fs::remove_file(&temp_path);
+        warn!("Failed to keep the SDE archive members: {}", err);
+        return;
+    }
+
+    let Ok(entries) = std::fs::read_dir(app_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let other = entry.path();
+        let is_kept = other
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(KEPT_MEMBERS_PREFIX) && name.ends_with(".zip"));
+        if is_kept && other != path {
+            if let Err(err) = std::fs::remove_file(&other) {
+                warn!("Failed to remove old SDE members {:?}: {}", other, err);
+            }
+        }
+    }
+}
+
+/// Build the index from an archive without saving it, returning the rows
+/// indexed. Entry point for `benches/sde_build.rs`.
+pub fn build_index_for_bench(zip_path: &Path) -> Result<usize, String> {
+    Ok(cache_rows(&build_cache_for_bench(zip_path)?))
+}
+
+/// Build the index from an archive and encode it as the cache file.
+/// Entry point for `benches/sde_build.rs`.
+pub fn encode_index_for_bench(zip_path: &Path) -> Result<Vec<u8>, String> {
+    Ok(encode_index_cache(&build_cache_for_bench(zip_path)?))
+}
+
+/// Decode a cache file as loading it does, returning the rows loaded.
+/// Entry point for `benches/sde_build.rs`.
+pub fn decode_index_for_bench(bytes: &[u8]) -> Result<usize, String> {
+    let cache = decode_index_cache(bytes)?.ok_or("SDE index cache has an outdated layout")?;
+    Ok(cache_rows(&cache))
+}
+
+fn build_cache_for_bench(zip_path: &Path) -> Result<SdeIndexCache, String> {
+    let progress = ProgressReporter {
+        snapshot: Arc::default(),
+        listener: Arc::new(|_| {}),
+    };
+    build_index_cache_from_zip(zip_path, None, default_coverage(), &progress)
+}
+
+fn cache_rows(cache: &SdeIndexCache) -> usize {
+    cache.entries.len()
+        + cache.systems.len()
+        + cache.stargates.len()
+        + cache.ship_attributes.len()
+        + cache.hull_profiles.len()
+}
+
+/// The build number comes from the archive's `_sde.jsonl`, else
+/// `fallback_build`; with neither the archive is refused.
+fn build_index_cache_from_zip(
+    zip_path: &Path,
+    fallback_build: Option<i64>,
+    coverage: SdeCoverage,
+    progress: &ProgressReporter,
+) -> Result<SdeIndexCache, String> {
+    let file = File::open(zip_path).map_err(|err| err.to_string())?;
+    let mut archive = ZipArchive::new(file).map_err(|err| err.to_string())?;
+
+    let build_number = read_sde_build_number(&mut archive)?
+        .or(fallback_build)
+        .ok_or_else(|| "SDE archive doesn't say which build it is".to_string())?;
+    progress.update(|progress| {
+        progress.stage = SdeUpdateStage::Building;
+        progress.target_build = Some(build_number);
+        progress.rows_indexed = 0;
+    });
+    let categories = read_categories(&mut archive)?;
+    let groups = read_groups(&mut archive, &coverage)?;
+    // Readers count rows per chunk as they parse; after each one the
+    // count settles on what was actually kept.
+    let mut rows = 0;
+    let mut settle = |kept: usize| {
+        rows += kept;
+        progress.update(|progress| progress.rows_indexed = rows);
+    };
+    let (entries, groupings) = read_type_entries(&mut archive, &categories, &groups, progress)?;
+    settle(entries.len());
+    let ship_attributes = read_ship_attributes(&mut archive, &entries, progress)?;
+    settle(ship_attributes.len());
+    let hull_profiles = read_hull_profiles(&mut archive, &entries, &groupings, &ship_attributes)?;
+    settle(hull_profiles.len());
+    let systems = read_solar_systems(&mut archive, progress)?;
+    settle(systems.len());
+    let stargates = read_stargates(&mut archive, progress)?;
+    settle(stargates.len());
+
+    Ok(SdeIndexCache {
+        format_version: INDEX_FORMAT_VERSION,
+        build_number,
+        generated_at: Utc::now().to_rfc3339(),
+        coverage,
+        entries,
+        systems,
+        stargates,
+        ship_attributes,
+        hull_profiles,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// JSONL readers
+// ---------------------------------------------------------------------------
+//
+// Each member is decompressed as a stream on the calling thread and handed
+// out in chunks of whole lines to one parser thread per core, which
+// deserialize straight into the typed records below (fields the index
+// doesn't use are skipped, not materialized).
+
+/// Bytes of lines handed to a parser thread at once: big enough to amortize
+/// the hand-off, small enough that only a few are in memory.
+const CHUNK_BYTES: usize = 1 << 20;
+
+#[derive(Deserialize)]
+struct SdeRecord {
+    #[serde(rename = "buildNumber")]
+    build_number: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct CategoryRecord {
+    #[serde(rename = "_key", default)]
+    key: i64,
+    #[serde(default, deserialize_with = "localized_names")]
+    name: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct GroupRecord {
+    #[serde(rename = "_key", default)]
+    key: i64,
+    #[serde(rename = "categoryID", default)]
+    category_id: i64,
+    #[serde(default, deserialize_with = "localized_names")]
+    name: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct TypeRecord {
+    #[serde(rename = "_key", default)]
+    key: i64,
+    #[serde(rename = "groupID", default)]
+    group_id: i64,
+    #[serde(default, deserialize_with = "localized_names")]
+    name: BTreeMap<String, String>,
+    #[serde(rename = "metaGroupID")]
+    meta_group_id: Option<i64>,
+    #[serde(rename = "marketGroupID")]
+    market_group_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct MarketGroupRecord {
+    #[serde(rename = "_key", default)]
+    key: i64,
+    #[serde(default, deserialize_with = "localized_names")]
+    name: BTreeMap<String, String>,
+    #[serde(rename = "parentGroupID")]
+    parent_group_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct TypeDogmaRecord {
+    #[serde(rename = "_key", default)]
+    key: i64,
+    #[serde(rename = "dogmaAttributes", default)]
+    attributes: Vec<DogmaAttributeRecord>,
+}
+
+#[derive(Deserialize)]
+struct DogmaAttributeRecord {
+    #[serde(rename = "attributeID")]
+    attribute_id: i64,
+    value: f64,
+}
+
+#[derive(Deserialize)]
+struct TypeBonusRecord {
+    #[serde(rename = "_key", default)]
+    key: i64,
+    #[serde(rename = "roleBonuses", default)]
+    role_bonuses: Vec<BonusRecord>,
+    #[serde(rename = "miscBonuses", default)]
+    misc_bonuses: Vec<BonusRecord>,
+    /// Per-level bonuses, one entry per hull skill.
+    #[serde(default)]
+    types: Vec<SkillBonusesRecord>,
+}
+
+#[derive(Deserialize)]
+struct SkillBonusesRecord {
+    #[serde(rename = "_value", default)]
+    bonuses: Vec<BonusRecord>,
+}
+
+#[derive(Deserialize)]
+struct BonusRecord {
+    bonus: Option<f64>,
+    #[serde(rename = "bonusText", default, deserialize_with = "localized_names")]
+    text: BTreeMap<String, String>,
+    #[serde(rename = "unitID")]
+    unit_id: Option<i64>,
+}
+
+/// A region or constellation.
+#[derive(Deserialize)]
+struct MapAreaRecord {
+    #[serde(rename = "_key", default)]
+    key: i64,
+    #[serde(default, deserialize_with = "localized_names")]
+    name: BTreeMap<String, String>,
+    #[serde(rename = "regionID")]
+    region_id: Option<i64>,
+    #[serde(rename = "wormholeClassID")]
+    wormhole_class: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct SolarSystemRecord {
+    #[serde(rename = "_key", default)]
+    key: i64,
+    #[serde(rename = "constellationID", default)]
+    constellation_id: i64,
+    #[serde(default, deserialize_with = "localized_names")]
+    name: BTreeMap<String, String>,
+    #[serde(rename = "securityStatus", default)]
+    security_status: f64,
+    #[serde(rename = "wormholeClassID")]
+    wormhole_class: Option<i64>,
+    position: Option<PositionRecord>,
+}
+
+#[derive(Deserialize)]
+struct PositionRecord {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Deserialize)]
+struct StargateRecord {
+    #[serde(rename = "solarSystemID")]
+    solar_system_id: Option<i64>,
+    destination: Option<StargateDestination>,
+}
+
+#[derive(Deserialize)]
+struct StargateDestination {
+    #[serde(rename = "solarSystemID")]
+    solar_system_id: Option<i64>,
+}
+
+/// Every locale of an SDE `name` object, e.g. `{"en": "Rifter", "de": ...}`,
+/// skipping any that isn't a string.
+fn localized_names<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, String>, D::Error> {
+    let names = BTreeMap::<String, Value>::deserialize(deserializer)?;
+    Ok(names
+        .into_iter()
+        .filter_map(|(language, name)| match name {
+            Value::String(name) => Some((language, name)),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Deserialize every record of a JSONL member and keep what `keep` maps it
+/// to, in file order. Lines that don't deserialize are skipped and counted,
+/// with the first one's error logged, rather than failing the build. With
+/// `rows`, each parsed chunk's kept records count towards `rows_indexed`.
+fn read_records<R, U>(
     archive: &mut ZipArchive<File>,
-    categories: &HashMap<i64, String>,
-    groups: &HashMap<i64, (i64, String)>,
-) -> Result<Vec<ScanTypeIndexEntry>, String> {
-    let mut file = archive
-        .by_name("types.jsonl")
-        .map_err(|err| err.to_string())?;
-    let reader = BufReader::new(&mut file);
-    let mut entries = Vec::new();
+    file_name: &str,
+    rows: Option<&ProgressReporter>,
+    keep: impl Fn(R) -> Option<U> + Sync,
+) -> Result<Vec<U>, String>
+where
+    R: DeserializeOwned,
+    U: Send,
+{
+    let mut file = archive.by_name(file_name).map_err(|err| err.to_string())?;
+    let mut reader = BufReader::new(&mut file);
+    let workers = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    // Bounded, so a slow parser stalls decompression instead of letting the
+    // whole member pile up in memory.
+    let (sender, receiver) = std::sync::mpsc::sync_channel::<(usize, String)>(workers * 2);
+    // Each parser holds the receiver, so it's dropped once the last one
+    // exits (panicking or not) and the reader stops blocking on a full
+    // channel.
+    let receiver = Arc::new(std::sync::Mutex::new(receiver));
+    let keep = &keep;
+
+    std::thread::scope(|scope| {
+        let parsers: Vec<_> = (0..workers)
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                scope.spawn(move || {
+                    let mut parsed = Vec::new();
+                    loop {
+                        let next = receiver
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .recv();
+                        let Ok((first_line, chunk)) = next else {
+                            break;
+                        };
+                        let chunk = parse_chunk(file_name, first_line, &chunk, keep);
+                        if let Some(rows) = rows {
+                            let kept = chunk.records.len();
+                            rows.update(|progress| progress.rows_indexed += kept);
+                        }
+                        parsed.push(chunk);
+                    }
+                    parsed
+                })
+            })
+            .collect();
+        drop(receiver);
+
+        let read = read_chunks(&mut reader, |first_line, chunk| {
+            // Only fails once every parser is gone, which means they
+            // panicked; joining them below reports it.
+            let _ = sender.send((first_line, chunk));
+        });
+        drop(sender);
+
+        let mut chunks = Vec::new();
+        for parser in parsers {
+            let parsed = parser
+                .join()
+                .map_err(|_| format!("Parsing {} panicked", file_name))?;
+            chunks.extend(parsed);
+        }
+        read.map_err(|err| format!("{}: {}", file_name, err))?;
+
+        chunks.sort_unstable_by_key(|chunk| chunk.first_line);
+        let skipped: usize = chunks.iter().map(|chunk| chunk.skipped).sum();
+        if let Some(error) = chunks.iter().find_map(|chunk| chunk.first_error.as_ref()) {
+            warn!("Skipped {} unreadable lines, first {}", skipped, error);
+        }
+        Ok(chunks.into_iter().flat_map(|chunk| chunk.records).collect())
+    })
+}
+
+/// Split a stream into chunks of about `CHUNK_BYTES` of whole lines,
+/// passing each with the index of its first line.
+fn read_chunks(
+    reader: &mut impl BufRead,
+    mut emit: impl FnMut(usize, String),
+) -> std::io::Result<()> {
+    let mut first_line = 0;
+    let mut lines = 0;
+    let mut chunk = String::new();
+    loop {
+        let read = reader.read_line(&mut chunk)?;
+        if read > 0 {
+            lines += 1;
+        }
+        if chunk.len() >= CHUNK_BYTES || (read == 0 && !chunk.is_empty()) {
+            emit(first_line, std::mem::take(&mut chunk));
+            first_line += lines;
+            lines = 0;
+        }
+        if read == 0 {
+            return Ok(());
+        }
+    }
+}
+
+/// The records of one chunk, and how many of its lines couldn't be read.
+struct ParsedChunk<U> {
+    first_line: usize,
+    records: Vec<U>,
+    skipped: usize,
+    /// The first unreadable line's error, naming the line.
+    first_error: Option<String>,
+}
 
-    for line in reader.lines() {
-        let line = line.map_err(|err| err.to_string())?;
+fn parse_chunk<R: DeserializeOwned, U>(
+    file_name: &str,
+    first_line: usize,
+    chunk: &str,
+    keep: &impl Fn(R) -> Option<U>,
+) -> ParsedChunk<U> {
+    let mut parsed = ParsedChunk {
+        first_line,
+        records: Vec::new(),
+        skipped: 0,
+        first_error: None,
+    };
+    for (offset, line) in chunk.lines().enumerate() {
         if line.trim().is_empty() {
             continue;
         }
+        match serde_json::from_str(line) {
+            Ok(record) => parsed.records.extend(keep(record)),
+            Err(err) => {
+                parsed.skipped += 1;
+                parsed.first_error.get_or_insert_with(|| {
+                    format!("{} line {}: {}", file_name, first_line + offset + 1, err)
+                });
+            }
+        }
+    }
+    parsed
+}
 
-        let value: Value = serde_json::from_str(&line).map_err(|err| err.to_string())?;
-        let type_id = value
-            .get("_key")
-            .and_then(Value::as_i64)
-            .unwrap_or_default();
-        let group_id = value
-            .get("groupID")
-            .and_then(Value::as_i64)
-            .unwrap_or_default();
+fn read_sde_build_number(archive: &mut ZipArchive<File>) -> Result<Option<i64>, String> {
+    if archive.index_for_name("_sde.jsonl").is_none() {
+        return Ok(None);
+    }
+    let builds = read_records(archive, "_sde.jsonl", None, |record: SdeRecord| {
+        record.build_number
+    })?;
+    Ok(builds.first().copied())
+}
 
-        let Some((category_id, group_name)) = groups.get(&group_id) else {
-            continue;
-        };
-        let Some(category_name) = categories.get(category_id) else {
-            continue;
-        };
-        let Some(type_name) = value
-            .get("name")
-            .and_then(|name| name.get("en"))
-            .and_then(Value::as_str)
-        else {
-            continue;
-        };
+/// All category names: the file is tiny, and explicitly covered groups can
+/// live in categories that aren't covered themselves.
+fn read_categories(archive: &mut ZipArchive<File>) -> Result<HashMap<i64, String>, String> {
+    let categories = read_records(
+        archive,
+        "categories.jsonl",
+        None,
+        |mut record: CategoryRecord| Some((record.key, record.name.remove("en")?)),
+    )?;
+    Ok(categories.into_iter().collect())
+}
 
-        entries.push(ScanTypeIndexEntry {
-            type_id,
-            type_name: type_name.to_string(),
-            group_id,
-            group_name: group_name.clone(),
-            category_id: *category_id,
-            category_name: category_name.clone(),
+/// Group metadata needed to classify types: category and names.
+struct GroupInfo {
+    category_id: i64,
+    name: String,
+    names: BTreeMap<String, String>,
+}
+
+fn read_groups(
+    archive: &mut ZipArchive<File>,
+    coverage: &SdeCoverage,
+) -> Result<HashMap<i64, GroupInfo>, String> {
+    let included_categories: HashSet<i64> = coverage.category_ids.iter().copied().collect();
+    let included_groups: HashSet<i64> = coverage.group_ids.iter().copied().collect();
+
+    let groups = read_records(archive, "groups.jsonl", None, |record: GroupRecord| {
+        if !included_categories.contains(&record.category_id)
+            && !included_groups.contains(&record.key)
+        {
+            return None;
+        }
+        let name = record.name.get("en")?.clone();
+        Some((
+            record.key,
+            GroupInfo {
+                category_id: record.category_id,
+                name,
+                names: record.name,
+            },
+        ))
+    })?;
+    Ok(groups.into_iter().collect())
+}
+
+/// Where a ship type sits outside its group, for hull classification.
+struct ShipGroupings {
+    meta_group_id: Option<i64>,
+    market_group_id: Option<i64>,
+}
+
+/// Index entries for every covered type, plus the groupings of the ships
+/// among them.
+fn read_type_entries(
+    archive: &mut ZipArchive<File>,
+    categories: &HashMap<i64, String>,
+    groups: &HashMap<i64, GroupInfo>,
+    progress: &ProgressReporter,
+) -> Result<(Vec<ScanTypeIndexEntry>, HashMap<i64, ShipGroupings>), String> {
+    let types = read_records(
+        archive,
+        "types.jsonl",
+        Some(progress),
+        |record: TypeRecord| {
+            let group = groups.get(&record.group_id)?;
+            let category_name = categories.get(&group.category_id)?;
+            let type_name = record.name.get("en")?.clone();
+
+            let groupings = (group.category_id == SHIP_CATEGORY_ID).then_some(ShipGroupings {
+                meta_group_id: record.meta_group_id,
+                market_group_id: record.market_group_id,
+            });
+            let entry = ScanTypeIndexEntry {
+                type_id: record.key,
+                type_name,
+                type_names: record.name,
+                group_id: record.group_id,
+                group_name: group.name.clone(),
+                group_names: group.names.clone(),
+                category_id: group.category_id,
+                category_name: category_name.clone(),
+            };
+            Some((entry, groupings))
+        },
+    )?;
+
+    let mut ship_groupings = HashMap::new();
+    let entries = types
+        .into_iter()
+        .map(|(entry, groupings)| {
+            if let Some(groupings) = groupings {
+                ship_groupings.insert(entry.type_id, groupings);
+            }
+            entry
+        })
+        .collect();
+    Ok((entries, ship_groupings))
+}
+
+/// Hull attributes of every indexed ship, sorted by type ID. Ships without
+/// a `typeDogma` record (none in a real build) get none.
+fn read_ship_attributes(
+    archive: &mut ZipArchive<File>,
+    entries: &[ScanTypeIndexEntry],
+    progress: &ProgressReporter,
+) -> Result<Vec<(i64, ShipAttributes)>, String> {
+    let ship_ids: HashSet<i64> = entries
+        .iter()
+        .filter(|entry| entry.category_id == SHIP_CATEGORY_ID)
+        .map(|entry| entry.type_id)
+        .collect();
+
+    let bonuses = read_records(
+        archive,
+        "typeBonus.jsonl",
+        None,
+        |record: TypeBonusRecord| {
+            if !ship_ids.contains(&record.key) {
+                return None;
+            }
+            let per_level = record
+                .types
+                .into_iter()
+                .flat_map(|skill| skill.bonuses)
+                .map(|bonus| (bonus, true));
+            let flat = record
+                .role_bonuses
+                .into_iter()
+                .chain(record.misc_bonuses)
+                .map(|bonus| (bonus, false));
+            let lines: Vec<BonusLine> = per_level
+                .chain(flat)
+                .filter_map(|(mut bonus, per_level)| {
+                    Some(BonusLine {
+                        amount: bonus.bonus,
+                        unit_id: bonus.unit_id,
+                        text: bonus.text.remove("en")?,
+                        per_level,
+                    })
+                })
+                .collect();
+            Some((record.key, lines))
+        },
+    )?;
+    let bonuses: HashMap<i64, Vec<BonusLine>> = bonuses.into_iter().collect();
+
+    let mut ships = read_records(
+        archive,
+        "typeDogma.jsonl",
+        Some(progress),
+        |record: TypeDogmaRecord| {
+            if !ship_ids.contains(&record.key) {
+                return None;
+            }
+            let attributes: HashMap<i64, f64> = record
+                .attributes
+                .into_iter()
+                .filter(|attribute| SHIP_ATTRIBUTE_IDS.contains(&attribute.attribute_id))
+                .map(|attribute| (attribute.attribute_id, attribute.value))
+                .collect();
+            let lines = bonuses
+                .get(&record.key)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            Some((record.key, ship_attributes(&attributes, lines)))
+        },
+    )?;
+    ships.sort_unstable_by_key(|(type_id, _)| *type_id);
+    Ok(ships)
+}
+
+/// Deeper than any real market group tree; stops a parent cycle.
+const MAX_MARKET_GROUP_DEPTH: usize = 16;
+
+/// English market group names from the root down to each market group.
+fn read_market_paths(archive: &mut ZipArchive<File>) -> Result<HashMap<i64, Vec<String>>, String> {
+    let groups = read_records(
+        archive,
+        "marketGroups.jsonl",
+        None,
+        |mut record: MarketGroupRecord| {
+            Some((
+                record.key,
+                (record.name.remove("en")?, record.parent_group_id),
+            ))
+        },
+    )?;
+    let groups: HashMap<i64, (String, Option<i64>)> = groups.into_iter().collect();
+
+    Ok(groups
+        .keys()
+        .map(|market_group_id| {
+            let mut path = Vec::new();
+            let mut next = Some(*market_group_id);
+            while let Some((name, parent)) = next.and_then(|id| groups.get(&id)) {
+                if path.len() == MAX_MARKET_GROUP_DEPTH {
+                    break;
+                }
+                path.push(name.clone());
+                next = *parent;
+            }
+            path.reverse();
+            (*market_group_id, path)
+        })
+        .collect())
+}
+
+/// Roles and tech level of every indexed ship, sorted by type ID.
+fn read_hull_profiles(
+    archive: &mut ZipArchive<File>,
+    entries: &[ScanTypeIndexEntry],
+    groupings: &HashMap<i64, ShipGroupings>,
+    ship_attributes: &[(i64, ShipAttributes)],
+) -> Result<Vec<(i64, HullProfile)>, String> {
+    let market_paths = read_market_paths(archive)?;
+    let attributes: HashMap<i64, &ShipAttributes> = ship_attributes
+        .iter()
+        .map(|(type_id, ship)| (*type_id, ship))
+        .collect();
+
+    let mut profiles: Vec<(i64, HullProfile)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let groupings = groupings.get(&entry.type_id)?;
+            let market_path = groupings
+                .market_group_id
+                .and_then(|id| market_paths.get(&id))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let profile = hull_profile(&HullFacts {
+                group_name: &entry.group_name,
+                market_path,
+                meta_group_id: groupings.meta_group_id,
+                attributes: attributes.get(&entry.type_id).copied(),
+            });
+            Some((entry.type_id, profile))
+        })
+        .collect();
+    profiles.sort_unstable_by_key(|(type_id, _)| *type_id);
+    Ok(profiles)
+}
+
+/// Name and wormhole class of a region or constellation.
+struct MapArea {
+    name: String,
+    region_id: i64,
+    wormhole_class: Option<i64>,
+}
+
+fn read_map_areas(
+    archive: &mut ZipArchive<File>,
+    file_name: &str,
+) -> Result<HashMap<i64, MapArea>, String> {
+    let areas = read_records(archive, file_name, None, |mut record: MapAreaRecord| {
+        Some((
+            record.key,
+            MapArea {
+                name: record.name.remove("en")?,
+                // Regions have no regionID of their own.
+                region_id: record.region_id.unwrap_or(record.key),
+                wormhole_class: record.wormhole_class,
+            },
+        ))
+    })?;
+    Ok(areas.into_iter().collect())
+}
+
+/// Every solar system, with constellation and region names resolved and
+/// the wormhole class inherited downwards (region, constellation, system).
+fn read_solar_systems(
+    archive: &mut ZipArchive<File>,
+    progress: &ProgressReporter,
+) -> Result<Vec<SolarSystemEntry>, String> {
+    let regions = read_map_areas(archive, "mapRegions.jsonl")?;
+    let constellations = read_map_areas(archive, "mapConstellations.jsonl")?;
+
+    read_records(
+        archive,
+        "mapSolarSystems.jsonl",
+        Some(progress),
+        |mut record: SolarSystemRecord| {
+            let constellation = constellations.get(&record.constellation_id)?;
+            let region = regions.get(&constellation.region_id)?;
+
+            Some(SolarSystemEntry {
+                system_id: record.key,
+                name: record.name.remove("en")?,
+                constellation_id: record.constellation_id,
+                constellation_name: constellation.name.clone(),
+                region_id: constellation.region_id,
+                region_name: region.name.clone(),
+                security_status: record.security_status,
+                wormhole_class: record
+                    .wormhole_class
+                    .or(constellation.wormhole_class)
+                    .or(region.wormhole_class),
+                position: record
+                    .position
+                    .map(|position| [position.x, position.y, position.z]),
+            })
+        },
+    )
+}
+
+/// Gate connections between systems. Every connection has a gate at each
+/// end; both collapse into one (lower ID, higher ID) pair.
+fn read_stargates(
+    archive: &mut ZipArchive<File>,
+    progress: &ProgressReporter,
+) -> Result<Vec<(i64, i64)>, String> {
+    let mut stargates = read_records(
+        archive,
+        "mapStargates.jsonl",
+        Some(progress),
+        |record: StargateRecord| {
+            let from = record.solar_system_id?;
+            let to = record.destination?.solar_system_id?;
+            Some((from.min(to), from.max(to)))
+        },
+    )?;
+
+    stargates.sort_unstable();
+    stargates.dedup();
+    Ok(stargates)
+}
+
+// ---------------------------------------------------------------------------
+// Binary cache format
+// ---------------------------------------------------------------------------
+//
+// "TSDE", a layout version byte, a string table, then the cache fields in
+// declaration order. Integers are LEB128 varints (zigzag for signed ones),
+// floats little-endian f64, options a 0/1 tag byte, and every string an
+// index into the table, so group, category, locale, constellation and
+// region names are stored once per distinct value. Loading still gives
+// every field its own copy (see the `sde_load` timing in
+// `benches/sde_build.rs`).
+
+const CACHE_MAGIC: &[u8; 4] = b"TSDE";
+/// Bump whenever the byte layout changes. A cache in another layout loads
+/// as missing and is rebuilt.
+const CACHE_LAYOUT_VERSION: u8 = 4;
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+#[derive(Default)]
+struct CacheWriter<'a> {
+    body: Vec<u8>,
+    strings: Vec<&'a str>,
+    string_ids: HashMap<&'a str, u64>,
+}
+
+impl<'a> CacheWriter<'a> {
+    fn uint(&mut self, value: u64) {
+        write_varint(&mut self.body, value);
+    }
+
+    fn len(&mut self, len: usize) {
+        self.uint(len as u64);
+    }
+
+    fn int(&mut self, value: i64) {
+        self.uint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn float(&mut self, value: f64) {
+        self.body.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn string(&mut self, value: &'a str) {
+        let next_id = self.strings.len() as u64;
+        let id = *self.string_ids.entry(value).or_insert_with(|| {
+            self.strings.push(value);
+            next_id
         });
+        self.uint(id);
+    }
+
+    fn ids(&mut self, ids: &[i64]) {
+        self.len(ids.len());
+        for id in ids {
+            self.int(*id);
+        }
+    }
+
+    fn names(&mut self, names: &'a BTreeMap<String, String>) {
+        self.len(names.len());
+        for (locale, name) in names {
+            self.string(locale);
+            self.string(name);
+        }
     }
 
-    Ok(entries)
+    fn finish(self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.body.len() + self.strings.len() * 16);
+        out.extend_from_slice(CACHE_MAGIC);
+        out.push(CACHE_LAYOUT_VERSION);
+        write_varint(&mut out, self.strings.len() as u64);
+        for string in self.strings {
+            write_varint(&mut out, string.len() as u64);
+            out.extend_from_slice(string.as_bytes());
+        }
+        out.extend_from_slice(&self.body);
+        out
+    }
 }
 
+fn encode_index_cache(cache: &SdeIndexCache) -> Vec<u8> {
+    let mut writer = CacheWriter::default();
+    writer.uint(u64::from(cache.format_version));
+    writer.int(cache.build_number);
+    writer.string(&cache.generated_at);
+    writer.ids(&cache.coverage.category_ids);
+    writer.ids(&cache.coverage.group_ids);
+
+    writer.len(cache.entries.len());
+    for entry in &cache.entries {
+        writer.int(entry.type_id);
+        writer.string(&entry.type_name);
+        writer.names(&entry.type_names);
+        writer.int(entry.group_id);
+        writer.string(&entry.group_name);
+        writer.names(&entry.group_names);
+        writer.int(entry.category_id);
+        writer.string(&entry.category_name);
+    }
+
+    writer.len(cache.systems.len());
+    for system in &cache.systems {
+        writer.int(system.system_id);
+        writer.string(&system.name);
+        writer.int(system.constellation_id);
+        writer.string(&system.constellation_name);
+        writer.int(system.region_id);
+        writer.string(&system.region_name);
+        writer.float(system.security_status);
+        match system.wormhole_class {
+            Some(class) => {
+                writer.uint(1);
+                writer.int(class);
+            }
+            None => writer.uint(0),
+        }
+        match system.position {
+            Some(position) => {
+                writer.uint(1);
+                position.iter().for_each(|axis| writer.float(*axis));
+            }
+            None => writer.uint(0),
+        }
+    }
+
+    writer.len(cache.stargates.len());
+    for (from, to) in &cache.stargates {
+        writer.int(*from);
+        writer.int(*to);
+    }
+
+    writer.len(cache.ship_attributes.len());
+    for (type_id, ship) in &cache.ship_attributes {
+        writer.int(*type_id);
+        let slots = ship.slots;
+        for count in [
+            slots.high,
+            slots.mid,
+            slots.low,
+            slots.rig,
+            slots.turret,
+            slots.launcher,
+        ] {
+            writer.uint(u64::from(count));
+        }
+        writer.float(ship.signature_radius);
+        writer.float(ship.max_velocity);
+        writer.float(ship.warp_speed);
+        writer.uint(u64::from(ship.covert_ops_cloak) | u64::from(ship.jump_drive) << 1);
+        writer.len(ship.bonuses.len());
+        for bonus in &ship.bonuses {
+            writer.len(bonus.kinds.len());
+            for kind in &bonus.kinds {
+                writer.uint(bonus_kind_code(*kind));
+            }
+            writer.string(&bonus.text);
+            writer.uint(u64::from(bonus.per_level));
+        }
+    }
+
+    writer.len(cache.hull_profiles.len());
+    for (type_id, profile) in &cache.hull_profiles {
+        writer.int(*type_id);
+        writer.len(profile.roles.len());
+        for role in &profile.roles {
+            writer.uint(hull_role_code(*role));
+        }
+        writer.uint(tech_level_code(profile.tech_level));
+        writer.uint(hull_size_code(profile.size));
+    }
+
+    writer.finish()
+}
+
+/// Stored codes of `HullBonusKind`; append new kinds, never renumber.
+fn bonus_kind_code(kind: HullBonusKind) -> u64 {
+    match kind {
+        HullBonusKind::WarpScrambler => 0,
+        HullBonusKind::WarpDisruptor => 1,
+        HullBonusKind::Interdiction => 2,
+        HullBonusKind::StasisWebifier => 3,
+        HullBonusKind::Ecm => 4,
+        HullBonusKind::SensorDampener => 5,
+        HullBonusKind::WeaponDisruptor => 6,
+        HullBonusKind::TargetPainter => 7,
+        HullBonusKind::CapacitorWarfare => 8,
+    }
+}
+
+fn bonus_kind(code: u64) -> Result<HullBonusKind, String> {
+    Ok(match code {
+        0 => HullBonusKind::WarpScrambler,
+        1 => HullBonusKind::WarpDisruptor,
+        2 => HullBonusKind::Interdiction,
+        3 => HullBonusKind::StasisWebifier,
+        4 => HullBonusKind::Ecm,
+        5 => HullBonusKind::SensorDampener,
+        6 => HullBonusKind::WeaponDisruptor,
+        7 => HullBonusKind::TargetPainter,
+        8 => HullBonusKind::CapacitorWarfare,
+        code => {
+            return Err(format!(
+                "SDE index cache has an unknown bonus kind {}",
+                code
+            ))
+        }
+    })
+}
+
+/// Stored codes of `HullRole`; append new roles, never renumber.
+fn hull_role_code(role: HullRole) -> u64 {
+    match role {
+        HullRole::Tackle => 0,
+        HullRole::Interdiction => 1,
+        HullRole::Logistics => 2,
+        HullRole::Ewar => 3,
+        HullRole::Dps => 4,
+        HullRole::Hauler => 5,
+        HullRole::Mining => 6,
+        HullRole::Covert => 7,
+        HullRole::Capital => 8,
+        HullRole::Super => 9,
+    }
+}
+
+fn hull_role(code: u64) -> Result<HullRole, String> {
+    Ok(match code {
+        0 => HullRole::Tackle,
+        1 => HullRole::Interdiction,
+        2 => HullRole::Logistics,
+        3 => HullRole::Ewar,
+        4 => HullRole::Dps,
+        5 => HullRole::Hauler,
+        6 => HullRole::Mining,
+        7 => HullRole::Covert,
+        8 => HullRole::Capital,
+        9 => HullRole::Super,
+        code => return Err(format!("SDE index cache has an unknown hull role {}", code)),
+    })
+}
+
+/// Stored codes of `TechLevel`; append new levels, never renumber.
+fn tech_level_code(level: TechLevel) -> u64 {
+    match level {
+        TechLevel::Tech1 => 0,
+        TechLevel::Tech2 => 1,
+        TechLevel::Tech3 => 2,
+        TechLevel::Faction => 3,
+    }
+}
+
+fn tech_level(code: u64) -> Result<TechLevel, String> {
+    Ok(match code {
+        0 => TechLevel::Tech1,
+        1 => TechLevel::Tech2,
+        2 => TechLevel::Tech3,
+        3 => TechLevel::Faction,
+        code => {
+            return Err(format!(
+                "SDE index cache has an unknown tech level {}",
+                code
+            ))
+        }
+    })
+}
+
+/// Stored codes of `HullSize`; append new sizes, never renumber.
+fn hull_size_code(size: HullSize) -> u64 {
+    match size {
+        HullSize::Frigate => 0,
+        HullSize::Destroyer => 1,
+        HullSize::Cruiser => 2,
+        HullSize::Battlecruiser => 3,
+        HullSize::Battleship => 4,
+        HullSize::Capital => 5,
+        HullSize::Other => 6,
+    }
+}
+
+fn hull_size(code: u64) -> Result<HullSize, String> {
+    Ok(match code {
+        0 => HullSize::Frigate,
+        1 => HullSize::Destroyer,
+        2 => HullSize::Cruiser,
+        3 => HullSize::Battlecruiser,
+        4 => HullSize::Battleship,
+        5 => HullSize::Capital,
+        6 => HullSize::Other,
+        code => return Err(format!("SDE index cache has an unknown hull size {}", code)),
+    })
+}
+
+struct CacheReader<'a> {
+    bytes: &'a [u8],
+    strings: Vec<String>,
+}
+
+impl CacheReader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8], String> {
+        if len > self.bytes.len() {
+            return Err("SDE index cache is truncated".to_string());
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn uint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("SDE index cache has an oversized integer".to_string())
+    }
+
+    /// A collection length, bounded by the bytes left so a corrupt length
+    /// can't trigger a huge allocation.
+    fn len(&mut self) -> Result<usize, String> {
+        usize::try_from(self.uint()?)
+            .ok()
+            .filter(|len| *len <= self.bytes.len())
+            .ok_or_else(|| "SDE index cache has an invalid length".to_string())
+    }
+
+    fn int(&mut self) -> Result<i64, String> {
+        let value = self.uint()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        u8::try_from(self.uint()?).map_err(|err| err.to_string())
+    }
+
+    fn float(&mut self) -> Result<f64, String> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(f64::from_le_bytes(bytes))
+    }
+
+    fn present(&mut self) -> Result<bool, String> {
+        match self.uint()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(format!("SDE index cache has an invalid option tag {}", tag)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let id = self.uint()?;
+        usize::try_from(id)
+            .ok()
+            .and_then(|id| self.strings.get(id))
+            .cloned()
+            .ok_or_else(|| format!("SDE index cache has an unknown string {}", id))
+    }
+
+    fn ids(&mut self) -> Result<Vec<i64>, String> {
+        (0..self.len()?).map(|_| self.int()).collect()
+    }
+
+    fn names(&mut self) -> Result<BTreeMap<String, String>, String> {
+        (0..self.len()?)
+            .map(|_| Ok((self.string()?, self.string()?)))
+            .collect()
+    }
+}
+
+/// `Ok(None)` for a cache in another layout version.
+fn decode_index_cache(bytes: &[u8]) -> Result<Option<SdeIndexCache>, String> {
+    let Some(rest) = bytes.strip_prefix(CACHE_MAGIC) else {
+        return Err("SDE index cache has an unknown format".to_string());
+    };
+    let Some((&layout, rest)) = rest.split_first() else {
+        return Err("SDE index cache is truncated".to_string());
+    };
+    if layout != CACHE_LAYOUT_VERSION {
+        return Ok(None);
+    }
+
+    let mut reader = CacheReader {
+        bytes: rest,
+        strings: Vec::new(),
+    };
+    let string_count = reader.len()?;
+    let mut strings = Vec::with_capacity(string_count);
+    for _ in 0..string_count {
+        let len = reader.len()?;
+        let string = std::str::from_utf8(reader.take(len)?).map_err(|err| err.to_string())?;
+        strings.push(string.to_string());
+    }
+    reader.strings = strings;
+
+    let format_version = u32::try_from(reader.uint()?).map_err(|err| err.to_string())?;
+    let build_number = reader.int()?;
+    let generated_at = reader.string()?;
+    let coverage = SdeCoverage {
+        category_ids: reader.ids()?,
+        group_ids: reader.ids()?,
+    };
+
+    let entries = (0..reader.len()?)
+        .map(|_| {
+            Ok(ScanTypeIndexEntry {
+                type_id: reader.int()?,
+                type_name: reader.string()?,
+                type_names: reader.names()?,
+                group_id: reader.int()?,
+                group_name: reader.string()?,
+                group_names: reader.names()?,
+                category_id: reader.int()?,
+                category_name: reader.string()?,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let systems = (0..reader.len()?)
+        .map(|_| {
+            Ok(SolarSystemEntry {
+                system_id: reader.int()?,
+                name: reader.string()?,
+                constellation_id: reader.int()?,
+                constellation_name: reader.string()?,
+                region_id: reader.int()?,
+                region_name: reader.string()?,
+                security_status: reader.float()?,
+                wormhole_class: match reader.present()? {
+                    true => Some(reader.int()?),
+                    false => None,
+                },
+                position: match reader.present()? {
+                    true => Some([reader.float()?, reader.float()?, reader.float()?]),
+                    false => None,
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let stargates = (0..reader.len()?)
+        .map(|_| Ok((reader.int()?, reader.int()?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let ship_attributes = (0..reader.len()?)
+        .map(|_| {
+            let type_id = reader.int()?;
+            let slots = ShipSlots {
+                high: reader.byte()?,
+                mid: reader.byte()?,
+                low: reader.byte()?,
+                rig: reader.byte()?,
+                turret: reader.byte()?,
+                launcher: reader.byte()?,
+            };
+            let signature_radius = reader.float()?;
+            let max_velocity = reader.float()?;
+            let warp_speed = reader.float()?;
+            let flags = reader.uint()?;
+            let bonuses = (0..reader.len()?)
+                .map(|_| {
+                    Ok(HullBonus {
+                        kinds: (0..reader.len()?)
+                            .map(|_| bonus_kind(reader.uint()?))
+                            .collect::<Result<_, String>>()?,
+                        text: reader.string()?,
+                        per_level: reader.present()?,
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok((
+                type_id,
+                ShipAttributes {
+                    slots,
+                    signature_radius,
+                    max_velocity,
+                    warp_speed,
+                    covert_ops_cloak: flags & 1 != 0,
+                    jump_drive: flags & 2 != 0,
+                    bonuses,
+                },
+            ))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let hull_profiles = (0..reader.len()?)
+        .map(|_| {
+            let type_id = reader.int()?;
+            let roles = (0..reader.len()?)
+                .map(|_| hull_role(reader.uint()?))
+                .collect::<Result<_, String>>()?;
+            let tech_level = tech_level(reader.uint()?)?;
+            let size = hull_size(reader.uint()?)?;
+            Ok((
+                type_id,
+                HullProfile {
+                    roles,
+                    tech_level,
+                    size,
+                },
+            ))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    if !reader.bytes.is_empty() {
+        return Err("SDE index cache has trailing data".to_string());
+    }
+
+    Ok(Some(SdeIndexCache {
+        format_version,
+        build_number,
+        generated_at,
+        coverage,
+        entries,
+        systems,
+        stargates,
+        ship_attributes,
+        hull_profiles,
+    }))
+}
+
+/// Write the cache beside the index and rename it over the old one once
+/// synced, so a crash at any point leaves a whole index in place. The old
+/// one is kept as the previous index.
 fn save_index_cache(app_dir: &Path, cache: &SdeIndexCache) -> Result<(), String> {
     let path = index_path(app_dir);
-    let json = serde_json::to_vec(cache).map_err(|err| err.to_string())?;
-    std::fs::write(path, json).map_err(|err| err.to_string())
+    if path.exists() {
+        std::fs::copy(&path, app_dir.join(PREVIOUS_INDEX_FILE)).map_err(|err| err.to_string())?;
+    }
+    write_atomically(&path, &encode_index_cache(cache)).map_err(|err| err.to_string())
 }
 
 fn load_index_cache(app_dir: &Path) -> Result<Option<SdeIndexCache>, String> {
     let path = index_path(app_dir);
     if !path.exists() {
+        return migrate_legacy_index_cache(app_dir);
+    }
+
+    let bytes = std::fs::read(&path).map_err(|err| err.to_string())?;
+    let cache = match decode_index_cache(&bytes) {
+        Ok(cache) => cache,
+        Err(err) => return restore_previous_index_cache(app_dir, err),
+    };
+    if cache.is_none() {
+        warn!("SDE index cache has an outdated layout; it will be rebuilt");
+    }
+    Ok(cache)
+}
+
+/// Fall back to the index the last rebuild replaced when the current file
+/// is damaged, and put it back in place.
+fn restore_previous_index_cache(
+    app_dir: &Path,
+    error: String,
+) -> Result<Option<SdeIndexCache>, String> {
+    let previous_path = app_dir.join(PREVIOUS_INDEX_FILE);
+    let Ok(bytes) = std::fs::read(&previous_path) else {
+        return Err(error);
+    };
+    let Ok(Some(cache)) = decode_index_cache(&bytes) else {
+        return Err(error);
+    };
+
+    warn!(
+        "SDE index cache is damaged ({}); restoring build {}",
+        error, cache.build_number
+    );
+    std::fs::rename(&previous_path, index_path(app_dir)).map_err(|err| err.to_string())?;
+    Ok(Some(cache))
+}
+
+/// Rewrite a JSON cache from before the binary format, so upgrading
+/// doesn't cost a re-download.
+fn migrate_legacy_index_cache(app_dir: &Path) -> Result<Option<SdeIndexCache>, String> {
+    let legacy_path = app_dir.join(LEGACY_INDEX_FILE);
+    if !legacy_path.exists() {
         return Ok(None);
     }
 
-    let json = std::fs::read(path).map_err(|err| err.to_string())?;
-    let cache = serde_json::from_slice(&json).map_err(|err| err.to_string())?;
+    let json = std::fs::read(&legacy_path).map_err(|err| err.to_string())?;
+    let cache: SdeIndexCache = serde_json::from_slice(&json).map_err(|err| err.to_string())?;
+    save_index_cache(app_dir, &cache)?;
+    if let Err(err) = std::fs::remove_file(&legacy_path) {
+        warn!(
+            "Failed to remove legacy SDE index {:?}: {}",
+            legacy_path, err
+        );
+    }
     Ok(Some(cache))
 }
 
@@ -448,6 +2851,768 @@ fn extract_build_from_url(url: &str) -> Option<i64> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn localized_names_reads_every_locale() {
+        let record: TypeRecord = serde_json::from_str(
+            r#"{"_key": 587, "groupID": 25, "name": {"en": "Rifter", "de": "Rifter", "ru": "Рифтер", "zh": "裂谷级", "ja": null}, "mass": 1067000}"#,
+        )
+        .unwrap();
+        assert_eq!(record.name.len(), 4);
+        assert_eq!(record.name.get("ru").map(String::as_str), Some("Рифтер"));
+        let record: TypeRecord = serde_json::from_str(r#"{"_key": 1}"#).unwrap();
+        assert!(record.name.is_empty());
+    }
+
+    #[test]
+    fn records_keep_file_order_across_chunks() {
+        // Several chunks' worth, so parsing is spread over the workers.
+        let line = |type_id: usize| {
+            format!(
+                "{{\"_key\": {}, \"groupID\": 25, \"name\": {{\"en\": \"Type {}\"}}, \"description\": {{\"en\": \"{}\"}}}}\n",
+                type_id,
+                type_id,
+                "x".repeat(200)
+            )
+        };
+        let types: String = (0..(3 * CHUNK_BYTES / 200)).map(line).collect();
+        let path = write_archive("chunks", &[("types.jsonl", &types)]);
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+
+        // Rows are reported chunk by chunk, not once for the whole member.
+        let updates = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = updates.clone();
+        let progress = ProgressReporter {
+            snapshot: Arc::default(),
+            listener: Arc::new(move |progress: &SdeProgress| {
+                seen.lock().unwrap().push(progress.rows_indexed);
+            }),
+        };
+        let keys = read_records(
+            &mut archive,
+            "types.jsonl",
+            Some(&progress),
+            |record: TypeRecord| (record.key % 2 == 0).then_some(record.key),
+        )
+        .unwrap();
+        let expected: Vec<i64> = (0..(3 * CHUNK_BYTES / 200) as i64)
+            .filter(|key| key % 2 == 0)
+            .collect();
+        assert_eq!(keys, expected);
+        let updates = updates.lock().unwrap();
+        assert!(updates.len() > 1);
+        assert_eq!(updates.iter().max(), Some(&expected.len()));
+
+        // Malformed records are skipped; the rest of the member is kept.
+        let broken = format!(
+            "{{\"_key\": \"587\"}}\n{}{{\"_key\": 588, \"groupID\": null}}\n{{\"_key\": 589}}\n",
+            types
+        );
+        let path = write_archive("chunks", &[("types.jsonl", &broken)]);
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let keys = read_records(&mut archive, "types.jsonl", None, |record: TypeRecord| {
+            Some(record.key)
+        })
+        .unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(keys.len(), 3 * CHUNK_BYTES / 200 + 1);
+        assert_eq!(keys.last(), Some(&589));
+    }
+
+    #[test]
+    fn a_panicking_parser_fails_the_read_instead_of_hanging() {
+        // More chunks than the channel and the parsers can hold at once.
+        let workers = std::thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(8);
+        let line = format!("{{\"_key\": 1, \"padding\": \"{}\"}}\n", "x".repeat(1000));
+        let types = line.repeat((3 * workers + 2) * CHUNK_BYTES / line.len());
+        let path = write_archive("panicking", &[("types.jsonl", &types)]);
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+
+        let result = read_records(
+            &mut archive,
+            "types.jsonl",
+            None,
+            |_: TypeRecord| -> Option<i64> { panic!("parser bug") },
+        );
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(result.unwrap_err(), "Parsing types.jsonl panicked");
+    }
+
+    #[test]
+    fn unreadable_lines_are_counted_with_the_first_error() {
+        let chunk = "{\"_key\": 1}\n{\"_key\": \"2\"}\n\n{\"_key\": 3}\n{\"_key\": []}\n";
+        let parsed = parse_chunk("types.jsonl", 10, chunk, &|record: TypeRecord| {
+            Some(record.key)
+        });
+        assert_eq!(parsed.records, [1, 3]);
+        assert_eq!(parsed.skipped, 2);
+        assert!(parsed
+            .first_error
+            .unwrap()
+            .starts_with("types.jsonl line 12:"));
+    }
+
+    #[test]
+    fn unversioned_cache_is_stale() {
+        let cache: SdeIndexCache =
+            serde_json::from_str(r#"{"build_number": 1, "generated_at": "", "entries": []}"#)
+                .unwrap();
+        assert_eq!(cache.format_version, 0);
+        assert_eq!(cache.coverage, legacy_coverage());
+        assert!(cache.is_stale(&legacy_coverage()));
+    }
+
+    #[test]
+    fn coverage_change_makes_cache_stale() {
+        let cache = SdeIndexCache {
+            format_version: INDEX_FORMAT_VERSION,
+            build_number: 1,
+            generated_at: String::new(),
+            coverage: default_coverage(),
+            entries: Vec::new(),
+            systems: Vec::new(),
+            stargates: Vec::new(),
+            ship_attributes: Vec::new(),
+            hull_profiles: Vec::new(),
+        };
+        assert!(!cache.is_stale(&default_coverage()));
+
+        let mut narrower = default_coverage();
+        narrower.category_ids.retain(|id| *id != 87);
+        assert!(cache.is_stale(&narrower));
+    }
+
+    fn sample_cache() -> SdeIndexCache {
+        let names = |en: &str, de: &str| {
+            BTreeMap::from([
+                ("en".to_string(), en.to_string()),
+                ("de".to_string(), de.to_string()),
+            ])
+        };
+        let entry = |type_id: i64, type_name: &str| ScanTypeIndexEntry {
+            type_id,
+            type_name: type_name.to_string(),
+            type_names: names(type_name, type_name),
+            group_id: 25,
+            group_name: "Frigate".to_string(),
+            group_names: names("Frigate", "Fregatte"),
+            category_id: 6,
+            category_name: "Ship".to_string(),
+        };
+        SdeIndexCache {
+            format_version: INDEX_FORMAT_VERSION,
+            build_number: 3_012_345,
+            generated_at: "2026-10-18T00:00:00+00:00".to_string(),
+            coverage: default_coverage(),
+            entries: vec![entry(587, "Rifter"), entry(603, "Merlin")],
+            systems: vec![
+                SolarSystemEntry {
+                    system_id: 30000142,
+                    name: "Jita".to_string(),
+                    constellation_id: 20000020,
+                    constellation_name: "Kimotoro".to_string(),
+                    region_id: 10000002,
+                    region_name: "The Forge".to_string(),
+                    security_status: 0.9459,
+                    wormhole_class: Some(7),
+                    position: Some([-1.29e17, 6.07e16, 1.17e17]),
+                },
+                SolarSystemEntry {
+                    system_id: 31000005,
+                    name: "Thera".to_string(),
+                    constellation_id: 21000324,
+                    constellation_name: "B-C00324".to_string(),
+                    region_id: 11000031,
+                    region_name: "G-R00031".to_string(),
+                    security_status: -0.99,
+                    wormhole_class: None,
+                    position: None,
+                },
+            ],
+            stargates: vec![(30000142, 30000144)],
+            ship_attributes: vec![(
+                587,
+                ShipAttributes {
+                    slots: ShipSlots {
+                        high: 4,
+                        mid: 3,
+                        low: 3,
+                        rig: 3,
+                        turret: 3,
+                        launcher: 1,
+                    },
+                    signature_radius: 35.0,
+                    max_velocity: 365.0,
+                    warp_speed: 5.0,
+                    covert_ops_cloak: false,
+                    jump_drive: false,
+                    bonuses: vec![HullBonus {
+                        kinds: vec![HullBonusKind::WarpScrambler, HullBonusKind::WarpDisruptor],
+                        text: "80% reduction in Warp Scrambler and Warp Disruptor capacitor need"
+                            .to_string(),
+                        per_level: false,
+                    }],
+                },
+            )],
+            hull_profiles: vec![(
+                587,
+                HullProfile {
+                    roles: vec![HullRole::Tackle, HullRole::Dps],
+                    tech_level: TechLevel::Tech1,
+                    size: HullSize::Frigate,
+                },
+            )],
+        }
+    }
+
+    fn temp_app_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("telescope-sde-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A cache that passes `validate`: enough types in every checked
+    /// category, the sentinels among them, and a full-sized map.
+    fn plausible_cache() -> SdeIndexCache {
+        let mut cache = sample_cache();
+        cache.entries.clear();
+        for (category_id, minimum) in MIN_TYPES_PER_CATEGORY {
+            let template = ScanTypeIndexEntry {
+                category_id,
+                ..sample_cache().entries[0].clone()
+            };
+            cache
+                .entries
+                .extend((0..minimum as i64).map(|n| ScanTypeIndexEntry {
+                    type_id: category_id * 1_000_000 + n,
+                    ..template.clone()
+                }));
+            cache.entries.extend(
+                SENTINEL_TYPE_IDS
+                    .iter()
+                    .filter(|(sentinel_category, _)| *sentinel_category == category_id)
+                    .map(|(_, type_id)| ScanTypeIndexEntry {
+                        type_id: *type_id,
+                        ..template.clone()
+                    }),
+            );
+        }
+        let jita = cache.systems[0].clone();
+        cache.systems = (0..MIN_SOLAR_SYSTEMS as i64)
+            .map(|n| SolarSystemEntry {
+                system_id: JITA_SYSTEM_ID + n,
+                ..jita.clone()
+            })
+            .collect();
+        cache.stargates = (0..MIN_STARGATES as i64)
+            .map(|n| (JITA_SYSTEM_ID + n, JITA_SYSTEM_ID + n + 1))
+            .collect();
+        let rifter = cache.ship_attributes[0].1.clone();
+        cache.ship_attributes = vec![(587, rifter.clone()), (670, rifter)];
+        cache
+    }
+
+    #[test]
+    fn validation_catches_truncated_indexes() {
+        assert_eq!(plausible_cache().validate(), Ok(()));
+
+        let mut cache = plausible_cache();
+        cache.entries.retain(|entry| entry.category_id != 18);
+        assert!(cache.validate().unwrap_err().contains("category 18"));
+        // Drones aren't required when they aren't covered.
+        cache.coverage.category_ids.retain(|id| *id != 18);
+        assert_eq!(cache.validate(), Ok(()));
+
+        let mut cache = plausible_cache();
+        cache.entries.retain(|entry| entry.type_id != 670);
+        assert!(cache.validate().unwrap_err().contains("type 670"));
+
+        let mut cache = plausible_cache();
+        cache.ship_attributes.clear();
+        assert!(cache.validate().unwrap_err().contains("ship 587"));
+
+        let mut cache = plausible_cache();
+        cache.systems.truncate(100);
+        assert!(cache.validate().is_err());
+
+        let mut cache = plausible_cache();
+        cache.stargates.clear();
+        assert!(cache.validate().is_err());
+    }
+
+    #[test]
+    fn damaged_index_rolls_back_to_the_previous_one() {
+        let dir = temp_app_dir("rollback");
+        let previous = sample_cache();
+        save_index_cache(&dir, &previous).unwrap();
+        let mut newer = sample_cache();
+        newer.build_number += 1;
+        save_index_cache(&dir, &newer).unwrap();
+        assert_eq!(load_index_cache(&dir).unwrap(), Some(newer));
+        // Written aside and renamed into place.
+        assert!(!index_path(&dir).with_extension("bin.tmp").exists());
+
+        let damaged = [&CACHE_MAGIC[..], &[CACHE_LAYOUT_VERSION], b"truncated"].concat();
+        std::fs::write(index_path(&dir), &damaged).unwrap();
+        assert_eq!(load_index_cache(&dir).unwrap(), Some(previous.clone()));
+        // Restored in place, so the next load doesn't need the fallback.
+        assert!(!dir.join(PREVIOUS_INDEX_FILE).exists());
+        assert_eq!(load_index_cache(&dir).unwrap(), Some(previous));
+
+        // Nothing to roll back to: the damage is reported.
+        std::fs::write(index_path(&dir), &damaged).unwrap();
+        assert!(load_index_cache(&dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn binary_cache_round_trips() {
+        let cache = sample_cache();
+        let bytes = encode_index_cache(&cache);
+        assert_eq!(decode_index_cache(&bytes).unwrap(), Some(cache.clone()));
+
+        // Smaller than the JSON of the type entries alone.
+        let json = serde_json::to_vec(&cache.entries).unwrap();
+        assert!(bytes.len() < json.len());
+    }
+
+    #[test]
+    fn damaged_or_foreign_caches_are_rejected() {
+        let mut bytes = encode_index_cache(&sample_cache());
+        assert!(decode_index_cache(&bytes[..bytes.len() - 3]).is_err());
+        assert!(decode_index_cache(b"{\"build_number\": 1}").is_err());
+
+        bytes[CACHE_MAGIC.len()] = CACHE_LAYOUT_VERSION + 1;
+        assert_eq!(decode_index_cache(&bytes).unwrap(), None);
+    }
+
+    #[test]
+    fn legacy_json_cache_is_migrated() {
+        let dir = temp_app_dir("migrate");
+        let mut cache = sample_cache();
+        // Written before format 4: no positions.
+        cache.format_version = 3;
+        let json = serde_json::json!({
+            "format_version": 3,
+            "build_number": cache.build_number,
+            "generated_at": cache.generated_at,
+            "coverage": cache.coverage,
+            "entries": cache.entries,
+            "systems": cache.systems.iter().map(|system| {
+                let mut value = serde_json::to_value(system).unwrap();
+                value.as_object_mut().unwrap().remove("position");
+                value
+            }).collect::<Vec<_>>(),
+            "stargates": cache.stargates,
+        });
+        cache
+            .systems
+            .iter_mut()
+            .for_each(|system| system.position = None);
+        cache.ship_attributes.clear();
+        cache.hull_profiles.clear();
+        std::fs::write(dir.join(LEGACY_INDEX_FILE), json.to_string()).unwrap();
+
+        assert_eq!(load_index_cache(&dir).unwrap(), Some(cache.clone()));
+        assert!(!dir.join(LEGACY_INDEX_FILE).exists());
+        assert!(index_path(&dir).exists());
+        assert_eq!(load_index_cache(&dir).unwrap(), Some(cache));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn normalize_sources_validates_and_keeps_order() {
+        let sources = normalize_sources(vec![
+            " https://mirror.example.org/sde.zip ".to_string(),
+            SDE_URL.to_string(),
+            "https://mirror.example.org/sde.zip".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            sources,
+            vec![
+                "https://mirror.example.org/sde.zip".to_string(),
+                SDE_URL.to_string()
+            ]
+        );
+
+        assert_eq!(
+            normalize_sources(Vec::new()).unwrap(),
+            vec![SDE_URL.to_string()]
+        );
+        assert!(normalize_sources(vec!["file:///tmp/sde.zip".to_string()]).is_err());
+        assert!(normalize_sources(vec!["not a url".to_string()]).is_err());
+    }
+
+    #[test]
+    fn normalize_coverage_sorts_and_dedups() {
+        let coverage = normalize_coverage(SdeCoverage {
+            category_ids: vec![65, 6, 6, 2],
+            group_ids: vec![548, 479, 548],
+        });
+        assert_eq!(coverage.category_ids, vec![2, 6, 65]);
+        assert_eq!(coverage.group_ids, vec![479, 548]);
+        assert_eq!(normalize_coverage(default_coverage()), default_coverage());
+    }
+
+    fn write_archive(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        use std::io::Write;
+
+        let path =
+            std::env::temp_dir().join(format!("telescope-sde-{}-{}.zip", name, std::process::id()));
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        for (file_name, contents) in files {
+            writer
+                .start_file(*file_name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn parse_content_range_reads_start_and_total() {
+        assert_eq!(
+            parse_content_range("bytes 100-199/200"),
+            Some(ContentRange {
+                start: Some(100),
+                total: Some(200)
+            })
+        );
+        assert_eq!(
+            parse_content_range("bytes 0-99/*"),
+            Some(ContentRange {
+                start: Some(0),
+                total: None
+            })
+        );
+        assert_eq!(
+            parse_content_range("bytes */200"),
+            Some(ContentRange {
+                start: None,
+                total: Some(200)
+            })
+        );
+        assert_eq!(parse_content_range("items 1-2/3"), None);
+        assert_eq!(parse_content_range("bytes x-1/3"), None);
+    }
+
+    #[test]
+    fn verify_sde_archive_detects_corruption() {
+        let contents = r#"{"_key": "sde", "buildNumber": 3012345}"#.repeat(50);
+        let members: Vec<(&str, &str)> = SDE_MEMBERS
+            .iter()
+            .map(|name| (*name, contents.as_str()))
+            .collect();
+        let path = write_archive("verify", &members);
+        assert!(verify_sde_archive(&path).is_ok());
+
+        // Flip a byte inside the first member's compressed data, past the
+        // local header: the directory is intact, but reading it fails.
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[60] ^= 0xff;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(verify_sde_archive(&path).is_ok());
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        assert!(read_sde_build_number(&mut archive).is_err());
+
+        // A truncated transfer has no central directory.
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+        assert!(verify_sde_archive(&path).is_err());
+
+        let path = write_archive("verify", &members[..3]);
+        let err = verify_sde_archive(&path).unwrap_err();
+        assert!(err.contains("types.jsonl"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    fn silent_reporter() -> ProgressReporter {
+        ProgressReporter {
+            snapshot: Arc::default(),
+            listener: Arc::new(|_| {}),
+        }
+    }
+
+    #[test]
+    fn progress_is_forwarded_and_cleared_when_finished() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let service = SdeService::default();
+        let progress = service.reporter({
+            let seen = seen.clone();
+            Arc::new(move |progress: &SdeProgress| seen.lock().unwrap().push(progress.clone()))
+        });
+
+        progress.stage(SdeUpdateStage::Downloading);
+        progress.update(|progress| progress.bytes_downloaded = 42);
+        assert_eq!(service.progress().unwrap().bytes_downloaded, 42);
+
+        progress.finish(Some("timeout".to_string()));
+        assert_eq!(service.progress(), None);
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 3);
+        assert_eq!(seen[2].stage, SdeUpdateStage::Failed);
+        assert_eq!(seen[2].error.as_deref(), Some("timeout"));
+    }
+
+    #[test]
+    fn schedule_remembers_the_last_check() {
+        let dir = temp_app_dir("schedule");
+        let service = SdeService::default();
+        let schedule = load_sde_schedule(&dir).unwrap();
+        assert_eq!(schedule.check_interval_hours, DEFAULT_CHECK_INTERVAL_HOURS);
+        // Never checked: due right away.
+        assert_eq!(next_check_delay(&schedule), Duration::ZERO);
+
+        record_sde_check(&dir, 3_064_089).unwrap();
+        let schedule = load_sde_schedule(&dir).unwrap();
+        assert_eq!(schedule.latest_build_number, Some(3_064_089));
+        let interval = check_interval(&schedule);
+        let delay = next_check_delay(&schedule);
+        assert!(delay <= interval && delay > interval - Duration::from_secs(60));
+
+        // Clamped to hourly..weekly; the last check survives.
+        let schedule = save_sde_check_interval(&dir, &service, 0).unwrap();
+        assert_eq!(schedule.check_interval_hours, 1);
+        let schedule = save_sde_check_interval(&dir, &service, 10_000).unwrap();
+        assert_eq!(schedule.check_interval_hours, 7 * 24);
+        assert!(schedule.last_checked_at.is_some());
+
+        // A torn file reads as never checked, and the next check replaces it.
+        std::fs::write(schedule_path(&dir), "{\"check_interval_hours\": 1").unwrap();
+        assert!(load_sde_schedule(&dir).is_err());
+        assert_eq!(
+            next_check_delay(&load_sde_schedule_or_default(&dir)),
+            Duration::ZERO
+        );
+        record_sde_check(&dir, 3_064_089).unwrap();
+        assert!(load_sde_schedule(&dir).unwrap().last_checked_at.is_some());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let checked = |hours_ago: i64| SdeSchedule {
+            check_interval_hours: 12,
+            last_checked_at: Some((Utc::now() - chrono::Duration::hours(hours_ago)).to_rfc3339()),
+            latest_build_number: None,
+        };
+        assert_eq!(next_check_delay(&checked(13)), Duration::ZERO);
+        // A clock that jumped backwards doesn't postpone checks forever.
+        assert_eq!(next_check_delay(&checked(-48)), Duration::ZERO);
+    }
+
+    #[test]
+    fn last_error_persists_until_success() {
+        let dir = temp_app_dir("last-error");
+        assert_eq!(load_last_error(&dir), None);
+        save_last_error(&dir, Some("no route to host")).unwrap();
+        assert_eq!(load_last_error(&dir).as_deref(), Some("no route to host"));
+        save_last_error(&dir, None).unwrap();
+        assert_eq!(load_last_error(&dir), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejected_build_only_applies_to_the_same_rebuild() {
+        let dir = temp_app_dir("rejected");
+        let coverage = default_coverage();
+        assert_eq!(load_rejected_build(&dir, &coverage), None);
+        save_rejected_build(&dir, Some(3012345), &coverage).unwrap();
+        assert_eq!(load_rejected_build(&dir, &coverage), Some(3012345));
+
+        // Other coverage can still pass validation with that build.
+        let narrower = SdeCoverage {
+            category_ids: vec![6],
+            group_ids: vec![],
+        };
+        assert_eq!(load_rejected_build(&dir, &narrower), None);
+
+        save_rejected_build(&dir, None, &coverage).unwrap();
+        assert_eq!(load_rejected_build(&dir, &coverage), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn kept_members_replace_the_previous_build() {
+        let dir = temp_app_dir("kept");
+        let path = write_archive(
+            "kept",
+            &[
+                ("_sde.jsonl", r#"{"_key": "sde", "buildNumber": 3012345}"#),
+                ("types.jsonl", r#"{"_key": 587}"#),
+                ("blueprints.jsonl", r#"{"_key": 688}"#),
+            ],
+        );
+        keep_sde_members(&dir, &path, 3012344);
+        keep_sde_members(&dir, &path, 3012345);
+        assert!(!kept_members_path(&dir, 3012344).exists());
+
+        // Only the members the index reads, without recompressing them.
+        let kept = File::open(kept_members_path(&dir, 3012345)).unwrap();
+        let mut archive = ZipArchive::new(kept).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert_eq!(names.len(), 2);
+        assert_eq!(read_sde_build_number(&mut archive).unwrap(), Some(3012345));
+        assert!(archive.by_name("blueprints.jsonl").is_err());
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn local_archive_is_imported() {
+        let dir = temp_app_dir("import");
+        let path = write_archive(
+            "import",
+            &[
+                ("_sde.jsonl", r#"{"_key": "sde", "buildNumber": 3012345}"#),
+                ("categories.jsonl", r#"{"_key": 6, "name": {"en": "Ship"}}"#),
+                (
+                    "groups.jsonl",
+                    r#"{"_key": 25, "categoryID": 6, "name": {"en": "Frigate"}}"#,
+                ),
+                (
+                    "marketGroups.jsonl",
+                    concat!(
+                        r#"{"_key": 4, "name": {"en": "Ships"}}"#,
+                        "\n",
+                        r#"{"_key": 61, "name": {"en": "Standard Frigates"}, "parentGroupID": 4}"#,
+                        "\n",
+                        // A parent cycle must not hang the build.
+                        r#"{"_key": 98, "name": {"en": "Loop"}, "parentGroupID": 99}"#,
+                        "\n",
+                        r#"{"_key": 99, "name": {"en": "Loop"}, "parentGroupID": 98}"#,
+                    ),
+                ),
+                (
+                    "types.jsonl",
+                    r#"{"_key": 587, "groupID": 25, "name": {"en": "Rifter"}, "metaGroupID": 1, "marketGroupID": 61}"#,
+                ),
+                (
+                    "typeDogma.jsonl",
+                    r#"{"_key": 587, "dogmaAttributes": [{"attributeID": 14, "value": 4.0}, {"attributeID": 552, "value": 35.0}, {"attributeID": 600, "value": 5.0}, {"attributeID": 3, "value": 0.0}]}"#,
+                ),
+                (
+                    "typeBonus.jsonl",
+                    r#"{"_key": 587, "roleBonuses": [{"bonus": 80.0, "bonusText": {"en": "reduction in <a href=showinfo:447>Warp Scrambler</a> capacitor need", "de": "..."}, "importance": 1, "unitID": 105}], "types": [{"_key": 3329, "_value": [{"bonus": 5.0, "bonusText": {"en": "bonus to Small Projectile Turret damage"}, "importance": 1, "unitID": 105}]}]}"#,
+                ),
+                ("mapRegions.jsonl", ""),
+                ("mapConstellations.jsonl", ""),
+                ("mapSolarSystems.jsonl", ""),
+                ("mapStargates.jsonl", ""),
+            ],
+        );
+
+        let progress = silent_reporter();
+        let cache = build_index_cache_from_zip(&path, None, default_coverage(), &progress).unwrap();
+        assert_eq!(cache.build_number, 3012345);
+        assert_eq!(cache.entries[0].type_name, "Rifter");
+        let (type_id, rifter) = &cache.ship_attributes[0];
+        assert_eq!((*type_id, rifter.slots.high), (587, 4));
+        assert_eq!(rifter.signature_radius, 35.0);
+        assert_eq!(rifter.warp_speed, 5.0);
+        assert_eq!(rifter.bonuses.len(), 1);
+        assert_eq!(
+            rifter.bonuses[0].text,
+            "80% reduction in Warp Scrambler capacitor need"
+        );
+        // The scrambler bonus makes it tackle; no hardpoints, so no DPS.
+        let (_, profile) = &cache.hull_profiles[0];
+        assert_eq!(profile.roles, [HullRole::Tackle]);
+        assert_eq!(profile.tech_level, TechLevel::Tech1);
+        assert_eq!(profile.size, HullSize::Frigate);
+        let snapshot = progress.snapshot.lock().unwrap().clone().unwrap();
+        assert_eq!(snapshot.stage, SdeUpdateStage::Building);
+        assert_eq!(snapshot.target_build, Some(3012345));
+        assert_eq!(snapshot.rows_indexed, 3);
+
+        // A single ship and no map is far too little to replace the
+        // working index.
+        save_index_cache(&dir, &sample_cache()).unwrap();
+        let result = build_index_from_archive(&dir, &path, default_coverage(), &progress);
+        assert!(matches!(result, Err(UpdateFailure::Invalid(_))));
+        assert_eq!(load_index_cache(&dir).unwrap(), Some(sample_cache()));
+        // The user's archive is left in place.
+        assert!(path.exists());
+
+        std::fs::write(&path, b"not a zip").unwrap();
+        let result = build_index_from_archive(&dir, &path, default_coverage(), &progress);
+        assert!(matches!(result, Err(UpdateFailure::Build(_))));
+
+        // Without `_sde.jsonl` or a build in the file name there's no
+        // build number to label the index with.
+        let members: Vec<(&str, &str)> = SDE_MEMBERS[1..].iter().map(|name| (*name, "")).collect();
+        let unnamed = write_archive("unnamed", &members);
+        let result = build_index_from_archive(&dir, &unnamed, default_coverage(), &progress);
+        assert!(matches!(result, Err(UpdateFailure::Build(err)) if err.contains("build")));
+        assert_eq!(load_index_cache(&dir).unwrap(), Some(sample_cache()));
+        std::fs::remove_file(&unnamed).unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn read_solar_systems_resolves_areas_and_wormhole_class() {
+        let path = write_archive(
+            "systems",
+            &[
+                (
+                    "mapRegions.jsonl",
+                    "{\"_key\": 10000002, \"name\": {\"en\": \"The Forge\"}}\n\
+                     {\"_key\": 11000001, \"name\": {\"en\": \"A-R00001\"}, \"wormholeClassID\": 1}",
+                ),
+                (
+                    "mapConstellations.jsonl",
+                    "{\"_key\": 20000020, \"name\": {\"en\": \"Kimotoro\"}, \"regionID\": 10000002, \"wormholeClassID\": 7}\n\
+                     {\"_key\": 21000001, \"name\": {\"en\": \"A-C00311\"}, \"regionID\": 11000001}",
+                ),
+                (
+                    "mapSolarSystems.jsonl",
+                    "{\"_key\": 30000142, \"name\": {\"en\": \"Jita\", \"de\": \"Jita\"}, \"constellationID\": 20000020, \"securityStatus\": 0.9459, \"position\": {\"x\": -1.29e17, \"y\": 6.07e16, \"z\": 1.17e17}}\n\
+                     {\"_key\": 31000005, \"name\": {\"en\": \"J123456\"}, \"constellationID\": 21000001, \"securityStatus\": -0.99}\n\
+                     {\"_key\": 39999999, \"name\": {\"en\": \"Orphan\"}, \"constellationID\": 1}",
+                ),
+            ],
+        );
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let systems = read_solar_systems(&mut archive, &silent_reporter()).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(systems.len(), 2);
+        let jita = &systems[0];
+        assert_eq!(jita.name, "Jita");
+        assert_eq!(jita.constellation_name, "Kimotoro");
+        assert_eq!(jita.region_id, 10000002);
+        assert_eq!(jita.region_name, "The Forge");
+        assert_eq!(jita.security_status, 0.9459);
+        assert_eq!(jita.wormhole_class, Some(7));
+        assert_eq!(jita.position, Some([-1.29e17, 6.07e16, 1.17e17]));
+        assert_eq!(systems[1].position, None);
+        // Inherited from the region.
+        assert_eq!(systems[1].wormhole_class, Some(1));
+    }
+
+    #[test]
+    fn read_stargates_collapses_both_gate_ends() {
+        let path = write_archive(
+            "stargates",
+            &[(
+                "mapStargates.jsonl",
+                "{\"_key\": 50001248, \"solarSystemID\": 30000142, \"destination\": {\"solarSystemID\": 30000144, \"stargateID\": 50001249}}\n\
+                 {\"_key\": 50001249, \"solarSystemID\": 30000144, \"destination\": {\"solarSystemID\": 30000142, \"stargateID\": 50001248}}\n\
+                 {\"_key\": 50001250, \"solarSystemID\": 30000142}",
+            )],
+        );
+        let mut archive = ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let stargates = read_stargates(&mut archive, &silent_reporter()).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(stargates, vec![(30000142, 30000144)]);
+    }
+
     #[test]
     fn extract_build_from_url_parses_trailing_digits() {
         assert_eq!(
@@ -303,6 +303,7 @@ pub async fn share_scan(
     scan_type: &str,
     raw_text: &str,
     solar_system: Option<&str>,
+    solar_system_id: Option<i64>,
 ) -> Result<NetworkScan, String> {
     let resp = client
         .post(format!("{}/api/networks/{}/scans", base_url, network_id))
@@ -310,6 +311,7 @@ pub async fn share_scan(
             "scan_type": scan_type,
             "raw_text": raw_text,
             "solar_system": solar_system,
+            "solar_system_id": solar_system_id,
         }))
         .send()
         .await
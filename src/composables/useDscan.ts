@@ -1,11 +1,13 @@
-import { computed, ref } from 'vue'
+import { computed, onMounted, onUnmounted, ref } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
-import type { DscanParseResult, SdeStatus } from '../types'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import type { DscanParseResult, SdeProgress, SdeStatus } from '../types'
 
 export function useDscan() {
     const rawInput = ref('')
     const result = ref<DscanParseResult | null>(null)
     const sdeStatus = ref<SdeStatus | null>(null)
+    const sdeProgress = ref<SdeProgress | null>(null)
     const loading = ref(false)
     const syncing = ref(false)
     const error = ref<string | null>(null)
@@ -22,19 +24,49 @@ export function useDscan() {
         }
     }
 
-    async function ensureSdeIndex() {
+    /**
+     * Check for a new SDE build. Unless forced, the backend skips the check
+     * when its background scheduler checked recently.
+     */
+    async function ensureSdeIndex(force = false) {
         syncing.value = true
         error.value = null
         try {
-            sdeStatus.value = await invoke<SdeStatus>('ensure_sde_index')
+            sdeStatus.value = await invoke<SdeStatus>('ensure_sde_index', {
+                force,
+            })
         } catch (err) {
             error.value = String(err)
             await refreshSdeStatus()
         } finally {
+            sdeProgress.value = null
             syncing.value = false
         }
     }
 
+    // Progress of every update, including the background scheduler's, and
+    // the builds it installs.
+    let unlistenProgress: UnlistenFn | null = null
+    let unlistenUpdated: UnlistenFn | null = null
+    onMounted(async () => {
+        unlistenProgress = await listen<SdeProgress>('sde-progress', (event) => {
+            const finished =
+                event.payload.stage === 'ready' ||
+                event.payload.stage === 'failed'
+            sdeProgress.value = finished ? null : event.payload
+            if (event.payload.stage === 'failed') {
+                void refreshSdeStatus()
+            }
+        })
+        unlistenUpdated = await listen<SdeStatus>('sde-updated', (event) => {
+            sdeStatus.value = event.payload
+        })
+    })
+    onUnmounted(() => {
+        unlistenProgress?.()
+        unlistenUpdated?.()
+    })
+
     async function parse(text: string) {
         rawInput.value = text
         loading.value = true
@@ -61,6 +93,7 @@ export function useDscan() {
         rawInput,
         result,
         sdeStatus,
+        sdeProgress,
         loading,
         syncing,
         error,
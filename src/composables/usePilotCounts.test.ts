@@ -49,6 +49,10 @@ function makePilot(
             is_capital: false,
             is_super: false,
             is_solo: false,
+            is_tackle: false,
+            is_interdiction: false,
+            is_logistics: false,
+            is_ewar: false,
             ...overrides.flags,
         },
         error: null,
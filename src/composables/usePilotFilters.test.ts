@@ -45,6 +45,10 @@ function makePilot(
             is_capital: false,
             is_super: false,
             is_solo: false,
+            is_tackle: false,
+            is_interdiction: false,
+            is_logistics: false,
+            is_ewar: false,
         },
         error: null,
         ...rest,
@@ -80,6 +84,10 @@ describe('usePilotFilters', () => {
                     is_capital: false,
                     is_super: false,
                     is_solo: false,
+                    is_tackle: false,
+                    is_interdiction: false,
+                    is_logistics: false,
+                    is_ewar: false,
                 },
             }),
             makePilot(),
@@ -129,6 +137,10 @@ describe('usePilotFilters', () => {
                     is_capital: false,
                     is_super: false,
                     is_solo: false,
+                    is_tackle: false,
+                    is_interdiction: false,
+                    is_logistics: false,
+                    is_ewar: false,
                 },
             }),
             makePilot({
@@ -139,6 +151,10 @@ describe('usePilotFilters', () => {
                     is_capital: false,
                     is_super: false,
                     is_solo: false,
+                    is_tackle: false,
+                    is_interdiction: false,
+                    is_logistics: false,
+                    is_ewar: false,
                 },
             }),
             makePilot(),
@@ -41,7 +41,10 @@ export function usePilots() {
 
     const pilotCount = computed(() => splitPilotNames(pilotNames.value).length)
 
-    async function lookupPilots(namesOverride?: string) {
+    async function lookupPilots(
+        namesOverride?: string,
+        originSystemId?: number | null
+    ) {
         const names = namesOverride ?? pilotNames.value
         if (!names.trim()) return
 
@@ -62,6 +65,7 @@ export function usePilots() {
         try {
             const finalPilots = await invoke<PilotIntel[]>('lookup_pilots', {
                 namesText: names,
+                originSystemId: originSystemId ?? null,
             })
 
             // Event delivery isn't guaranteed to complete before the invoke
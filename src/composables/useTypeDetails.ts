@@ -0,0 +1,31 @@
+import { ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import type { TypeDetails } from '../types'
+
+// Shared by every component: details only change with an SDE rebuild.
+const details = ref(new Map<number, TypeDetails>())
+const requested = new Set<number>()
+
+export function useTypeDetails() {
+    /** Fetch details for types not already loaded or in flight. */
+    async function loadTypeDetails(typeIds: number[]) {
+        const missing = typeIds.filter((typeId) => !requested.has(typeId))
+        missing.forEach((typeId) => requested.add(typeId))
+        await Promise.all(
+            missing.map(async (typeId) => {
+                try {
+                    const found = await invoke<TypeDetails | null>(
+                        'get_type_details',
+                        { typeId }
+                    )
+                    if (found) details.value.set(typeId, found)
+                } catch {
+                    // No SDE index yet; try again on the next request.
+                    requested.delete(typeId)
+                }
+            })
+        )
+    }
+
+    return { typeDetails: details, loadTypeDetails }
+}
@@ -0,0 +1,47 @@
+import { ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { useDebounceFn } from '@vueuse/core'
+import type { TypeSearchFilter, TypeSearchHit } from '../types'
+
+export function searchTypes(query: string, filter: TypeSearchFilter = {}) {
+    return invoke<TypeSearchHit[]>('search_types', {
+        query,
+        categoryIds: filter.categoryIds ?? null,
+        groupIds: filter.groupIds ?? null,
+        limit: filter.limit ?? null,
+    })
+}
+
+/**
+ * Ranked type autocomplete for an input, e.g. ships only with
+ * `{ categoryIds: [6] }`. Empty until the SDE index is built.
+ */
+export function useTypeSearch(filter: TypeSearchFilter = {}) {
+    const results = ref<TypeSearchHit[]>([])
+
+    // Monotonic token so an out-of-order response can't clobber newer results.
+    let searchToken = 0
+
+    async function performSearch(query: string) {
+        const token = ++searchToken
+        try {
+            const found = await searchTypes(query, filter)
+            if (token === searchToken) results.value = found
+        } catch {
+            if (token === searchToken) results.value = []
+        }
+    }
+
+    const debouncedSearch = useDebounceFn(performSearch, 100)
+
+    function search(query: string) {
+        if (query.trim() === '') {
+            ++searchToken
+            results.value = []
+            return
+        }
+        debouncedSearch(query)
+    }
+
+    return { results, search }
+}
@@ -71,6 +71,7 @@ function makeScan(overrides: Partial<NetworkScan> = {}): NetworkScan {
         scan_type: 'local',
         raw_text: 'Pilot One\nPilot Two',
         solar_system: null,
+        solar_system_id: null,
         created_at: '2026-04-01T10:00:00+00:00',
         submitted_by: { id: 12345, character_name: 'Scanner' },
         ...overrides,
@@ -98,6 +99,10 @@ function makePilot(overrides: Partial<PilotIntel> = {}): PilotIntel {
             is_capital: false,
             is_super: false,
             is_solo: false,
+            is_tackle: false,
+            is_interdiction: false,
+            is_logistics: false,
+            is_ewar: false,
         },
         error: null,
         ...overrides,
@@ -12,6 +12,7 @@ import type {
     NetworkAccess,
     NetworkScan,
     PaginatedScans,
+    ScanType,
     EntityType,
     PermissionLevel,
     SearchResult,
@@ -394,15 +395,17 @@ export const latestSharedScan = ref<NetworkScan | null>(null)
 
 export async function shareScan(
     networkId: number,
-    scanType: 'local' | 'dscan',
+    scanType: ScanType,
     rawText: string,
-    solarSystem?: string | null
+    solarSystem?: string | null,
+    solarSystemId?: number | null
 ) {
     return invoke<NetworkScan>('share_scan', {
         networkId,
         scanType,
         rawText,
         solarSystem: solarSystem ?? null,
+        solarSystemId: solarSystemId ?? null,
     })
 }
 
@@ -16,6 +16,7 @@ export interface SystemStats {
     system_id: number
     system_name: string
     kills: number
+    jumps: number | null
 }
 
 export interface ZkillStats {
@@ -53,6 +54,10 @@ export interface PilotFlags {
     is_capital: boolean
     is_super: boolean
     is_solo: boolean
+    is_tackle: boolean
+    is_interdiction: boolean
+    is_logistics: boolean
+    is_ewar: boolean
 }
 
 export interface PilotIntel {
@@ -69,22 +74,383 @@ export interface SdeStatus {
     ready: boolean
     updating: boolean
     last_error: string | null
+    progress: SdeProgress | null
 }
 
+export type SdeUpdateStage =
+    | 'checking'
+    | 'downloading'
+    | 'verifying'
+    | 'building'
+    | 'ready'
+    | 'failed'
+
+/** Payload of the backend 'sde-progress' event. */
+export interface SdeProgress {
+    stage: SdeUpdateStage
+    target_build: number | null
+    bytes_downloaded: number
+    bytes_total: number | null
+    rows_indexed: number
+    error: string | null
+}
+
+export type ScanObjectKind =
+    | 'ship'
+    | 'structure'
+    | 'deployable'
+    | 'drone'
+    | 'fighter'
+    | 'celestial'
+    | 'probe'
+    | 'wreck'
+    | 'other'
+
+export interface SdeCoverage {
+    category_ids: number[]
+    group_ids: number[]
+}
+
+/** How often the backend checks for a new SDE build in the background. */
+export interface SdeSchedule {
+    check_interval_hours: number
+    last_checked_at: string | null
+    latest_build_number: number | null
+}
+
+export interface SolarSystemEntry {
+    system_id: number
+    name: string
+    constellation_id: number
+    constellation_name: string
+    region_id: number
+    region_name: string
+    security_status: number
+    wormhole_class: number | null
+    position: [number, number, number] | null
+}
+
+export interface ShipSlots {
+    high: number
+    mid: number
+    low: number
+    rig: number
+    turret: number
+    launcher: number
+}
+
+export type HullBonusKind =
+    | 'warp_scrambler'
+    | 'warp_disruptor'
+    | 'interdiction'
+    | 'stasis_webifier'
+    | 'ecm'
+    | 'sensor_dampener'
+    | 'weapon_disruptor'
+    | 'target_painter'
+    | 'capacitor_warfare'
+
+export interface HullBonus {
+    kinds: HullBonusKind[]
+    text: string
+    per_level: boolean
+}
+
+/** SDE hull attributes of a ship, unfitted and unskilled. */
+export interface ShipAttributes {
+    slots: ShipSlots
+    signature_radius: number
+    max_velocity: number
+    /** AU/s */
+    warp_speed: number
+    covert_ops_cloak: boolean
+    jump_drive: boolean
+    /** Tackle and e-war bonuses only. */
+    bonuses: HullBonus[]
+}
+
+export type HullRole =
+    | 'tackle'
+    | 'interdiction'
+    | 'logistics'
+    | 'ewar'
+    | 'dps'
+    | 'hauler'
+    | 'mining'
+    | 'covert'
+    | 'capital'
+    | 'super'
+
+export type TechLevel = 't1' | 't2' | 't3' | 'faction'
+
+export type HullSize =
+    | 'frigate'
+    | 'destroyer'
+    | 'cruiser'
+    | 'battlecruiser'
+    | 'battleship'
+    | 'capital'
+    | 'other'
+
+export interface HullProfile {
+    roles: HullRole[]
+    tech_level: TechLevel
+    size: HullSize
+}
+
+export interface TypeDetails {
+    type_id: number
+    type_name: string
+    type_names: Record<string, string>
+    group_id: number
+    group_name: string
+    group_names: Record<string, string>
+    category_id: number
+    category_name: string
+    ship: ShipAttributes | null
+    hull: HullProfile | null
+}
+
+export type TypeMatchKind =
+    | 'exact'
+    | 'prefix'
+    | 'word_prefix'
+    | 'substring'
+    | 'fuzzy'
+
+export interface TypeSearchHit {
+    type_id: number
+    type_name: string
+    group_id: number
+    group_name: string
+    category_id: number
+    category_name: string
+    matched_name: string
+    match_kind: TypeMatchKind
+}
+
+export interface TypeSearchFilter {
+    categoryIds?: number[]
+    groupIds?: number[]
+    limit?: number
+}
+
+export type RoutePreference = 'shortest' | 'safer'
+
+export interface Route {
+    systems: SolarSystemEntry[]
+    jumps: number
+}
+
+export type JumpShipClass =
+    | 'capital'
+    | 'supercapital'
+    | 'black_ops'
+    | 'jump_freighter'
+    | 'rorqual'
+
+export interface JumpRangeSystem {
+    system: SolarSystemEntry
+    distance_ly: number
+}
+
+export interface CapitalThreat {
+    character_id: number
+    character_name: string
+    is_super: boolean
+    systems_in_range: JumpRangeSystem[]
+}
+
+export type DscanRange = 'on_grid' | 'warp_bubble' | 'in_system' | 'unknown'
+
 export interface DscanEntry {
     type_id: number | null
     name: string
     type_name: string
+    display_type_name: string | null
     distance: string | null
+    distance_m: number | null
+    range: DscanRange
+    group_id: number | null
     group_name: string | null
+    category_id: number | null
     category_name: string | null
+    kind: ScanObjectKind
     is_ship: boolean
+    hull_roles: HullRole[]
+    tech_level: TechLevel | null
+}
+
+export interface DscanRangeCounts {
+    on_grid: number
+    warp_bubble: number
+    in_system: number
+    unknown: number
+}
+
+export interface DscanCount {
+    id: number | null
+    name: string
+    count: number
+}
+
+export interface ShipClassTotals {
+    frigates: number
+    destroyers: number
+    cruisers: number
+    battlecruisers: number
+    battleships: number
+    capitals: number
+    industrials: number
+    other: number
+}
+
+export interface HullRoleTotals {
+    tackle: number
+    interdiction: number
+    logistics: number
+    ewar: number
+    dps: number
+    hauler: number
+    mining: number
+    covert: number
+    capital: number
+    super: number
+}
+
+export interface TechLevelTotals {
+    t1: number
+    t2: number
+    t3: number
+    faction: number
+}
+
+export interface DscanSummary {
+    by_type: DscanCount[]
+    by_group: DscanCount[]
+    by_category: DscanCount[]
+    ship_classes: ShipClassTotals
+    hull_roles: HullRoleTotals
+    tech_levels: TechLevelTotals
+}
+
+export interface DscanCountChange {
+    id: number | null
+    name: string
+    before: number
+    after: number
+    delta: number
+}
+
+export interface DscanMove {
+    type_id: number | null
+    type_name: string
+    name: string
+    from: DscanRange
+    to: DscanRange
+}
+
+export interface DscanDiff {
+    arrivals: DscanCountChange[]
+    departures: DscanCountChange[]
+    group_changes: DscanCountChange[]
+    moved: DscanMove[]
 }
 
 export interface DscanParseResult {
     total_rows: number
     ship_count: number
+    ships_by_range: DscanRangeCounts
+    summary: DscanSummary
     entries: DscanEntry[]
+    detected_system: DetectedSystem | null
+}
+
+export interface DetectedSystem {
+    system_id: number
+    name: string
+    confidence: number
+    evidence: number
+}
+
+export interface DoctrineMatch {
+    name: string
+    confidence: number
+    matched_ships: number
+}
+
+export interface FleetAnalysis {
+    likely_doctrine: DoctrineMatch | null
+    matches: DoctrineMatch[]
+    ship_count: number
+    logistics_count: number
+    tackle_count: number
+    capital_count: number
+    has_capitals: boolean
+}
+
+export interface FleetMember {
+    pilot: string
+    system: string
+    ship_type_name: string
+    type_id: number | null
+    group_id: number | null
+    group_name: string | null
+    position: string
+    wing: string | null
+    squad: string | null
+}
+
+export interface FleetWing {
+    name: string
+    member_count: number
+    ships: DscanCount[]
+}
+
+export interface FleetComposition {
+    members: FleetMember[]
+    wings: FleetWing[]
+    pilot_names: string[]
+    reference_system: string | null
+    out_of_position: FleetMember[]
+}
+
+export interface FleetLookupResult {
+    composition: FleetComposition
+    pilots: PilotIntel[]
+}
+
+export interface OverviewColumns {
+    name: number | null
+    type_name: number | null
+    distance: number | null
+    corporation: number | null
+    alliance: number | null
+}
+
+export interface OverviewEntry {
+    name: string
+    type_name: string | null
+    type_id: number | null
+    group_id: number | null
+    group_name: string | null
+    category_id: number | null
+    kind: ScanObjectKind
+    is_ship: boolean
+    distance: string | null
+    distance_m: number | null
+    range: DscanRange
+    corporation_ticker: string | null
+    alliance_ticker: string | null
+}
+
+export interface OverviewParseResult {
+    total_rows: number
+    ship_count: number
+    columns: OverviewColumns
+    by_corporation: DscanCount[]
+    by_alliance: DscanCount[]
+    entries: OverviewEntry[]
 }
 
 export type EntityType = 'character' | 'corporation' | 'alliance'
@@ -170,6 +536,58 @@ export interface NetworkAccess {
     entity: EntityInfo | null
 }
 
+export type ScanType = 'local' | 'dscan' | 'probe'
+
+export type SignatureKind =
+    | 'combat'
+    | 'data'
+    | 'relic'
+    | 'gas'
+    | 'ore'
+    | 'wormhole'
+    | 'unknown'
+
+export interface ProbeSignature {
+    id: string
+    scan_group: string
+    group: string | null
+    kind: SignatureKind
+    site_name: string | null
+    signal_strength: number | null
+    distance: string | null
+    distance_m: number | null
+}
+
+export interface ProbeParseResult {
+    total_rows: number
+    resolved_count: number
+    signatures: ProbeSignature[]
+}
+
+export interface ProbeDiff {
+    new: ProbeSignature[]
+    gone: ProbeSignature[]
+    resolved: ProbeSignature[]
+}
+
+export type PasteKind =
+    | 'local'
+    | 'dscan'
+    | 'probe'
+    | 'overview'
+    | 'fleet'
+    | 'killmail'
+    | 'unknown'
+
+export type IngestedPaste =
+    | { kind: 'local'; result: PilotIntel[] }
+    | { kind: 'dscan'; result: DscanParseResult }
+    | { kind: 'probe'; result: ProbeParseResult }
+    | { kind: 'overview'; result: OverviewParseResult }
+    | { kind: 'fleet'; result: FleetLookupResult }
+    | { kind: 'killmail'; result: PilotIntel[] }
+    | { kind: 'unknown' }
+
 export interface ScanSubmitter {
     id: number
     character_name: string
@@ -177,9 +595,10 @@ export interface ScanSubmitter {
 
 export interface NetworkScan {
     id: number
-    scan_type: 'local' | 'dscan'
+    scan_type: ScanType
     raw_text: string
     solar_system: string | null
+    solar_system_id: number | null
     created_at: string
     submitted_by: ScanSubmitter | null
 }
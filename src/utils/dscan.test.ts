@@ -15,10 +15,18 @@ function makeEntry(overrides: Partial<DscanEntry> = {}): DscanEntry {
         type_id: 1,
         name: 'Some Pilot',
         type_name: 'Sabre',
+        display_type_name: 'Sabre',
         distance: null,
+        distance_m: null,
+        range: 'unknown',
+        group_id: 541,
         group_name: 'Interdictor',
+        category_id: 6,
         category_name: 'Ship',
+        kind: 'ship',
         is_ship: true,
+        hull_roles: ['interdiction'],
+        tech_level: 't2',
         ...overrides,
     }
 }
@@ -1,3 +1,5 @@
+import type { ShipAttributes } from '../types'
+
 export function formatIsk(value: number): string {
     if (value >= 1e12) return (value / 1e12).toFixed(1) + 'T'
     if (value >= 1e9) return (value / 1e9).toFixed(1) + 'B'
@@ -57,6 +59,26 @@ export function getPortraitUrl(characterId: number, size = 32): string {
     return `https://images.evetech.net/characters/${characterId}/portrait?size=${size}`
 }
 
+/**
+ * Hull summary for a ship tooltip: slots and speeds on the first line, one
+ * tackle/e-war bonus per line after it.
+ */
+export function formatShipAttributes(ship: ShipAttributes): string {
+    const { high, mid, low } = ship.slots
+    const stats = [
+        `${high}H ${mid}M ${low}L`,
+        `${Math.round(ship.signature_radius)} m sig`,
+        `${Math.round(ship.max_velocity)} m/s`,
+        `${Number(ship.warp_speed.toFixed(2))} AU/s`,
+    ]
+    if (ship.covert_ops_cloak) stats.push('covert cloak')
+    if (ship.jump_drive) stats.push('jump drive')
+    return [
+        stats.join(' · '),
+        ...ship.bonuses.map((bonus) => bonus.text),
+    ].join('\n')
+}
+
 export function getShipIconUrl(typeId: number, size = 32): string {
     return `https://images.evetech.net/types/${typeId}/icon?size=${size}`
 }
@@ -10,6 +10,10 @@ function makeFlags(overrides: Partial<PilotFlags> = {}): PilotFlags {
         is_capital: false,
         is_super: false,
         is_solo: false,
+        is_tackle: false,
+        is_interdiction: false,
+        is_logistics: false,
+        is_ewar: false,
         ...overrides,
     }
 }
@@ -48,4 +52,16 @@ describe('getFlagLabels', () => {
             'BLACK OPS'
         )
     })
+
+    it('returns hull role flags', () => {
+        const labels = getFlagLabels(
+            makeFlags({
+                is_tackle: true,
+                is_interdiction: true,
+                is_logistics: true,
+                is_ewar: true,
+            })
+        )
+        expect(labels).toEqual(['DICTOR', 'TACKLE', 'EWAR', 'LOGI'])
+    })
 })
@@ -9,6 +9,10 @@ export function getFlagLabels(flags: PilotFlags): string[] {
     if (flags.is_blops) labels.push('BLACK OPS')
     if (flags.is_recon) labels.push('RECON')
     if (flags.is_cyno) labels.push('CYNO')
+    if (flags.is_interdiction) labels.push('DICTOR')
+    if (flags.is_tackle) labels.push('TACKLE')
+    if (flags.is_ewar) labels.push('EWAR')
+    if (flags.is_logistics) labels.push('LOGI')
 
     if (flags.is_solo) labels.push('SOLO')
 
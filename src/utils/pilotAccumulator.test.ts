@@ -23,6 +23,10 @@ function makePilot(id: number, threat: string, name?: string): PilotIntel {
             is_capital: false,
             is_super: false,
             is_solo: false,
+            is_tackle: false,
+            is_interdiction: false,
+            is_logistics: false,
+            is_ewar: false,
         },
         error: null,
     }
@@ -63,6 +63,10 @@ function makePilot(overrides: PilotOverrides = {}): PilotIntel {
             is_capital: false,
             is_super: false,
             is_solo: false,
+            is_tackle: false,
+            is_interdiction: false,
+            is_logistics: false,
+            is_ewar: false,
             ...overrides.flags,
         },
         error: null,
@@ -37,6 +37,10 @@ function makePilot(overrides: Partial<PilotIntel['flags']> = {}): PilotIntel {
             is_capital: false,
             is_super: false,
             is_solo: false,
+            is_tackle: false,
+            is_interdiction: false,
+            is_logistics: false,
+            is_ewar: false,
             ...overrides,
         },
         error: null,
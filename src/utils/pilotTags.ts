@@ -21,6 +21,10 @@ const FLAG_COLORS: Record<string, string> = {
     'BLACK OPS': '#6366F1',
     RECON: '#14B8A6',
     CYNO: '#A855F7',
+    DICTOR: '#EAB308',
+    TACKLE: '#F97316',
+    EWAR: '#EC4899',
+    LOGI: '#22C55E',
     SOLO: '#38BDF8',
 }
 